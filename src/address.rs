@@ -1,8 +1,9 @@
 use crate::bx;
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
 use crate::memory::{MemoryLocation, RuntimeMemoryManager};
-use crate::util::{get_usize, USIZE_BYTES};
+use crate::util::{get_u128, get_usize, USIZE_BYTES};
 use std::fmt::Debug;
-use std::fmt::Formatter;
+use std::fmt::{Display, Formatter};
 
 pub struct CloneableBox<T>
 where
@@ -24,6 +25,10 @@ impl<T: Clone + Debug> CloneableBox<T> {
         self.inner_box.as_ref()
     }
 
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner_box.as_mut()
+    }
+
     pub fn get(self) -> Box<T> {
         self.inner_box
     }
@@ -65,7 +70,7 @@ pub enum Address {
     ),
 }
 
-const ADDRESS_CODE_LENGTH: usize = 1;
+pub const ADDRESS_CODE_LENGTH: usize = 1;
 const IMMEDIATE_CODE: u8 = 0;
 const IMMEDIATE_INDEXED_CODE: u8 = 1;
 const STACK_DIRECT_CODE: u8 = 2;
@@ -85,12 +90,72 @@ impl Address {
             Address::StackDirect(address) | Address::StackIndirect(address) => {
                 *address += amount;
             }
+            Address::StackIndexed(location, offset) => {
+                //? Immediate locations are stack addresses stored as values
+                if let Address::Immediate(data) = location.get_mut() {
+                    let mut pointer = 0;
+                    let value = get_usize(&mut pointer, data) + amount;
+                    *data = Vec::from(value.to_le_bytes());
+                } else {
+                    location.get_mut().offset_if_stack(amount);
+                }
+                offset.get_mut().offset_if_stack(amount);
+            }
+            //? The frame, location and offset are read from addresses that may be on the stack
+            Address::HeapIndexed(frame, location, offset) => {
+                frame.get_mut().offset_if_stack(amount);
+                location.get_mut().offset_if_stack(amount);
+                offset.get_mut().offset_if_stack(amount);
+            }
             _ => {}
         }
     }
 
-    pub fn get_address_size(memory: &[u8], address: usize, expected_len: usize) -> usize {
-        match memory[address] {
+    /// Returns the address `amount` bytes after this one. Only supported for direct addresses
+    /// and indexed addresses with an immediate location
+    pub fn offset_direct(&self, amount: usize) -> Result<Address, String> {
+        fn offset_immediate(location: &Address, amount: usize) -> Option<Address> {
+            let Address::Immediate(data) = location else {
+                return None;
+            };
+            let value = get_usize(&mut 0, data) + amount;
+            Some(Address::Immediate(Vec::from(value.to_le_bytes())))
+        }
+
+        match self {
+            Address::StackDirect(address) => Ok(Address::StackDirect(address + amount)),
+            Address::HeapDirect((frame, address)) => {
+                Ok(Address::HeapDirect((*frame, address + amount)))
+            }
+            Address::StackIndexed(location, offset) => {
+                match offset_immediate(location.get_ref(), amount) {
+                    Some(location) => Ok(Address::StackIndexed(
+                        CloneableBox::new(location),
+                        offset.clone(),
+                    )),
+                    None => Err(format!("Cannot offset address {:?}", self)),
+                }
+            }
+            Address::HeapIndexed(frame, location, offset) => {
+                match offset_immediate(location.get_ref(), amount) {
+                    Some(location) => Ok(Address::HeapIndexed(
+                        frame.clone(),
+                        CloneableBox::new(location),
+                        offset.clone(),
+                    )),
+                    None => Err(format!("Cannot offset address {:?}", self)),
+                }
+            }
+            _ => Err(format!("Cannot offset address {:?}", self)),
+        }
+    }
+
+    pub fn get_address_size(
+        memory: &[u8],
+        address: usize,
+        expected_len: usize,
+    ) -> RuntimeResult<usize> {
+        Ok(match memory[address] {
             // ? Code + length
             IMMEDIATE_CODE => ADDRESS_CODE_LENGTH + expected_len,
             // ? Code + address length
@@ -102,20 +167,20 @@ impl Address {
             //? Code + location address length + offset address length
             IMMEDIATE_INDEXED_CODE | STACK_INDEXED_CODE => {
                 let mut p = address + ADDRESS_CODE_LENGTH;
-                p += Self::get_address_size(memory, p, USIZE_BYTES);
-                p += Self::get_address_size(memory, p, USIZE_BYTES);
+                p += Self::get_address_size(memory, p, USIZE_BYTES)?;
+                p += Self::get_address_size(memory, p, USIZE_BYTES)?;
                 p - address
             }
             //? Code + frame address length + location address length + offset address length
             HEAP_INDEXED_CODE => {
                 let mut p = address + ADDRESS_CODE_LENGTH;
-                p += Self::get_address_size(memory, p, USIZE_BYTES);
-                p += Self::get_address_size(memory, p, USIZE_BYTES);
-                p += Self::get_address_size(memory, p, USIZE_BYTES);
+                p += Self::get_address_size(memory, p, USIZE_BYTES)?;
+                p += Self::get_address_size(memory, p, USIZE_BYTES)?;
+                p += Self::get_address_size(memory, p, USIZE_BYTES)?;
                 p - address
             }
-            _ => panic!("Invalid address code!"),
-        }
+            code => return Err(RuntimeErrorKind::InvalidAddressCode(code)),
+        })
     }
 
     pub fn get_bytes(&self) -> Vec<u8> {
@@ -174,34 +239,83 @@ impl Address {
         match data[pointer - 1] {
             STACK_DIRECT_CODE => Ok(Address::StackDirect(get_usize(&mut pointer, data))),
             STACK_INDIRECT_CODE => Ok(Address::StackIndirect(get_usize(&mut pointer, data))),
-            STACK_INDEXED_CODE => {
-                todo!();
-            }
+            STACK_INDEXED_CODE => Ok(Address::StackIndexed(
+                CloneableBox::new(Self::from_bytes(data, &mut pointer, USIZE_BYTES)?),
+                CloneableBox::new(Self::from_bytes(data, &mut pointer, USIZE_BYTES)?),
+            )),
             IMMEDIATE_CODE | IMMEDIATE_INDEXED_CODE => {
                 Err("Address [Immediate] is not a stack address".to_string())
             }
             HEAP_DIRECT_CODE | HEAP_INDIRECT_CODE | HEAP_INDEXED_CODE => {
                 Err("Address [Heap] is not a stack address".to_string())
             }
-            _ => panic!("Invalid address code!"),
+            code => Err(RuntimeErrorKind::InvalidAddressCode(code).to_string()),
         }
     }
 
+    /// Reads an address from `data` without evaluating it, moving `pointer` to the end of the
+    /// address. Used for displaying compiled code so malformed data returns an error rather than
+    /// panicking
+    ///
+    /// `expected_len` is the length of immediate data
+    pub fn from_bytes(
+        data: &[u8],
+        pointer: &mut usize,
+        expected_len: usize,
+    ) -> Result<Address, String> {
+        fn read<'a>(data: &'a [u8], pointer: &mut usize, len: usize) -> Result<&'a [u8], String> {
+            let bytes = data.get(*pointer..*pointer + len).ok_or_else(|| {
+                format!("Address at [{:05}] runs past the end of memory", pointer)
+            })?;
+            *pointer += len;
+            Ok(bytes)
+        }
+        fn read_usize(data: &[u8], pointer: &mut usize) -> Result<usize, String> {
+            Ok(get_usize(&mut 0, read(data, pointer, USIZE_BYTES)?))
+        }
+        let next = |pointer: &mut usize| -> Result<CloneableBox<Address>, String> {
+            Ok(CloneableBox::new(Self::from_bytes(
+                data,
+                pointer,
+                USIZE_BYTES,
+            )?))
+        };
+
+        let code = read(data, pointer, ADDRESS_CODE_LENGTH)?[0];
+        Ok(match code {
+            IMMEDIATE_CODE => Address::Immediate(Vec::from(read(data, pointer, expected_len)?)),
+            IMMEDIATE_INDEXED_CODE => Address::ImmediateIndexed(next(pointer)?, next(pointer)?),
+            STACK_DIRECT_CODE => Address::StackDirect(read_usize(data, pointer)?),
+            STACK_INDIRECT_CODE => Address::StackIndirect(read_usize(data, pointer)?),
+            STACK_INDEXED_CODE => Address::StackIndexed(next(pointer)?, next(pointer)?),
+            HEAP_DIRECT_CODE => {
+                Address::HeapDirect((read_usize(data, pointer)?, read_usize(data, pointer)?))
+            }
+            HEAP_INDIRECT_CODE => {
+                Address::HeapIndirect((read_usize(data, pointer)?, read_usize(data, pointer)?))
+            }
+            HEAP_INDEXED_CODE => {
+                Address::HeapIndexed(next(pointer)?, next(pointer)?, next(pointer)?)
+            }
+            code => return Err(format!("Invalid address code [{}]", code)),
+        })
+    }
+
     // TODO: Properly support heap memory
     /// Evaluates a pointer to find the final data it points to.
     ///
     /// # Arguments
     /// - `pointer`: Represents address location - moved to point to end of address
     /// - `expected_len`: Length of the data. Ignored in direct and indirect addressing.
-    /// In indexed addressing refers to the size of a single item
+    ///   In indexed addressing refers to the size of a single item
     /// - `address_location`: Location of the address to evaluate
     pub fn evaluate_address(
         pointer: &mut usize,
         address_location: &MemoryLocation,
         expected_len: &usize,
         memory: &RuntimeMemoryManager,
-    ) -> (usize, MemoryLocation) {
-        let code = memory.get_byte(address_location, *pointer);
+    ) -> RuntimeResult<(usize, MemoryLocation)> {
+        let code = memory.get_byte(address_location, *pointer)?;
         *pointer += ADDRESS_CODE_LENGTH;
 
         match code {
@@ -209,26 +323,26 @@ impl Address {
                 // Increment pointer by immediate length, return start of immediate
                 // and the same location
                 *pointer += expected_len;
-                (*pointer - expected_len, address_location.clone())
+                Ok((*pointer - expected_len, address_location.clone()))
             }
             STACK_DIRECT_CODE => {
                 // Get pointer
                 let (address_memory, mut transformed_pointer) =
-                    memory.get_memory(address_location, *pointer);
+                    memory.get_memory(address_location, *pointer)?;
 
                 // ? Increment real pointer
                 *pointer += USIZE_BYTES;
 
                 // ? Return location (doesn't increment real pointer)
-                (
+                Ok((
                     get_usize(&mut transformed_pointer, address_memory),
                     MemoryLocation::Stack,
-                )
+                ))
             }
             STACK_INDIRECT_CODE => {
                 // Get pointer
                 let (address_memory, mut transformed_pointer) =
-                    memory.get_memory(address_location, *pointer);
+                    memory.get_memory(address_location, *pointer)?;
 
                 // ? Get next address (doesn't increment real pointer)
                 let mut next_address = get_usize(&mut transformed_pointer, address_memory);
@@ -248,29 +362,29 @@ impl Address {
             HEAP_DIRECT_CODE => {
                 // Get frame pointer
                 let (frame_memory, mut frame_pointer) =
-                    memory.get_memory(address_location, *pointer);
+                    memory.get_memory(address_location, *pointer)?;
 
                 // Get address pointer
                 let (address_memory, mut address_pointer) =
-                    memory.get_memory(address_location, *pointer + USIZE_BYTES);
+                    memory.get_memory(address_location, *pointer + USIZE_BYTES)?;
 
                 // ? Increment real pointer
                 *pointer += USIZE_BYTES + USIZE_BYTES;
 
                 // ? Return location (doesn't increment real pointer)
-                (
+                Ok((
                     get_usize(&mut address_pointer, address_memory),
                     MemoryLocation::Heap(get_usize(&mut frame_pointer, frame_memory)),
-                )
+                ))
             }
             HEAP_INDIRECT_CODE => {
                 // Get frame pointer
                 let (frame_memory, mut frame_pointer) =
-                    memory.get_memory(address_location, *pointer);
+                    memory.get_memory(address_location, *pointer)?;
 
                 // Get address pointer
                 let (address_memory, mut address_pointer) =
-                    memory.get_memory(address_location, *pointer + USIZE_BYTES);
+                    memory.get_memory(address_location, *pointer + USIZE_BYTES)?;
 
                 // ? Get next frame (doesn't increment real pointer)
                 let next_frame = get_usize(&mut frame_pointer, frame_memory);
@@ -297,11 +411,11 @@ impl Address {
                     address_location,
                     &USIZE_BYTES, // ? Expecting usize (address)
                     memory,
-                ); // ? pointer incremented here
+                )?; // ? pointer incremented here
 
                 // Get memory at location address
                 let (address_memory, mut transformed_pointer) =
-                    memory.get_memory(&location_memory_location, location_address);
+                    memory.get_memory(&location_memory_location, location_address)?;
 
                 // Get location
                 let location = get_usize(&mut transformed_pointer, address_memory);
@@ -312,23 +426,23 @@ impl Address {
                     address_location,
                     &USIZE_BYTES, // ? Expecting usize (address)
                     memory,
-                ); // ? pointer incremented here
+                )?; // ? pointer incremented here
 
                 // Get memory at offset address
                 let (address_memory, mut transformed_pointer) =
-                    memory.get_memory(&offset_memory_location, offset_address);
+                    memory.get_memory(&offset_memory_location, offset_address)?;
 
                 // Get offset
                 let offset = get_usize(&mut transformed_pointer, address_memory);
 
-                (
+                Ok((
                     location + (offset * expected_len),
                     match code {
                         IMMEDIATE_INDEXED_CODE => MemoryLocation::Program,
                         STACK_INDEXED_CODE => MemoryLocation::Stack,
-                        _ => panic!(),
+                        _ => unreachable!(),
                     },
-                )
+                ))
             }
             HEAP_INDEXED_CODE => {
                 // Get frame address using normal evaluate
@@ -337,11 +451,11 @@ impl Address {
                     address_location,
                     &USIZE_BYTES, // ? Expecting usize (address)
                     memory,
-                ); // ? pointer incremented here
+                )?; // ? pointer incremented here
 
                 // Get memory at frame address
                 let (address_memory, mut transformed_pointer) =
-                    memory.get_memory(&frame_memory_location, frame_address);
+                    memory.get_memory(&frame_memory_location, frame_address)?;
 
                 // Get frame
                 let frame = get_usize(&mut transformed_pointer, address_memory);
@@ -352,11 +466,11 @@ impl Address {
                     address_location,
                     &USIZE_BYTES, // ? Expecting usize (address)
                     memory,
-                ); // ? pointer incremented here
+                )?; // ? pointer incremented here
 
                 // Get memory at location address
                 let (address_memory, mut transformed_pointer) =
-                    memory.get_memory(&location_memory_location, location_address);
+                    memory.get_memory(&location_memory_location, location_address)?;
 
                 // Get location
                 let location = get_usize(&mut transformed_pointer, address_memory);
@@ -367,21 +481,21 @@ impl Address {
                     address_location,
                     &USIZE_BYTES, // ? Expecting usize (address)
                     memory,
-                ); // ? pointer incremented here
+                )?; // ? pointer incremented here
 
                 // Get memory at offset address
                 let (address_memory, mut transformed_pointer) =
-                    memory.get_memory(&offset_memory_location, offset_address);
+                    memory.get_memory(&offset_memory_location, offset_address)?;
 
                 // Get offset
                 let offset = get_usize(&mut transformed_pointer, address_memory);
 
-                (
+                Ok((
                     location + (offset * expected_len),
                     MemoryLocation::Heap(frame),
-                )
+                ))
             }
-            _ => panic!("Invalid address code!"),
+            code => Err(RuntimeErrorKind::InvalidAddressCode(code)),
         }
     }
 
@@ -390,10 +504,59 @@ impl Address {
         address_location: &MemoryLocation,
         expected_len: &usize,
         memory: &'a RuntimeMemoryManager,
-    ) -> &'a [u8] {
+    ) -> RuntimeResult<&'a [u8]> {
         let (address, location) =
-            Self::evaluate_address(pointer, address_location, expected_len, memory);
+            Self::evaluate_address(pointer, address_location, expected_len, memory)?;
 
         memory.get_data(&location, address, *expected_len)
     }
 }
+
+//? `S` - stack, `H{frame}` - heap frame, `P` - program memory, `#` - immediate, `*` - indirect
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Address::Immediate(data) if data.len() <= 16 => {
+                write!(f, "#{}", get_u128(data))
+            }
+            Address::Immediate(data) => {
+                write!(f, "#0x")?;
+                for byte in data.iter().rev() {
+                    write!(f, "{:02X}", byte)?;
+                }
+                Ok(())
+            }
+            Address::ImmediateIndexed(location, offset) => {
+                write!(f, "P[{} + {}]", location.get_ref(), offset.get_ref())
+            }
+            Address::StackDirect(address) => write!(f, "S[{}]", address),
+            Address::StackIndirect(address) => write!(f, "*S[{}]", address),
+            Address::StackIndexed(location, offset) => {
+                write!(f, "S[{} + {}]", location.get_ref(), offset.get_ref())
+            }
+            Address::HeapDirect((frame, address)) => write!(f, "H{{{}}}[{}]", frame, address),
+            Address::HeapIndirect((frame, address)) => write!(f, "*H{{{}}}[{}]", frame, address),
+            Address::HeapIndexed(frame, location, offset) => write!(
+                f,
+                "H{{{}}}[{} + {}]",
+                frame.get_ref(),
+                location.get_ref(),
+                offset.get_ref()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_address_codes() {
+        assert!(matches!(
+            Address::get_address_size(&[0xFF], 0, USIZE_BYTES),
+            Err(RuntimeErrorKind::InvalidAddressCode(0xFF))
+        ));
+        assert!(Address::stack_address_from_bytes(0, &[0xFF]).is_err());
+    }
+}
@@ -1,22 +1,253 @@
-use crate::processing::preprocessor::SymbolData;
+use crate::processing::preprocessor::COMMENT_CHARACTER;
 use crate::processing::symbols::{Literal, Operator, TypeSymbol};
 use crate::processing::types::Type;
+use std::fmt::{Display, Formatter};
 
-/// Takes zero-indexed line
-pub fn create_line_error<T>(
-    error: String,
-    line: usize,
-    symbol_data: &SymbolData,
-) -> Result<T, String> {
-    Err(format!("{}: {}", symbol_data.get_error_path(line), error))
+/// Category of a compile error, shown as e.g. `error[E0005]`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorCode {
+    /// Line couldn't be split into symbols
+    Syntax,
+    Indentation,
+    Import,
+    /// Line didn't match any line handler
+    UnmatchedLine,
+    /// Line matched but couldn't be compiled for a reason without a more specific code
+    Line,
+    /// Block couldn't be exited
+    Block,
+    /// Name doesn't refer to a variable, function, class or module in scope
+    UnknownReference,
+    /// Value of one type used where another is expected, including operators that aren't
+    /// implemented for the types given
+    TypeMismatch,
+    /// Name declared twice in the same scope
+    DuplicateName,
 }
 
-pub fn create_simple_line_error<T>(
-    error: String,
-    line: usize,
-    file_name: &str,
-) -> Result<T, String> {
-    Err(format!("{} - Line {}: {}", file_name, line + 1, error))
+impl ErrorCode {
+    pub fn get_number(&self) -> usize {
+        match self {
+            ErrorCode::Syntax => 1,
+            ErrorCode::Indentation => 2,
+            ErrorCode::Import => 3,
+            ErrorCode::UnmatchedLine => 4,
+            ErrorCode::Line => 5,
+            ErrorCode::Block => 6,
+            ErrorCode::UnknownReference => 7,
+            ErrorCode::TypeMismatch => 8,
+            ErrorCode::DuplicateName => 9,
+        }
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "E{:04}", self.get_number())
+    }
+}
+
+/// Result of compiling part of a line
+pub type CompileResult<T> = Result<T, CompileError>;
+
+/// Error created while compiling a line. Carries its code and the name it's about (if any) so
+/// that the diagnostic can underline the name and point to where it was declared
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompileError {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Name used in the line that the error is about e.g. a reference that wasn't found
+    pub name: Option<Vec<String>>,
+}
+
+impl CompileError {
+    pub fn new(code: ErrorCode, message: String) -> Self {
+        Self {
+            code,
+            message,
+            name: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &[String]) -> Self {
+        self.name = Some(name.to_vec());
+        self
+    }
+}
+
+//? Errors without a more specific code are line errors
+impl From<String> for CompileError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Line, message)
+    }
+}
+
+impl From<&str> for CompileError {
+    fn from(message: &str) -> Self {
+        Self::from(message.to_string())
+    }
+}
+
+//? Allows errors to be returned from code that reports them as messages e.g. the preprocessor
+impl From<CompileError> for String {
+    fn from(error: CompileError) -> Self {
+        error.message
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Byte range of a symbol within its source line
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Span covering `line` excluding leading and trailing whitespace and comments
+    pub fn of_line(line: &str) -> Self {
+        let code = match line.find(COMMENT_CHARACTER) {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        let end = code.trim_end().len();
+        let start = code.len() - code.trim_start().len();
+        Self::new(start.min(end), end)
+    }
+}
+
+/// Position in a source file that a diagnostic refers to
+#[derive(Clone, Debug)]
+pub struct SourceLocation {
+    pub file_name: String,
+    /// Zero-indexed
+    pub line_index: usize,
+    pub source: String,
+    pub span: Span,
+}
+
+impl SourceLocation {
+    pub fn new(file_name: String, line_index: usize, source: String, span: Span) -> Self {
+        Self {
+            file_name,
+            line_index,
+            source,
+            span,
+        }
+    }
+
+    /// Renders the location followed by the source line with the span underlined e.g.
+    /// ```text
+    ///   --> main.why:3:7
+    ///    |
+    ///  3 | print x + 1
+    ///    |       ^
+    /// ```
+    fn render(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let line_number = (self.line_index + 1).to_string();
+        let gutter = " ".repeat(line_number.len());
+        //? Tabs are rendered as single spaces so that carets line up
+        let source = self.source.replace('\t', " ");
+        let start = self.span.start.min(source.len());
+        let width = self.span.end.saturating_sub(start).max(1);
+
+        writeln!(
+            f,
+            "{} --> {}:{}:{}",
+            gutter,
+            self.file_name,
+            line_number,
+            source[..start].chars().count() + 1
+        )?;
+        writeln!(f, "{}  |", gutter)?;
+        writeln!(f, " {} | {}", line_number, source)?;
+        write!(
+            f,
+            "{}  | {}{}",
+            gutter,
+            " ".repeat(source[..start].chars().count()),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.render(f)
+    }
+}
+
+/// Additional information attached to a `Diagnostic` e.g. where a variable was declared
+#[derive(Clone, Debug)]
+pub struct Note {
+    pub message: String,
+    pub location: Option<SourceLocation>,
+}
+
+/// Compile error with an error code, the offending source and any notes
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub code: ErrorCode,
+    pub message: String,
+    //? Boxed to keep `Result<_, Diagnostic>` small
+    pub location: Option<Box<SourceLocation>>,
+    pub notes: Vec<Note>,
+}
+
+impl Diagnostic {
+    pub fn new(code: ErrorCode, message: String, location: Option<SourceLocation>) -> Self {
+        Self {
+            code,
+            message,
+            location: location.map(Box::new),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_note(mut self, message: String, location: Option<SourceLocation>) -> Self {
+        self.notes.push(Note { message, location });
+        self
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error[{}]: {}", self.code, self.message)?;
+        if let Some(location) = &self.location {
+            writeln!(f)?;
+            location.render(f)?;
+        }
+        for note in &self.notes {
+            write!(f, "\nnote: {}", note.message)?;
+            if let Some(location) = &note.location {
+                writeln!(f)?;
+                location.render(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders every diagnostic followed by a count e.g. `aborting due to 3 errors`
+pub fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    let mut output = String::new();
+    for diagnostic in diagnostics {
+        output += &format!("{}\n\n", diagnostic);
+    }
+    output += &match diagnostics.len() {
+        1 => "aborting due to 1 error".to_string(),
+        count => format!("aborting due to {} errors", count),
+    };
+    output
 }
 
 /// Creates an error explaining that the operator isn't implemented for the given type
@@ -31,17 +262,20 @@ pub fn create_op_not_impl_error<T>(
     operator: &Operator,
     lhs: TypeSymbol,
     rhs: Option<&dyn Type>,
-) -> Result<T, String> {
+) -> CompileResult<T> {
     match rhs {
-        Some(rhs) => Err(format!(
-            "'{}' operator not implemented for '{}' and '{}'",
-            operator,
-            lhs,
-            rhs.get_type_symbol()
+        Some(rhs) => Err(CompileError::new(
+            ErrorCode::TypeMismatch,
+            format!(
+                "'{}' operator not implemented for '{}' and '{}'",
+                operator,
+                lhs,
+                rhs.get_type_symbol()
+            ),
         )),
-        None => Err(format!(
-            "'{}' operator not implemented for '{}'",
-            operator, lhs
+        None => Err(CompileError::new(
+            ErrorCode::TypeMismatch,
+            format!("'{}' operator not implemented for '{}'", operator, lhs),
         )),
     }
 }
@@ -53,9 +287,97 @@ pub fn create_op_not_impl_error<T>(
 pub fn create_literal_not_impl_error<T>(
     literal: &Literal,
     type_symbol: TypeSymbol,
-) -> Result<T, String> {
-    Err(format!(
-        "{} literal cannot be used to initialise {}",
-        literal, type_symbol
+) -> CompileResult<T> {
+    Err(CompileError::new(
+        ErrorCode::TypeMismatch,
+        format!(
+            "{} literal cannot be used to initialise {}",
+            literal, type_symbol
+        ),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::compile_err;
+
+    fn get_codes(source: &str) -> Vec<ErrorCode> {
+        compile_err(source).iter().map(|e| e.code).collect()
+    }
+
+    #[test]
+    fn unknown_references() {
+        assert_eq!(get_codes("int x = y\n"), [ErrorCode::UnknownReference]);
+        assert_eq!(
+            get_codes("fn foo(bogus b)\n    int x = 1\n"),
+            [ErrorCode::UnknownReference]
+        );
+    }
+
+    #[test]
+    fn type_mismatches() {
+        assert_eq!(get_codes("bool b = 'a'\n"), [ErrorCode::TypeMismatch]);
+        assert_eq!(
+            get_codes("int x = 1\nbool b = x\n"),
+            [ErrorCode::TypeMismatch]
+        );
+        assert_eq!(get_codes("int x = 1 + true\n"), [ErrorCode::TypeMismatch]);
+        assert_eq!(
+            get_codes("while 5\n    int x = 1\n"),
+            [ErrorCode::TypeMismatch]
+        );
+    }
+
+    #[test]
+    fn duplicate_names() {
+        assert_eq!(
+            get_codes("int x = 1\nint x = 2\n"),
+            [ErrorCode::DuplicateName]
+        );
+        assert_eq!(
+            get_codes("class P\n    int x\n    int x\n"),
+            [ErrorCode::DuplicateName]
+        );
+    }
+
+    #[test]
+    fn other_errors_keep_their_codes() {
+        assert_eq!(get_codes("fn foo\n    int x = 1\n"), [ErrorCode::Line]);
+        assert_eq!(
+            get_codes("int x = 1\n    int y = 2\n"),
+            [ErrorCode::Indentation]
+        );
+    }
+
+    #[test]
+    fn names_are_underlined() {
+        let errors = compile_err("int x = 1\nint y = x + z\n");
+        assert_eq!(errors[0].code, ErrorCode::UnknownReference);
+        assert_eq!(errors[0].location.as_ref().unwrap().span, Span::new(12, 13));
+        assert!(errors[0].notes.is_empty());
+
+        let errors = compile_err("int x = 1\nbool b = x\n");
+        assert_eq!(
+            errors[0].location.as_ref().unwrap().span,
+            Span::of_line("bool b = x")
+        );
+    }
+
+    #[test]
+    fn duplicates_point_to_declaration() {
+        let errors = compile_err("int x = 1\nint y = 2\nint x = 3\n");
+        assert_eq!(errors[0].code, ErrorCode::DuplicateName);
+        assert_eq!(errors[0].location.as_ref().unwrap().span, Span::new(4, 5));
+        assert_eq!(errors[0].notes[0].message, "'x' declared here");
+        assert_eq!(errors[0].notes[0].location.as_ref().unwrap().line_index, 0);
+    }
+
+    #[test]
+    fn messages_are_line_errors() {
+        assert_eq!(ErrorCode::UnknownReference.to_string(), "E0007");
+        let error = CompileError::from("Invalid".to_string());
+        assert_eq!(error.code, ErrorCode::Line);
+        assert_eq!(error.name, None);
+    }
+}
@@ -0,0 +1,453 @@
+use super::{
+    execute_step, format_runtime_error, get_instruction_code, RuntimeError, RuntimeErrorKind,
+};
+use crate::address::Address;
+use crate::col_println;
+use crate::memory::{
+    format_frame_id, DebugInfo, DebugVariable, MemoryLocation, RuntimeMemoryManager,
+};
+use crate::processing::instructions::print_22::PrintFormat;
+use crate::translator::translate_instruction;
+use crate::util::warn;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::io::{stdin, stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const HELP: &str = "Commands:
+    break <location>     (b) Add a breakpoint
+    delete [location]    (d) Remove a breakpoint, or all breakpoints
+    breakpoints          List breakpoints
+    step [count]         (s) Execute one (or `count`) instructions
+    next                 (n) Execute until the next source line
+    continue             (c) Execute until a breakpoint is hit
+    where                (w) Show the current instruction and source line
+    print <name>         (p) Show the value of a variable
+    locals               (l) Show the value of all variables in scope
+    stack                Show all stack frames, newest first
+    heap                 Show all heap frames
+    quit                 (q) Stop debugging
+Locations are a source line (`12` or `main.why:12`) or a program offset (`*120`)";
+
+/// Number of bytes shown per row when displaying frames
+const ROW_LENGTH: usize = 16;
+
+/// Whether execution can continue after a step
+enum StepResult {
+    Running,
+    Stopped,
+}
+
+/// Interactive debugger that executes a program one instruction at a time
+pub struct Debugger<'a> {
+    memory: &'a mut RuntimeMemoryManager,
+    debug_info: &'a DebugInfo,
+    pointer: usize,
+    breakpoints: BTreeSet<usize>,
+    /// Error that halted execution. Memory can still be inspected after an error
+    error: Option<RuntimeError>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(memory: &'a mut RuntimeMemoryManager, debug_info: &'a DebugInfo) -> Self {
+        Self {
+            pointer: memory.entry_point(),
+            memory,
+            debug_info,
+            breakpoints: BTreeSet::new(),
+            error: None,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.pointer >= self.memory.program_memory().len()
+    }
+
+    /// Runs the debugger until the user quits. Returns the error that halted execution, if any
+    pub fn run(mut self, exit: &AtomicBool) -> Result<(), RuntimeError> {
+        if self.debug_info.is_empty() {
+            warn("No debug info found - source lines and variables are unavailable");
+        }
+        println!("Debugging program - type `help` for a list of commands");
+        self.print_location();
+
+        loop {
+            print!("(debug) ");
+            stdout().flush().expect("Stdout flush failed");
+            let mut line = String::new();
+            //? Stop at the end of input
+            if stdin().read_line(&mut line).expect("Stdin read failed") == 0 {
+                break;
+            }
+
+            let mut parts = line.split_whitespace();
+            let Some(command) = parts.next() else {
+                continue;
+            };
+            let argument = parts.next();
+
+            let result = match command {
+                "help" | "h" => {
+                    println!("{}", HELP);
+                    Ok(())
+                }
+                "break" | "b" => self.add_breakpoint(argument),
+                "delete" | "d" => self.remove_breakpoint(argument),
+                "breakpoints" => {
+                    self.print_breakpoints();
+                    Ok(())
+                }
+                "step" | "s" => self.step(argument, exit),
+                "next" | "n" => self.next(exit),
+                "continue" | "c" => self.resume(exit),
+                "where" | "w" => {
+                    self.print_location();
+                    Ok(())
+                }
+                "print" | "p" => self.print_variable(argument),
+                "locals" | "l" => {
+                    self.print_locals();
+                    Ok(())
+                }
+                "stack" => {
+                    self.print_stack();
+                    Ok(())
+                }
+                "heap" => {
+                    self.print_heap();
+                    Ok(())
+                }
+                "quit" | "q" => break,
+                command => Err(format!(
+                    "Unknown command '{}' - type `help` for a list of commands",
+                    command
+                )),
+            };
+
+            if let Err(e) = result {
+                col_println!(red, "{}", e);
+            }
+        }
+
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Converts a location (`12`, `main.why:12` or `*120`) to a program offset
+    fn parse_location(&self, location: Option<&str>) -> Result<usize, String> {
+        let location = location.ok_or("Expected a location")?;
+
+        if let Some(offset) = location.strip_prefix('*') {
+            return offset
+                .parse()
+                .map_err(|_| format!("Invalid program offset '{}'", offset));
+        }
+
+        let (file_name, line) = match location.rsplit_once(':') {
+            Some((file_name, line)) => (Some(file_name), line),
+            None => (None, location),
+        };
+        let line_index = match line.parse::<usize>() {
+            Ok(line) if line > 0 => line - 1,
+            _ => return Err(format!("Invalid line number '{}'", line)),
+        };
+
+        self.debug_info
+            .get_line_offset(file_name, line_index)
+            .ok_or(format!("No code found for line '{}'", location))
+    }
+
+    /// Describes the source line of an offset e.g. `[00120] main.why:12 in add`
+    fn describe_offset(&self, offset: usize) -> String {
+        let mut description = format!("[{:05}]", offset);
+        if let Some(line) = self.debug_info.get_line(offset) {
+            description += &format!(" {}", self.debug_info.format_line(line));
+        }
+        if let Some(function) = self.debug_info.get_function(offset) {
+            description += &format!(" in {}", function.name);
+        }
+        description
+    }
+
+    fn add_breakpoint(&mut self, location: Option<&str>) -> Result<(), String> {
+        let offset = self.parse_location(location)?;
+        if offset >= self.memory.program_memory().len() {
+            return Err(format!("Program offset [{:05}] is out of range", offset));
+        }
+        self.breakpoints.insert(offset);
+        println!("Breakpoint added at {}", self.describe_offset(offset));
+        Ok(())
+    }
+
+    fn remove_breakpoint(&mut self, location: Option<&str>) -> Result<(), String> {
+        if location.is_none() {
+            self.breakpoints.clear();
+            println!("All breakpoints removed");
+            return Ok(());
+        }
+
+        let offset = self.parse_location(location)?;
+        if !self.breakpoints.remove(&offset) {
+            return Err(format!("No breakpoint at {}", self.describe_offset(offset)));
+        }
+        println!("Breakpoint removed at {}", self.describe_offset(offset));
+        Ok(())
+    }
+
+    fn print_breakpoints(&self) {
+        if self.breakpoints.is_empty() {
+            println!("No breakpoints");
+        }
+        for offset in &self.breakpoints {
+            println!("{}", self.describe_offset(*offset));
+        }
+    }
+
+    /// Executes a single instruction
+    fn step_instruction(&mut self, exit: &AtomicBool) -> Result<StepResult, String> {
+        if let Some(e) = &self.error {
+            return Err(format!("Execution halted by an error - {}", e));
+        }
+        if self.is_finished() {
+            return Err("Program has finished executing".to_string());
+        }
+
+        let instruction_pointer = self.pointer;
+        if let Err(e) = execute_step(self.memory, &mut self.pointer) {
+            col_println!(
+                (red, bold),
+                "Execution failed:\n{}",
+                format_runtime_error(&e, self.debug_info)
+            );
+            self.error = Some(e);
+            return Ok(StepResult::Stopped);
+        }
+
+        //? Ctrl+C returns to the debugger instead of terminating the program
+        if exit.swap(false, Ordering::Relaxed) {
+            let code = get_instruction_code(self.memory, instruction_pointer);
+            println!(
+                "\n{}",
+                RuntimeError::new(RuntimeErrorKind::Terminated, instruction_pointer, code)
+            );
+            return Ok(StepResult::Stopped);
+        }
+
+        if self.is_finished() {
+            col_println!((green, bold), "\nExecution completed");
+            if self.memory.stack_memory().get_current_level() != 0 {
+                warn("Execution ended with a non-zero stack level")
+            }
+            return Ok(StepResult::Stopped);
+        }
+
+        Ok(StepResult::Running)
+    }
+
+    fn step(&mut self, count: Option<&str>, exit: &AtomicBool) -> Result<(), String> {
+        let count = match count {
+            None => 1,
+            Some(count) => count
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid step count '{}'", count))?,
+        };
+
+        for _ in 0..count {
+            if let StepResult::Stopped = self.step_instruction(exit)? {
+                return Ok(());
+            }
+        }
+        self.print_location();
+        Ok(())
+    }
+
+    /// Executes until the start of a different source line or a breakpoint
+    fn next(&mut self, exit: &AtomicBool) -> Result<(), String> {
+        let start_line = self
+            .debug_info
+            .get_line(self.pointer)
+            .map(|line| line.offset);
+
+        loop {
+            if let StepResult::Stopped = self.step_instruction(exit)? {
+                return Ok(());
+            }
+            let line = self
+                .debug_info
+                .get_line(self.pointer)
+                .map(|line| line.offset);
+            if line != start_line || self.breakpoints.contains(&self.pointer) {
+                break;
+            }
+        }
+        self.print_location();
+        Ok(())
+    }
+
+    /// Executes until a breakpoint is hit
+    fn resume(&mut self, exit: &AtomicBool) -> Result<(), String> {
+        loop {
+            if let StepResult::Stopped = self.step_instruction(exit)? {
+                return Ok(());
+            }
+            if self.breakpoints.contains(&self.pointer) {
+                println!("Breakpoint hit");
+                break;
+            }
+        }
+        self.print_location();
+        Ok(())
+    }
+
+    /// Shows the next instruction to execute and the line it was compiled from
+    fn print_location(&self) {
+        if self.is_finished() {
+            println!("Program has finished executing");
+            return;
+        }
+
+        let instruction = match translate_instruction(self.memory.program_memory(), self.pointer) {
+            Ok((instruction, _)) => instruction,
+            Err(e) => format!("<{}>", e),
+        };
+        col_println!(
+            bold,
+            "{} | {}",
+            self.describe_offset(self.pointer),
+            instruction
+        );
+
+        if let Some(line) = self.debug_info.get_line(self.pointer) {
+            println!("{:>5} | {}", line.line_index + 1, line.source);
+        }
+    }
+
+    /// Returns the raw data of a variable
+    fn get_variable_data<'b>(&'b self, variable: &'b DebugVariable) -> Result<&'b [u8], String> {
+        let (location, address) = match &variable.address {
+            Address::Immediate(data) => return Ok(data),
+            Address::StackDirect(address) => (MemoryLocation::Stack, *address),
+            Address::HeapDirect((frame, address)) => (MemoryLocation::Heap(*frame), *address),
+            address => {
+                return Err(format!(
+                    "Reading variables with address {:?} is unsupported",
+                    address
+                ))
+            }
+        };
+        self.memory
+            .get_data(&location, address, variable.size)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Formats a variable's value according to its type
+    fn format_variable(&self, variable: &DebugVariable) -> String {
+        let data = match self.get_variable_data(variable) {
+            Ok(data) => data,
+            Err(e) => return format!("<{}>", e),
+        };
+        let value = match PrintFormat::from_type_symbol(&variable.type_symbol) {
+            Some(PrintFormat::Character) => format!("{:?}", PrintFormat::Character.format(data)),
+            Some(PrintFormat::String) => format!("{:?}", PrintFormat::String.format(data)),
+            Some(format) => format.format(data),
+            //? Fields are shown as separate variables
+            None => format!("<{} bytes>", data.len()),
+        };
+        format!(
+            "{}: {} = {}",
+            variable.name,
+            variable.type_symbol.get_code_representation(),
+            value
+        )
+    }
+
+    /// Shows a variable and its fields
+    fn print_variable(&self, name: Option<&str>) -> Result<(), String> {
+        let name = name.ok_or("Expected a variable name")?;
+        let variables = self.debug_info.get_variables_in_scope(self.pointer);
+
+        //? Variables are ordered innermost first so the first match is the visible one
+        let variable = variables
+            .iter()
+            .find(|variable| variable.name == name)
+            .ok_or(format!("No variable '{}' in scope", name))?;
+        println!("{}", self.format_variable(variable));
+
+        let field_prefix = format!("{}.", name);
+        for field in variables
+            .iter()
+            .filter(|field| field.name.starts_with(&field_prefix))
+            .filter(|field| field.scope == variable.scope)
+        {
+            println!("    {}", self.format_variable(field));
+        }
+        Ok(())
+    }
+
+    fn print_locals(&self) {
+        let variables = self.debug_info.get_variables_in_scope(self.pointer);
+        if variables.is_empty() {
+            println!("No variables in scope");
+        }
+        let mut shown: Vec<&str> = Vec::new();
+        for variable in variables {
+            //? Skip shadowed variables
+            if shown.contains(&variable.name.as_str()) {
+                continue;
+            }
+            shown.push(&variable.name);
+            println!("{}", self.format_variable(variable));
+        }
+    }
+
+    fn print_stack(&mut self) {
+        let stacks: Vec<String> = self
+            .memory
+            .stack_memory()
+            .get_stacks()
+            .map(format_frame)
+            .collect();
+        if stacks.is_empty() {
+            println!("No stack frames");
+        }
+        for (i, stack) in stacks.iter().enumerate() {
+            println!("Stack frame {}:\n{}", i, stack);
+        }
+    }
+
+    fn print_heap(&mut self) {
+        let frames: Vec<(usize, String)> = self
+            .memory
+            .heap_memory()
+            .get_frames()
+            .map(|(frame, data)| (frame, format_frame(data)))
+            .collect();
+        if frames.is_empty() {
+            println!("No heap frames");
+        }
+        for (frame, data) in frames {
+            println!("Heap {}:\n{}", format_frame_id(frame), data);
+        }
+    }
+}
+
+/// Formats a frame as rows of hex bytes prefixed with their address
+fn format_frame(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "    <empty>".to_string();
+    }
+
+    let mut output = String::new();
+    for (i, row) in data.chunks(ROW_LENGTH).enumerate() {
+        if i != 0 {
+            output.push('\n');
+        }
+        write!(output, "    [{:05}]", i * ROW_LENGTH).unwrap();
+        for byte in row {
+            write!(output, " {:02X}", byte).unwrap();
+        }
+    }
+    output
+}
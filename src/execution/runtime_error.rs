@@ -0,0 +1,124 @@
+use crate::memory::format_frame_id;
+use crate::processing::instructions::{get_instruction_name, InstructionCodeType};
+use std::fmt::{Display, Formatter};
+
+/// Result of an operation that may fault while a program is executing
+pub type RuntimeResult<T> = Result<T, RuntimeErrorKind>;
+
+/// Reason a program was halted while executing
+#[derive(Clone, Debug, PartialEq)]
+pub enum RuntimeErrorKind {
+    /// Stack address (relative to the newest stack) is past the end of the oldest stack
+    IndexOutOfStack(usize),
+    /// Stack down when there are no stacks
+    NoStack,
+    /// Heap frame id that was never allocated
+    FrameNotInHeap(usize),
+    /// Heap frame id of a frame that has been freed
+    UseAfterFree(usize),
+    /// Freeing a heap frame that has already been freed
+    DoubleFree(usize),
+    /// Address range outside of a heap frame - (frame, start, end)
+    IndexOutOfFrame(usize, usize, usize),
+    /// Heap frame size that is negative (as a signed integer) or too large to allocate
+    InvalidFrameSize(usize),
+    /// Array index outside of the array - (index, length)
+    IndexOutOfBounds(i128, i128),
+    /// Address range outside of the program memory - (start, end)
+    IndexOutOfProgram(usize, usize),
+    InvalidAddressCode(u8),
+    OverwritingProgramMemory,
+    DivisionByZero,
+    UnknownPrintFormat(u8),
+    InvalidInput(String),
+    /// Reading from stdin or writing to stdout failed
+    Io(String),
+    UnknownInstruction,
+    Terminated,
+}
+
+impl Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeErrorKind::IndexOutOfStack(address) => {
+                write!(f, "Index out of stack! [{}]", address)
+            }
+            RuntimeErrorKind::NoStack => write!(f, "Tried to stack down when there are no stacks!"),
+            RuntimeErrorKind::FrameNotInHeap(frame) => {
+                write!(f, "Frame not in Heap! [{}]", format_frame_id(*frame))
+            }
+            RuntimeErrorKind::UseAfterFree(frame) => {
+                write!(f, "Use of freed Heap frame! [{}]", format_frame_id(*frame))
+            }
+            RuntimeErrorKind::DoubleFree(frame) => {
+                write!(f, "Heap frame freed twice! [{}]", format_frame_id(*frame))
+            }
+            RuntimeErrorKind::IndexOutOfFrame(frame, start, end) => write!(
+                f,
+                "Index out of Heap frame! [{}..{} in {}]",
+                start,
+                end,
+                format_frame_id(*frame)
+            ),
+            RuntimeErrorKind::InvalidFrameSize(size) => {
+                write!(f, "Invalid Heap frame size! [{}]", *size as isize)
+            }
+            RuntimeErrorKind::IndexOutOfBounds(index, length) => {
+                write!(f, "Index out of bounds! [{} not in 0..{}]", index, length)
+            }
+            RuntimeErrorKind::IndexOutOfProgram(start, end) => {
+                write!(f, "Index out of program memory! [{}..{}]", start, end)
+            }
+            RuntimeErrorKind::InvalidAddressCode(code) => {
+                write!(f, "Invalid address code! [{}]", code)
+            }
+            RuntimeErrorKind::OverwritingProgramMemory => {
+                write!(f, "Overwriting program memory is forbidden!")
+            }
+            RuntimeErrorKind::DivisionByZero => write!(f, "Division by zero!"),
+            RuntimeErrorKind::UnknownPrintFormat(format) => {
+                write!(f, "Unknown print format! [{}]", format)
+            }
+            RuntimeErrorKind::InvalidInput(e) => write!(f, "Invalid input: {}", e),
+            RuntimeErrorKind::Io(e) => write!(f, "IO error: {}", e),
+            RuntimeErrorKind::UnknownInstruction => write!(f, "Unknown instruction code!"),
+            RuntimeErrorKind::Terminated => write!(f, "Program terminated by Ctrl+C"),
+        }
+    }
+}
+
+/// Fault raised while executing a program, along with the instruction that caused it
+#[derive(Clone, Debug)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    /// Position of the start of the faulting instruction (including its code) in program memory
+    pub instruction_pointer: usize,
+    pub opcode: InstructionCodeType,
+}
+
+impl RuntimeError {
+    pub fn new(
+        kind: RuntimeErrorKind,
+        instruction_pointer: usize,
+        opcode: InstructionCodeType,
+    ) -> Self {
+        Self {
+            kind,
+            instruction_pointer,
+            opcode,
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({} [{}] at [{:05}])",
+            self.kind,
+            get_instruction_name(self.opcode).unwrap_or("instruction"),
+            self.opcode,
+            self.instruction_pointer
+        )
+    }
+}
@@ -6,30 +6,43 @@ mod execution;
 pub mod file_loading;
 mod memory;
 mod processing;
+#[cfg(test)]
+mod test_util;
 mod translator;
 pub mod util;
 
-use crate::execution::execute;
+use crate::errors::format_diagnostics;
+use crate::execution::{execute, format_runtime_error, Debugger};
 use crate::memory::{MemoryManager, RuntimeMemoryManager};
+use crate::processing::optimiser::{optimise, OptimisationLevel};
 use crate::processing::preprocessor::SymbolData;
 #[allow(unused_imports)]
-use crate::translator::translate;
-use crate::util::{info, USIZE_BYTES};
+use crate::translator::disassemble;
+use crate::util::{info, warn, USIZE_BYTES};
 use processing::preprocessor::convert_to_symbols;
 use processing::processor::process_symbols;
-use std::{env, fs};
 use std::ffi::OsStr;
 use std::fmt::Write as _;
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
-use crate::processing::symbols::TypeSymbol;
+use std::{env, fs};
 
 static CTRL_C: AtomicBool = AtomicBool::new(false);
 
 const DEFAULT_FILE_NAME: &str = "main.why";
+/// Command that prints the compiled program instead of executing it e.g. `disasm main.why`
+const DISASSEMBLE_COMMAND: &str = "disasm";
+/// Flag that runs the program in the interactive debugger e.g. `--debug main.why`
+const DEBUG_FLAG: &str = "--debug";
+/// Flag that frees unreachable heap frames while the program runs e.g. `--gc main.why`
+const GC_FLAG: &str = "--gc";
+/// Flag that adds a directory to search for imported files e.g. `--lib ../shared main.why`
+const LIBRARY_FLAG: &str = "--lib";
+/// Environment variable listing directories to search for imported files (separated like `PATH`)
+const LIBRARY_PATH_VARIABLE: &str = "WHYTHON_PATH";
 
 fn main() {
     ctrlc::set_handler(|| {
@@ -45,7 +58,7 @@ fn main() {
 
 //noinspection SpellCheckingInspection
 fn wrapped_main(exit: &AtomicBool) {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
     info(
         format!(
             "Platform pointer (usize) length: {} [{}-bit]",
@@ -55,13 +68,47 @@ fn wrapped_main(exit: &AtomicBool) {
         .as_str(),
     );
 
-    let input_file = if args.len() >= 2 {
-        args[1].clone()
+    let debug = args.iter().any(|a| a == DEBUG_FLAG);
+    args.retain(|a| a != DEBUG_FLAG);
+
+    let garbage_collection = args.iter().any(|a| a == GC_FLAG);
+    args.retain(|a| a != GC_FLAG);
+
+    //? Directories given with the flag are searched before those in the environment variable
+    let mut library_path = Vec::new();
+    while let Some(i) = args.iter().position(|a| a == LIBRARY_FLAG) {
+        args.remove(i);
+        if i == args.len() {
+            col_println!((red, bold), "Expected a directory after '{}'", LIBRARY_FLAG);
+            return;
+        }
+        library_path.push(PathBuf::from(args.remove(i)));
+    }
+    if let Some(paths) = env::var_os(LIBRARY_PATH_VARIABLE) {
+        library_path.extend(env::split_paths(&paths));
+    }
+
+    //? Optimisation moves and removes code so is off by default when debugging
+    let optimisation_level = args
+        .iter()
+        .find_map(|a| OptimisationLevel::from_flag(a))
+        .unwrap_or(if debug {
+            OptimisationLevel::None
+        } else {
+            OptimisationLevel::Basic
+        });
+    args.retain(|a| OptimisationLevel::from_flag(a).is_none());
+
+    let disassemble_only = args.get(1).is_some_and(|a| a == DISASSEMBLE_COMMAND);
+    let file_argument = if disassemble_only { 2 } else { 1 };
+
+    let input_file = if args.len() > file_argument {
+        args[file_argument].clone()
     } else {
         DEFAULT_FILE_NAME.to_string()
     };
 
-    let memory;
+    let mut memory;
 
     let extension = match Path::new(&input_file).extension().and_then(OsStr::to_str) {
         None => {
@@ -76,13 +123,13 @@ fn wrapped_main(exit: &AtomicBool) {
         println!("Starting compilation (stage 1)");
         let start = Instant::now();
         let mut symbol_data = SymbolData::new();
-        match convert_to_symbols(input_file, &mut symbol_data) {
+        match convert_to_symbols(input_file, &library_path, &mut symbol_data) {
             Err(e) => {
                 col_println!(
                     (red, bold),
-                    "Compilation (stage 1) failed [{:?}]:\n\t{}",
+                    "Compilation (stage 1) failed [{:?}]:\n{}",
                     start.elapsed(),
-                    e
+                    format_diagnostics(&e)
                 );
                 return;
             }
@@ -123,9 +170,9 @@ fn wrapped_main(exit: &AtomicBool) {
             Err(e) => {
                 col_println!(
                     (red, bold),
-                    "Compilation (stage 2) failed [{:?}]:\n    {}",
+                    "Compilation (stage 2) failed [{:?}]:\n{}",
                     start.elapsed(),
-                    e
+                    format_diagnostics(&e)
                 );
                 return;
             }
@@ -138,6 +185,22 @@ fn wrapped_main(exit: &AtomicBool) {
             start.elapsed()
         );
 
+        if optimisation_level != OptimisationLevel::None {
+            println!("Starting optimisation");
+            let start = Instant::now();
+            let unoptimised_length = memory.memory.len();
+            match optimise(&mut memory, optimisation_level) {
+                Err(e) => warn(&format!("Optimisation skipped - {}", e)),
+                Ok(()) => col_println!(
+                    (green, bold),
+                    "Optimisation completed [{:?}] - {} -> {} bytes",
+                    start.elapsed(),
+                    unoptimised_length,
+                    memory.memory.len()
+                ),
+            }
+        }
+
         let start = Instant::now();
         memory.save_to_file("Compiled".to_string());
         col_println!(
@@ -166,16 +229,53 @@ fn wrapped_main(exit: &AtomicBool) {
         return;
     }
 
+    if disassemble_only {
+        println!(
+            "{}",
+            disassemble(&memory.memory, memory.get_entry_point(), &memory.debug_info)
+        );
+        return;
+    }
+
     #[cfg(debug_assertions)]
-    translate(&memory.memory, false);
+    println!(
+        "{}",
+        disassemble(&memory.memory, memory.get_entry_point(), &memory.debug_info)
+    );
 
+    let debug_info = std::mem::take(&mut memory.debug_info);
     let mut runtime_memory = RuntimeMemoryManager::from_program_memory(memory);
+    if garbage_collection {
+        runtime_memory.enable_garbage_collector();
+    }
 
     #[cfg(debug_assertions)]
     runtime_memory.dump_all("dump");
 
-    if let Err(e) = execute(&mut runtime_memory, exit) {
-        col_println!((red, bold), "Execution failed:\n\t{}", e)
+    let result = if debug {
+        Debugger::new(&mut runtime_memory, &debug_info).run(exit)
+    } else {
+        execute(&mut runtime_memory, exit)
+    };
+
+    if let Err(e) = result {
+        col_println!(
+            (red, bold),
+            "Execution failed:\n{}",
+            format_runtime_error(&e, &debug_info)
+        )
+    }
+
+    if let Some(stats) = runtime_memory.get_collection_stats().cloned() {
+        info(&format!(
+            "Garbage collector: {} collections [{:?}], {} frames ({} bytes) freed, {} frames at peak, {} frames live at exit",
+            stats.collections,
+            stats.time,
+            stats.frames_freed,
+            stats.bytes_freed,
+            stats.peak_frames,
+            runtime_memory.heap_memory().get_frame_count()
+        ));
     }
 
     #[cfg(debug_assertions)]
@@ -1,5 +1,12 @@
+mod compiled_header;
+mod debug_info;
+mod encoding;
 mod memory_manager;
+mod pointer_map;
 mod runtime_memory;
 
+pub use compiled_header::*;
+pub use debug_info::*;
 pub use memory_manager::*;
+pub use pointer_map::*;
 pub use runtime_memory::*;
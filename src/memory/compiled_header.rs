@@ -0,0 +1,114 @@
+use crate::util::USIZE_BYTES;
+
+/// Magic bytes at the start of every `.cwhy` file
+pub const CWHY_MAGIC: [u8; 4] = *b"CWHY";
+/// Version of the `.cwhy` format. Increment whenever the header or instruction encoding changes
+pub const CWHY_FORMAT_VERSION: u16 = 3;
+
+//? Fields are fixed width (independent of the platform's pointer width) so that mismatched files
+//? can be read and rejected with a clear error
+/// Magic (4) + version (2) + pointer width (1) + entry point (8) + program length (8) + pointer
+/// map length (8) + checksum (4)
+pub const CWHY_HEADER_LENGTH: usize = 4 + 2 + 1 + 8 + 8 + 8 + 4;
+
+/// Header placed before the program memory and pointer map in `.cwhy` files
+pub struct CompiledHeader {
+    pub version: u16,
+    /// Pointer (`usize`) width in bytes of the platform the program was compiled on
+    pub pointer_width: u8,
+    /// Position in program memory at which execution starts
+    pub entry_point: u64,
+    pub program_length: u64,
+    pub pointer_map_length: u64,
+    /// Checksum of the program memory
+    pub checksum: u32,
+}
+
+impl CompiledHeader {
+    /// Creates a header for `program` compiled on this platform
+    pub fn new(program: &[u8], pointer_map: &[u8], entry_point: usize) -> Self {
+        Self {
+            version: CWHY_FORMAT_VERSION,
+            pointer_width: USIZE_BYTES as u8,
+            entry_point: entry_point as u64,
+            program_length: program.len() as u64,
+            pointer_map_length: pointer_map.len() as u64,
+            checksum: checksum(program),
+        }
+    }
+
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CWHY_HEADER_LENGTH);
+        bytes.extend(CWHY_MAGIC);
+        bytes.extend(self.version.to_le_bytes());
+        bytes.push(self.pointer_width);
+        bytes.extend(self.entry_point.to_le_bytes());
+        bytes.extend(self.program_length.to_le_bytes());
+        bytes.extend(self.pointer_map_length.to_le_bytes());
+        bytes.extend(self.checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Reads the header from the start of `data` without validating it against this platform
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        if data.len() < CWHY_HEADER_LENGTH || data[..4] != CWHY_MAGIC {
+            return Err("File is not a compiled Whython file (missing 'CWHY' header)".to_string());
+        }
+
+        let field = |start: usize, length: usize| &data[start..start + length];
+        Ok(Self {
+            version: u16::from_le_bytes(field(4, 2).try_into().unwrap()),
+            pointer_width: data[6],
+            entry_point: u64::from_le_bytes(field(7, 8).try_into().unwrap()),
+            program_length: u64::from_le_bytes(field(15, 8).try_into().unwrap()),
+            pointer_map_length: u64::from_le_bytes(field(23, 8).try_into().unwrap()),
+            checksum: u32::from_le_bytes(field(31, 4).try_into().unwrap()),
+        })
+    }
+
+    /// Checks that the program described by this header can run on this platform and that
+    /// `data` (everything after the header) matches it
+    pub fn validate(&self, data: &[u8]) -> Result<(), String> {
+        if self.version != CWHY_FORMAT_VERSION {
+            return Err(format!(
+                "File uses format version {} but this compiler supports version {} - recompile the source",
+                self.version, CWHY_FORMAT_VERSION
+            ));
+        }
+        if self.pointer_width as usize != USIZE_BYTES {
+            return Err(format!(
+                "File was compiled for a {}-bit platform but this is a {}-bit platform - recompile the source",
+                self.pointer_width as usize * 8,
+                USIZE_BYTES * 8
+            ));
+        }
+        let length = self.program_length.saturating_add(self.pointer_map_length);
+        if length != data.len() as u64 {
+            return Err(format!(
+                "File is truncated or corrupted (expected {} bytes of program data, found {})",
+                length,
+                data.len()
+            ));
+        }
+        if self.checksum != checksum(&data[..self.program_length as usize]) {
+            return Err("File is corrupted (checksum mismatch)".to_string());
+        }
+        if self.entry_point > self.program_length {
+            return Err(format!(
+                "Entry point [{}] is outside of the program",
+                self.entry_point
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 32-bit FNV-1a hash of `data`
+pub fn checksum(data: &[u8]) -> u32 {
+    const OFFSET_BASIS: u32 = 0x811C9DC5;
+    const PRIME: u32 = 0x01000193;
+
+    data.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(PRIME)
+    })
+}
@@ -0,0 +1,291 @@
+use crate::address::Address;
+use crate::errors::{SourceLocation, Span};
+use crate::memory::checksum;
+use crate::memory::encoding::{Reader, Writer};
+use crate::processing::symbols::TypeSymbol;
+use std::ops::Range;
+use strum::IntoEnumIterator;
+
+/// Magic bytes at the start of every `.dwhy` (debug info) file
+pub const DWHY_MAGIC: [u8; 4] = *b"DWHY";
+/// Version of the `.dwhy` format. Increment whenever the encoding changes
+pub const DWHY_FORMAT_VERSION: u16 = 1;
+
+/// Position in program memory at which the code for a source line starts
+#[derive(Clone, Debug)]
+pub struct DebugLine {
+    pub offset: usize,
+    pub file_index: usize,
+    /// Zero-indexed
+    pub line_index: usize,
+    pub source: String,
+    /// Span of the code in `source`, excluding indentation and comments
+    pub span: Span,
+}
+
+/// Variable visible to the code in `scope`
+#[derive(Clone, Debug)]
+pub struct DebugVariable {
+    /// Full name e.g. `p.x`
+    pub name: String,
+    pub type_symbol: TypeSymbol,
+    pub address: Address,
+    pub size: usize,
+    /// Program memory range in which the variable is in scope
+    pub scope: Range<usize>,
+}
+
+/// Function compiled into program memory
+#[derive(Clone, Debug)]
+pub struct DebugFunction {
+    /// Full name e.g. `Point.length` for methods
+    pub name: String,
+    /// Program memory range of the function. Starts at the function's entry point. Code in a
+    /// function runs in its own stack frame so only variables declared in it are accessible
+    pub body: Range<usize>,
+}
+
+/// Debug symbol table produced by the compiler - maps program memory back to source lines,
+/// variables and functions. Saved alongside compiled files as `.dwhy`
+#[derive(Clone, Debug, Default)]
+pub struct DebugInfo {
+    pub files: Vec<String>,
+    /// Ordered by offset
+    pub lines: Vec<DebugLine>,
+    pub variables: Vec<DebugVariable>,
+    pub functions: Vec<DebugFunction>,
+}
+
+impl DebugInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Records that the code for the line at `location` starts at `offset`
+    pub fn add_line(&mut self, offset: usize, location: SourceLocation) {
+        let file_index = match self.files.iter().position(|f| *f == location.file_name) {
+            Some(index) => index,
+            None => {
+                self.files.push(location.file_name);
+                self.files.len() - 1
+            }
+        };
+        self.lines.push(DebugLine {
+            offset,
+            file_index,
+            line_index: location.line_index,
+            source: location.source,
+            span: location.span,
+        });
+    }
+
+    pub fn add_variable(&mut self, variable: DebugVariable) {
+        self.variables.push(variable);
+    }
+
+    pub fn add_function(&mut self, function: DebugFunction) {
+        self.functions.push(function);
+    }
+
+    /// Returns the source line that the instruction at `offset` was compiled from
+    pub fn get_line(&self, offset: usize) -> Option<&DebugLine> {
+        let index = self.lines.partition_point(|line| line.offset <= offset);
+        index.checked_sub(1).map(|i| &self.lines[i])
+    }
+
+    /// Returns the location of the source line that the instruction at `offset` was compiled from
+    pub fn get_location(&self, offset: usize) -> Option<SourceLocation> {
+        self.get_line(offset).map(|line| {
+            SourceLocation::new(
+                self.files[line.file_index].clone(),
+                line.line_index,
+                line.source.clone(),
+                line.span,
+            )
+        })
+    }
+
+    /// Returns the offset of the first instruction compiled from a line. `file_name` can be
+    /// omitted if the program has a single file
+    pub fn get_line_offset(&self, file_name: Option<&str>, line_index: usize) -> Option<usize> {
+        self.lines
+            .iter()
+            .find(|line| {
+                line.line_index == line_index
+                    && file_name.is_none_or(|name| self.files[line.file_index].ends_with(name))
+            })
+            .map(|line| line.offset)
+    }
+
+    /// Formats a line as `file:line`
+    pub fn format_line(&self, line: &DebugLine) -> String {
+        format!("{}:{}", self.files[line.file_index], line.line_index + 1)
+    }
+
+    /// Returns the innermost function containing `offset`
+    pub fn get_function(&self, offset: usize) -> Option<&DebugFunction> {
+        self.functions
+            .iter()
+            .filter(|function| function.body.contains(&offset))
+            .min_by_key(|function| function.body.len())
+    }
+
+    /// Returns the function with its entry point at `offset`
+    pub fn get_function_at_entry(&self, offset: usize) -> Option<&DebugFunction> {
+        self.functions
+            .iter()
+            .find(|function| function.body.start == offset)
+    }
+
+    /// Returns the variables accessible from the instruction at `offset`. Inner variables come
+    /// before the outer variables they shadow
+    pub fn get_variables_in_scope(&self, offset: usize) -> Vec<&DebugVariable> {
+        let function = self.get_function(offset).map(|f| &f.body);
+        let mut variables: Vec<&DebugVariable> = self
+            .variables
+            .iter()
+            .filter(|variable| variable.scope.contains(&offset))
+            //? Variables outside of the current function are in a different stack frame. The
+            //? end of the scope is used as parameters are declared before the function body
+            .filter(|variable| {
+                self.get_function(variable.scope.end - 1).map(|f| &f.body) == function
+            })
+            .collect();
+        variables.sort_by_key(|variable| std::cmp::Reverse(variable.scope.start));
+        variables
+    }
+
+    /// Moves every position in program memory to `map(position)` after the program has been
+    /// rewritten. Variables and functions whose code was removed entirely are dropped
+    pub fn relocate(&mut self, map: impl Fn(usize) -> usize) {
+        for line in &mut self.lines {
+            line.offset = map(line.offset);
+        }
+        for variable in &mut self.variables {
+            variable.scope = map(variable.scope.start)..map(variable.scope.end);
+        }
+        self.variables.retain(|variable| !variable.scope.is_empty());
+        for function in &mut self.functions {
+            function.body = map(function.body.start)..map(function.body.end);
+        }
+        self.functions.retain(|function| !function.body.is_empty());
+    }
+
+    /// Encodes the debug info for `program`. The program's checksum is stored so that debug info
+    /// for a different program is rejected when loading
+    pub fn get_bytes(&self, program: &[u8]) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.bytes.extend(DWHY_MAGIC);
+        writer.bytes.extend(DWHY_FORMAT_VERSION.to_le_bytes());
+        writer.bytes.extend(checksum(program).to_le_bytes());
+
+        writer.usize(self.files.len());
+        for file in &self.files {
+            writer.string(file);
+        }
+
+        writer.usize(self.lines.len());
+        for line in &self.lines {
+            writer.usize(line.offset);
+            writer.usize(line.file_index);
+            writer.usize(line.line_index);
+            writer.string(&line.source);
+            writer.usize(line.span.start);
+            writer.usize(line.span.end);
+        }
+
+        writer.usize(self.variables.len());
+        for variable in &self.variables {
+            writer.string(&variable.name);
+            writer.bytes.push(
+                TypeSymbol::iter()
+                    .position(|t| t == variable.type_symbol)
+                    .unwrap() as u8,
+            );
+            writer.usize(variable.size);
+            writer.bytes.extend(variable.address.get_bytes());
+            writer.usize(variable.scope.start);
+            writer.usize(variable.scope.end);
+        }
+
+        writer.usize(self.functions.len());
+        for function in &self.functions {
+            writer.string(&function.name);
+            writer.usize(function.body.start);
+            writer.usize(function.body.end);
+        }
+
+        writer.bytes
+    }
+
+    /// Decodes debug info, checking that it was produced for `program`
+    pub fn from_bytes(data: &[u8], program: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader::new(data);
+        if reader.read(4)? != DWHY_MAGIC {
+            return Err(
+                "File is not a Whython debug info file (missing 'DWHY' header)".to_string(),
+            );
+        }
+        let version = u16::from_le_bytes(reader.read(2)?.try_into().unwrap());
+        if version != DWHY_FORMAT_VERSION {
+            return Err(format!(
+                "File uses debug info format version {} but this compiler supports version {}",
+                version, DWHY_FORMAT_VERSION
+            ));
+        }
+        if u32::from_le_bytes(reader.read(4)?.try_into().unwrap()) != checksum(program) {
+            return Err("Debug info was created for a different program".to_string());
+        }
+
+        let mut debug_info = Self::new();
+
+        for _ in 0..reader.usize()? {
+            debug_info.files.push(reader.string()?);
+        }
+
+        for _ in 0..reader.usize()? {
+            let offset = reader.usize()?;
+            let file_index = reader.usize()?;
+            if file_index >= debug_info.files.len() {
+                return Err(format!("Invalid file index [{}]", file_index));
+            }
+            debug_info.lines.push(DebugLine {
+                offset,
+                file_index,
+                line_index: reader.usize()?,
+                source: reader.string()?,
+                span: Span::new(reader.usize()?, reader.usize()?),
+            });
+        }
+
+        for _ in 0..reader.usize()? {
+            let name = reader.string()?;
+            let type_code = reader.read(1)?[0];
+            let type_symbol = TypeSymbol::iter()
+                .nth(type_code as usize)
+                .ok_or(format!("Invalid type code [{}]", type_code))?;
+            let size = reader.usize()?;
+            let address = Address::from_bytes(reader.data, &mut reader.pointer, size)?;
+            debug_info.variables.push(DebugVariable {
+                name,
+                type_symbol,
+                address,
+                size,
+                scope: reader.usize()?..reader.usize()?,
+            });
+        }
+
+        for _ in 0..reader.usize()? {
+            debug_info.functions.push(DebugFunction {
+                name: reader.string()?,
+                body: reader.usize()?..reader.usize()?,
+            });
+        }
+
+        Ok(debug_info)
+    }
+}
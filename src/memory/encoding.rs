@@ -0,0 +1,50 @@
+//? Fields are fixed width (independent of the platform's pointer width), matching the `.cwhy`
+//? header
+/// Encodes the sections of compiled files that follow their headers
+#[derive(Default)]
+pub struct Writer {
+    pub bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn usize(&mut self, value: usize) {
+        self.bytes.extend((value as u64).to_le_bytes());
+    }
+
+    pub fn string(&mut self, value: &str) {
+        self.usize(value.len());
+        self.bytes.extend(value.as_bytes());
+    }
+}
+
+/// Decodes data written by a `Writer`
+pub struct Reader<'a> {
+    pub data: &'a [u8],
+    pub pointer: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pointer: 0 }
+    }
+
+    pub fn read(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.pointer..self.pointer.saturating_add(length))
+            .ok_or("Data ends unexpectedly")?;
+        self.pointer += length;
+        Ok(bytes)
+    }
+
+    pub fn usize(&mut self) -> Result<usize, String> {
+        let value = u64::from_le_bytes(self.read(8)?.try_into().unwrap());
+        usize::try_from(value).map_err(|_| format!("Value [{}] is too large", value))
+    }
+
+    pub fn string(&mut self) -> Result<String, String> {
+        let length = self.usize()?;
+        String::from_utf8(self.read(length)?.to_vec())
+            .map_err(|_| "Data contains invalid text".to_string())
+    }
+}
@@ -1,23 +1,55 @@
 use num_format::{Locale, ToFormattedString};
 use std::fs;
 use std::io::Write;
+use std::path::Path;
 
-use crate::util::USIZE_BYTES;
+use crate::memory::{CompiledHeader, DebugInfo, PointerMap, CWHY_HEADER_LENGTH};
+use crate::util::{warn, USIZE_BYTES};
 
 #[derive(Default)]
 pub struct MemoryManager {
     pub memory: Vec<u8>,
+    entry_point: usize,
+    /// Source lines, variables and functions of the compiled program. Loaded from a `.dwhy`
+    /// file for precompiled files
+    pub debug_info: DebugInfo,
+    /// Positions of `usize`s in memory that hold positions in program memory e.g. return
+    /// addresses. Used to relocate them when the program is optimised
+    pub code_pointers: Vec<usize>,
+    /// Stack slots holding heap pointers. Used by the garbage collector
+    pub pointer_map: PointerMap,
 }
 
 impl MemoryManager {
     /// Creates an empty memory manager
     pub fn new() -> Self {
-        Self { memory: Vec::new() }
+        Self {
+            memory: Vec::new(),
+            entry_point: 0,
+            debug_info: DebugInfo::new(),
+            code_pointers: Vec::new(),
+            pointer_map: PointerMap::new(),
+        }
     }
 
     /// Creates memory manager from vector of bytes
     pub fn from_vec(memory: Vec<u8>) -> Self {
-        Self { memory }
+        Self {
+            memory,
+            entry_point: 0,
+            debug_info: DebugInfo::new(),
+            code_pointers: Vec::new(),
+            pointer_map: PointerMap::new(),
+        }
+    }
+
+    /// Gets the position at which execution starts
+    pub fn get_entry_point(&self) -> usize {
+        self.entry_point
+    }
+
+    pub fn set_entry_point(&mut self, entry_point: usize) {
+        self.entry_point = entry_point;
     }
 
     /// Gets the position after the last piece of memory written
@@ -25,6 +57,11 @@ impl MemoryManager {
         self.memory.len()
     }
 
+    /// Records that the `usize` at `position` holds a position in program memory
+    pub fn add_code_pointer(&mut self, position: usize) {
+        self.code_pointers.push(position);
+    }
+
     /// Adds a byte to the memory
     pub fn append_byte(&mut self, data: u8) -> usize {
         let position = self.get_position();
@@ -81,42 +118,96 @@ impl MemoryManager {
         }
     }
 
-    /// Saves compiled data to a file with the specified name (excluding extension)
+    /// Saves compiled data, preceded by a `CompiledHeader`, to a file with the specified name
+    /// (excluding extension). Debug info, if any, is saved alongside it in a `.dwhy` file
     //noinspection SpellCheckingInspection
     pub fn save_to_file(&self, name: String) {
-        let name = name + format!(" - {}.cwhy", USIZE_BYTES * 8).as_str();
-
-        println!(
-            "Saving compiled data '{}' [{} bytes]",
-            &name,
-            self.memory.len().to_formatted_string(&Locale::en)
-        );
-
-        let file = fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(name);
-
-        let Ok(mut file) = file else {
-            println!("Failed to open file - {}", file.unwrap_err());
-            return;
-        };
-
-        if let Err(e) = file.write_all(&self.memory) {
-            println!("Failed to write to file - {}", e)
+        let name = name + format!(" - {}", USIZE_BYTES * 8).as_str();
+
+        let pointer_map = self.pointer_map.get_bytes();
+        let mut data =
+            CompiledHeader::new(&self.memory, &pointer_map, self.entry_point).get_bytes();
+        data.extend(self.memory.iter());
+        data.extend(pointer_map);
+        write_file("compiled data", name.clone() + ".cwhy", &data);
+
+        if !self.debug_info.is_empty() {
+            write_file(
+                "debug info",
+                name + ".dwhy",
+                &self.debug_info.get_bytes(&self.memory),
+            );
         }
     }
 
-    /// Loads data from a compiled file
+    /// Loads data from a compiled file, validating its `CompiledHeader`. Debug info is loaded
+    /// from a `.dwhy` file with the same name if it exists
     pub fn load_from_file(path: String) -> Result<Self, String> {
         println!("Loading precompiled data from file '{}'", &path);
 
-        let data = match fs::read(path) {
+        let data = match fs::read(&path) {
             Err(e) => return Err(e.to_string()),
             Ok(value) => value,
         };
 
-        Ok(Self { memory: data })
+        let header = CompiledHeader::from_bytes(&data)?;
+        header.validate(&data[CWHY_HEADER_LENGTH..])?;
+        let (program, pointer_map) =
+            data[CWHY_HEADER_LENGTH..].split_at(header.program_length as usize);
+        let pointer_map = PointerMap::from_bytes(pointer_map)
+            .map_err(|e| format!("File contains an invalid pointer map - {}", e))?;
+
+        //? Debug info is optional so failing to load it isn't an error
+        let debug_path = Path::new(&path).with_extension("dwhy");
+        let debug_info = match fs::read(&debug_path) {
+            Err(_) => DebugInfo::new(),
+            Ok(debug_data) => match DebugInfo::from_bytes(&debug_data, program) {
+                Ok(debug_info) => {
+                    println!("Loaded debug info from file '{}'", debug_path.display());
+                    debug_info
+                }
+                Err(e) => {
+                    warn(&format!(
+                        "Ignoring debug info file '{}' - {}",
+                        debug_path.display(),
+                        e
+                    ));
+                    DebugInfo::new()
+                }
+            },
+        };
+
+        Ok(Self {
+            memory: Vec::from(program),
+            entry_point: header.entry_point as usize,
+            debug_info,
+            code_pointers: Vec::new(),
+            pointer_map,
+        })
+    }
+}
+
+/// Writes `data` to the file `name`, reporting its size and any failure
+fn write_file(description: &str, name: String, data: &[u8]) {
+    println!(
+        "Saving {} '{}' [{} bytes]",
+        description,
+        &name,
+        data.len().to_formatted_string(&Locale::en)
+    );
+
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .open(name);
+
+    let Ok(mut file) = file else {
+        println!("Failed to open file - {}", file.unwrap_err());
+        return;
+    };
+
+    if let Err(e) = file.write_all(data) {
+        println!("Failed to write to file - {}", e)
     }
 }
@@ -0,0 +1,126 @@
+use crate::memory::encoding::{Reader, Writer};
+use std::ops::Range;
+
+/// Stack slot holding a heap frame id
+#[derive(Clone, Debug)]
+pub struct PointerSlot {
+    /// Address of the slot in its function's stack frame
+    pub offset: usize,
+    /// Program memory range in which the slot's variable is in scope
+    pub scope: Range<usize>,
+}
+
+/// Records which stack slots hold heap pointers so that the garbage collector can find the
+/// heap frames reachable from the stack. Saved in `.cwhy` files after the program as it's
+/// needed at runtime
+#[derive(Clone, Debug, Default)]
+pub struct PointerMap {
+    /// Program memory range of each function. Code in a function runs in its own stack frame
+    functions: Vec<Range<usize>>,
+    slots: Vec<PointerSlot>,
+    /// Offset and allocation position of temporaries whose stack frame hasn't ended yet
+    temporaries: Vec<(usize, usize)>,
+}
+
+impl PointerMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_function(&mut self, body: Range<usize>) {
+        self.functions.push(body);
+    }
+
+    pub fn add_slot(&mut self, slot: PointerSlot) {
+        self.slots.push(slot);
+    }
+
+    /// Adds a slot for a temporary (e.g. an intermediate value or returned pointer) allocated at
+    /// `start`. It stays in scope until the end of its stack frame as temporaries aren't freed
+    pub fn add_temporary(&mut self, offset: usize, start: usize) {
+        self.temporaries.push((offset, start));
+    }
+
+    /// Ends the scope of the temporaries allocated in the stack frame of the code in `body`
+    pub fn end_temporaries(&mut self, body: Range<usize>) {
+        let (ended, open): (Vec<_>, Vec<_>) = std::mem::take(&mut self.temporaries)
+            .into_iter()
+            .partition(|(_, start)| *start >= body.start);
+        self.temporaries = open;
+        for (offset, start) in ended {
+            self.add_slot(PointerSlot {
+                offset,
+                scope: start..body.end,
+            });
+        }
+    }
+
+    /// Returns the index of the innermost function containing `position`
+    fn get_function(&self, position: usize) -> Option<usize> {
+        self.functions
+            .iter()
+            .enumerate()
+            .filter(|(_, body)| body.contains(&position))
+            .min_by_key(|(_, body)| body.len())
+            .map(|(i, _)| i)
+    }
+
+    /// Returns the offsets of the slots holding pointers in the stack frame of the code at
+    /// `position`
+    pub fn get_slots(&self, position: usize) -> impl Iterator<Item = usize> + '_ {
+        let function = self.get_function(position);
+        self.slots
+            .iter()
+            .filter(move |slot| slot.scope.contains(&position))
+            //? The end of the scope is used as parameters are declared before the function body
+            .filter(move |slot| self.get_function(slot.scope.end - 1) == function)
+            .map(|slot| slot.offset)
+    }
+
+    /// Moves every position in program memory to `map(position)` after the program has been
+    /// rewritten
+    pub fn relocate(&mut self, map: impl Fn(usize) -> usize) {
+        for body in &mut self.functions {
+            *body = map(body.start)..map(body.end);
+        }
+        self.functions.retain(|body| !body.is_empty());
+        for slot in &mut self.slots {
+            slot.scope = map(slot.scope.start)..map(slot.scope.end);
+        }
+        self.slots.retain(|slot| !slot.scope.is_empty());
+    }
+
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.usize(self.functions.len());
+        for body in &self.functions {
+            writer.usize(body.start);
+            writer.usize(body.end);
+        }
+        writer.usize(self.slots.len());
+        for slot in &self.slots {
+            writer.usize(slot.offset);
+            writer.usize(slot.scope.start);
+            writer.usize(slot.scope.end);
+        }
+        writer.bytes
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader::new(data);
+        let mut pointer_map = Self::new();
+        for _ in 0..reader.usize()? {
+            pointer_map.add_function(reader.usize()?..reader.usize()?);
+        }
+        for _ in 0..reader.usize()? {
+            pointer_map.add_slot(PointerSlot {
+                offset: reader.usize()?,
+                scope: reader.usize()?..reader.usize()?,
+            });
+        }
+        if reader.pointer != data.len() {
+            return Err("Pointer map has unexpected trailing data".to_string());
+        }
+        Ok(pointer_map)
+    }
+}
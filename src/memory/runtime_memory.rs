@@ -1,12 +1,16 @@
+mod garbage_collector;
 mod heap_memory;
 mod stack_memory;
 
-pub use heap_memory::HeapMemory;
+pub use garbage_collector::{CollectionStats, GarbageCollector};
+pub use heap_memory::{format_frame_id, HeapMemory};
 pub use stack_memory::StackMemory;
 
-use super::MemoryManager;
+use super::{MemoryManager, PointerMap};
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
+use std::collections::VecDeque;
 use std::fs;
-use std::io::Write;
+use std::io::{self, Write};
 
 #[derive(Clone, Debug)]
 pub enum MemoryLocation {
@@ -29,20 +33,106 @@ fn dump_bytes(file: &str, data: &[u8]) {
 
 pub struct RuntimeMemoryManager {
     program_memory: Vec<u8>,
+    entry_point: usize,
     stack_memory: StackMemory,
     heap_memory: HeapMemory,
+    pointer_map: PointerMap,
+    /// Frees unreachable heap frames if enabled
+    garbage_collector: Option<GarbageCollector>,
+    /// Collects printed text instead of writing it to stdout if enabled
+    captured_output: Option<String>,
+    /// Lines read by input instructions instead of stdin if set
+    provided_input: Option<VecDeque<String>>,
 }
 
 impl RuntimeMemoryManager {
     /// Converts compile time `MemoryManager` to `RuntimeMemoryManager`
     pub fn from_program_memory(program_memory: MemoryManager) -> Self {
         Self {
+            entry_point: program_memory.get_entry_point(),
             program_memory: program_memory.memory,
             stack_memory: StackMemory::new(),
             heap_memory: HeapMemory::new(),
+            pointer_map: program_memory.pointer_map,
+            garbage_collector: None,
+            captured_output: None,
+            provided_input: None,
         }
     }
 
+    pub fn enable_garbage_collector(&mut self) {
+        self.garbage_collector = Some(GarbageCollector::new());
+    }
+
+    /// Collects everything the program prints instead of writing it to stdout
+    pub fn capture_output(&mut self) {
+        self.captured_output = Some(String::new());
+    }
+
+    /// Returns the text printed so far if output is being captured
+    pub fn take_output(&mut self) -> Option<String> {
+        self.captured_output.as_mut().map(std::mem::take)
+    }
+
+    /// Writes printed `text` to stdout, or to the captured output if enabled
+    pub fn write_output(&mut self, text: &str) -> RuntimeResult<()> {
+        match &mut self.captured_output {
+            Some(output) => output.push_str(text),
+            None => {
+                let mut stdout = io::stdout();
+                stdout
+                    .write_all(text.as_bytes())
+                    .and_then(|_| stdout.flush())
+                    .map_err(|e| RuntimeErrorKind::Io(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes input instructions read from `lines` instead of stdin
+    pub fn provide_input(&mut self, lines: &[&str]) {
+        self.provided_input = Some(lines.iter().map(|line| line.to_string()).collect());
+    }
+
+    /// Reads a line of input without its line ending. Reading past the end of the input gives
+    /// an empty line
+    pub fn read_input(&mut self) -> RuntimeResult<String> {
+        let mut input = match &mut self.provided_input {
+            Some(lines) => lines.pop_front().unwrap_or_default(),
+            None => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_line(&mut input)
+                    .map_err(|e| RuntimeErrorKind::Io(e.to_string()))?;
+                input
+            }
+        };
+        input.truncate(input.trim_end_matches(['\n', '\r']).len());
+        Ok(input)
+    }
+
+    /// Returns the garbage collector's statistics if it is enabled
+    pub fn get_collection_stats(&self) -> Option<&CollectionStats> {
+        self.garbage_collector.as_ref().map(|gc| gc.get_stats())
+    }
+
+    /// Called before the instruction at `position` allocates a heap frame
+    pub fn on_heap_allocation(&mut self, position: usize) {
+        if let Some(garbage_collector) = &mut self.garbage_collector {
+            garbage_collector.on_allocation(
+                position,
+                &self.pointer_map,
+                &self.stack_memory,
+                &mut self.heap_memory,
+            );
+        }
+    }
+
+    /// Position in program memory at which execution starts
+    pub fn entry_point(&self) -> usize {
+        self.entry_point
+    }
+
     pub fn program_memory(&self) -> &[u8] {
         &self.program_memory
     }
@@ -58,18 +148,31 @@ impl RuntimeMemoryManager {
     /// Returns a reference to the memory as `&[u8]` and the transformed address location as a
     /// `usize`. See `StackMemory::get_stack` for details about how the address location is
     /// transformed
-    pub fn get_memory(&self, location: &MemoryLocation, start_position: usize) -> (&[u8], usize) {
+    pub fn get_memory(
+        &self,
+        location: &MemoryLocation,
+        start_position: usize,
+    ) -> RuntimeResult<(&[u8], usize)> {
         match location {
-            MemoryLocation::Program => (&self.program_memory, start_position),
+            MemoryLocation::Program => Ok((&self.program_memory, start_position)),
             MemoryLocation::Stack => self.stack_memory.get_stack(start_position),
-            MemoryLocation::Heap(frame) => (self.heap_memory.get_frame(*frame), start_position),
+            MemoryLocation::Heap(frame) => {
+                Ok((self.heap_memory.get_frame(*frame)?, start_position))
+            }
         }
     }
 
     /// Returns a slice from a given `MemoryLocation` starting at `address` with length `length`
-    pub fn get_data(&self, location: &MemoryLocation, address: usize, length: usize) -> &[u8] {
+    pub fn get_data(
+        &self,
+        location: &MemoryLocation,
+        address: usize,
+        length: usize,
+    ) -> RuntimeResult<&[u8]> {
         match location {
-            MemoryLocation::Program => &self.program_memory[address..address + length],
+            MemoryLocation::Program => self.program_memory.get(address..address + length).ok_or(
+                RuntimeErrorKind::IndexOutOfProgram(address, address + length),
+            ),
             MemoryLocation::Stack => self.stack_memory.index_slice(address, address + length),
             MemoryLocation::Heap(frame) => {
                 self.heap_memory
@@ -79,31 +182,44 @@ impl RuntimeMemoryManager {
     }
 
     /// Overwrites data at an `address` in `location` with `data`
-    pub fn overwrite_data(&mut self, location: &MemoryLocation, address: usize, data: &[u8]) {
+    pub fn overwrite_data(
+        &mut self,
+        location: &MemoryLocation,
+        address: usize,
+        data: &[u8],
+    ) -> RuntimeResult<()> {
         match location {
             MemoryLocation::Program => {
-                panic!("Overwriting program memory is forbidden!");
+                return Err(RuntimeErrorKind::OverwritingProgramMemory);
             }
             MemoryLocation::Stack => {
-                let (stack, stack_address) = self.stack_memory.get_stack_mut(address);
-                stack[stack_address..(data.len() + stack_address)].copy_from_slice(data);
+                let (stack, stack_address) = self.stack_memory.get_stack_mut(address)?;
+                let stack_end = stack.len();
+                stack
+                    .get_mut(stack_address..(data.len() + stack_address))
+                    .ok_or(RuntimeErrorKind::IndexOutOfStack(stack_end))?
+                    .copy_from_slice(data);
                 // for i in 0..data.len() {
                 //     stack[stack_address + i] = data[i];
                 // }
             }
             MemoryLocation::Heap(frame) => {
-                let data = self.heap_memory.get_mut_frame(*frame);
-                for i in 0..data.len() {
-                    data[address + i] = data[i];
-                }
+                self.heap_memory
+                    .index_slice_mut(*frame, address, data.len() + address)?
+                    .copy_from_slice(data);
             }
         }
+        Ok(())
     }
 
     /// Gets a single byte out of memory at a given `location` and `address`
-    pub fn get_byte(&self, location: &MemoryLocation, address: usize) -> u8 {
+    pub fn get_byte(&self, location: &MemoryLocation, address: usize) -> RuntimeResult<u8> {
         match location {
-            MemoryLocation::Program => self.program_memory[address],
+            MemoryLocation::Program => self
+                .program_memory
+                .get(address)
+                .copied()
+                .ok_or(RuntimeErrorKind::IndexOutOfProgram(address, address + 1)),
             MemoryLocation::Stack => self.stack_memory.index(address),
             MemoryLocation::Heap(frame) => self.heap_memory.index(*frame, address),
         }
@@ -0,0 +1,145 @@
+use crate::memory::runtime_memory::{HeapMemory, StackMemory};
+use crate::memory::PointerMap;
+use crate::util::{get_usize, USIZE_BYTES};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Number of allocated heap frames at which the first collection runs
+const INITIAL_COLLECTION_THRESHOLD: usize = 64;
+
+/// Statistics reported when a program using the garbage collector exits
+#[derive(Clone, Debug, Default)]
+pub struct CollectionStats {
+    pub collections: usize,
+    pub frames_freed: usize,
+    pub bytes_freed: usize,
+    /// Largest number of heap frames allocated at once
+    pub peak_frames: usize,
+    /// Total time spent collecting
+    pub time: Duration,
+}
+
+/// Mark-and-sweep garbage collector for heap frames
+///
+/// The roots are the stack slots (variables and temporaries) that hold pointers according to the
+/// `PointerMap`. Heap frames don't record the types they hold so they are scanned conservatively -
+/// any `usize` in a reachable frame that is the id of an allocated frame keeps that frame alive
+pub struct GarbageCollector {
+    /// Number of allocated frames at which the next collection runs
+    threshold: usize,
+    stats: CollectionStats,
+}
+
+impl GarbageCollector {
+    pub fn new() -> Self {
+        Self {
+            threshold: INITIAL_COLLECTION_THRESHOLD,
+            stats: CollectionStats::default(),
+        }
+    }
+
+    pub fn get_stats(&self) -> &CollectionStats {
+        &self.stats
+    }
+
+    /// Called before the instruction at `position` allocates a frame. Collects garbage once
+    /// enough frames are allocated
+    pub fn on_allocation(
+        &mut self,
+        position: usize,
+        pointer_map: &PointerMap,
+        stack: &StackMemory,
+        heap: &mut HeapMemory,
+    ) {
+        if heap.get_frame_count() >= self.threshold {
+            self.collect(position, pointer_map, stack, heap);
+            //? Collect less often if most frames are still in use
+            self.threshold = INITIAL_COLLECTION_THRESHOLD.max(heap.get_frame_count() * 2);
+        }
+        self.stats.peak_frames = self.stats.peak_frames.max(heap.get_frame_count() + 1);
+    }
+
+    /// Frees every heap frame that isn't reachable from the stack while the instruction at
+    /// `position` is executing
+    pub fn collect(
+        &mut self,
+        position: usize,
+        pointer_map: &PointerMap,
+        stack: &StackMemory,
+        heap: &mut HeapMemory,
+    ) {
+        let start = Instant::now();
+
+        //? Mark
+        let mut pending = Vec::new();
+        //? The newest frame belongs to the code at `position`. Every other frame belongs to the
+        //? code at the return address stored at the start of the frame above it
+        let mut position = position;
+        for frame in stack.get_stacks() {
+            for offset in pointer_map.get_slots(position) {
+                if let Some(value) = frame.get(offset..offset + USIZE_BYTES) {
+                    pending.push(get_usize(&mut 0, value));
+                }
+            }
+            match frame.get(..USIZE_BYTES) {
+                Some(return_address) => position = get_usize(&mut 0, return_address),
+                None => break,
+            }
+        }
+
+        let mut marked = HashSet::new();
+        while let Some(frame) = pending.pop() {
+            //? Uninitialised and dangling pointers are ignored
+            let Ok(data) = heap.get_frame(frame) else {
+                continue;
+            };
+            if !marked.insert(frame) {
+                continue;
+            }
+            for window in data.windows(USIZE_BYTES) {
+                let value = get_usize(&mut 0, window);
+                if heap.contains_frame(value) && !marked.contains(&value) {
+                    pending.push(value);
+                }
+            }
+        }
+
+        //? Sweep
+        let garbage: Vec<(usize, usize)> = heap
+            .get_frames()
+            .filter(|(frame, _)| !marked.contains(frame))
+            .map(|(frame, data)| (frame, data.len()))
+            .collect();
+        for (frame, length) in garbage {
+            heap.free_frame(frame).unwrap();
+            self.stats.frames_freed += 1;
+            self.stats.bytes_freed += length;
+        }
+
+        self.stats.collections += 1;
+        self.stats.time += start.elapsed();
+    }
+}
+
+impl Default for GarbageCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::get_gc_output;
+
+    #[test]
+    fn keeps_temporaries_alive() {
+        let source = "fn mk (int v) -> ptr\n    ptr p = 0\n    alloc p int\n    p[int] = v\n    return p\nfn sum (ptr a, ptr b) -> int\n    return a[int] + b[int]\nptr t = 0\nfor i in 0..63\n    alloc t int\nprint sum (mk (1), mk (2))\n";
+        assert_eq!(get_gc_output(source), "3\n");
+    }
+
+    #[test]
+    fn keeps_array_items_and_fields_alive() {
+        let source = "class Node\n    int value\n    ptr next\nptr p = 0\nalloc p int\np[int] = 5\nptr[2] ps = (0, p)\nalloc p int\np[int] = 7\nNode n = (1, p)\np = 0\nptr t = 0\nfor i in 0..130\n    alloc t int\nprint ps[1][int]\nprint n.next[int]\n";
+        assert_eq!(get_gc_output(source), "5\n7\n");
+    }
+}
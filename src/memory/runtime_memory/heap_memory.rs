@@ -1,67 +1,157 @@
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
 use crate::memory::runtime_memory::dump_bytes;
-use std::collections::LinkedList;
+use std::collections::HashMap;
 use std::fs;
 
+/// Number of low bits of a frame id holding the slot the frame is stored in. The remaining
+/// bits hold the slot's generation
+const FRAME_SLOT_BITS: u32 = usize::BITS / 2;
+const FRAME_SLOT_MASK: usize = (1 << FRAME_SLOT_BITS) - 1;
+/// Generation of a slot's first frame. Not `0` so that no frame id is `0` (the value of an
+/// unallocated pointer) and ids are unlikely to be mistaken for small integers
+const FIRST_GENERATION: usize = 1;
+
+/// Splits a frame id into its slot and generation
+pub fn split_frame_id(frame: usize) -> (usize, usize) {
+    (frame & FRAME_SLOT_MASK, frame >> FRAME_SLOT_BITS)
+}
+
+/// Formats a frame id as its slot and generation
+pub fn format_frame_id(frame: usize) -> String {
+    let (slot, generation) = split_frame_id(frame);
+    format!("frame {}, generation {}", slot, generation)
+}
+
+fn create_frame_id(slot: usize, generation: usize) -> usize {
+    slot | (generation << FRAME_SLOT_BITS)
+}
+
+/// Heap of frames indexed by frame id
+///
+/// Freed slots are reused by later allocations. Each slot has a generation that is incremented
+/// when its frame is freed and is stored in the frame's id, so ids of freed frames never refer
+/// to a newer frame in the same slot
 pub struct HeapMemory {
-    // TODO: Use more efficient data structure (hash table?)
-    // (frame id, data)
-    memory: LinkedList<(usize, Vec<u8>)>,
-    next_frame: usize,
+    /// Data of every allocated frame, by frame id
+    memory: HashMap<usize, Vec<u8>>,
+    /// Current generation of each slot that has been used
+    generations: Vec<usize>,
+    /// Slots whose frames have been freed
+    free_slots: Vec<usize>,
 }
 
 impl HeapMemory {
     pub fn new() -> Self {
         Self {
-            memory: LinkedList::new(),
-            next_frame: 0,
+            memory: HashMap::new(),
+            generations: Vec::new(),
+            free_slots: Vec::new(),
         }
     }
 
-    // TODO: Better frame creation algorithm (prevent allow id re-use, prevent double usage)
     /// Creates frame with specified size, returns frame id
     pub fn create_frame(&mut self, size: usize) -> usize {
-        self.memory.push_back((self.next_frame, vec![0; size]));
-        self.next_frame += 1;
-        self.next_frame - 1
+        let slot = self.free_slots.pop().unwrap_or_else(|| {
+            self.generations.push(FIRST_GENERATION);
+            self.generations.len() - 1
+        });
+        let frame = create_frame_id(slot, self.generations[slot]);
+        self.memory.insert(frame, vec![0; size]);
+        frame
     }
 
-    /// Gets a frame's data with the specified frame id
-    pub fn get_frame(&self, frame: usize) -> &[u8] {
-        for f in self.memory.iter() {
-            if f.0 == frame {
-                return &f.1;
-            }
+    /// Returns the error for accessing a frame id that isn't allocated
+    fn get_missing_frame_error(&self, frame: usize) -> RuntimeErrorKind {
+        let (slot, generation) = split_frame_id(frame);
+        match self.generations.get(slot) {
+            Some(current) if generation < *current => RuntimeErrorKind::UseAfterFree(frame),
+            _ => RuntimeErrorKind::FrameNotInHeap(frame),
         }
+    }
+
+    /// Removes the frame with the specified frame id
+    pub fn free_frame(&mut self, frame: usize) -> RuntimeResult<()> {
+        if self.memory.remove(&frame).is_none() {
+            return Err(match self.get_missing_frame_error(frame) {
+                RuntimeErrorKind::UseAfterFree(frame) => RuntimeErrorKind::DoubleFree(frame),
+                error => error,
+            });
+        }
+
+        let (slot, _) = split_frame_id(frame);
+        //? Generations wrap once they no longer fit in a frame id
+        self.generations[slot] =
+            ((self.generations[slot] + 1) & FRAME_SLOT_MASK).max(FIRST_GENERATION);
+        self.free_slots.push(slot);
+        Ok(())
+    }
+
+    /// Returns whether `frame` is the id of an allocated frame
+    pub fn contains_frame(&self, frame: usize) -> bool {
+        self.memory.contains_key(&frame)
+    }
 
-        panic!("Frame not in Heap!");
+    /// Returns the number of allocated frames
+    pub fn get_frame_count(&self) -> usize {
+        self.memory.len()
     }
 
     /// Gets a frame's data with the specified frame id
-    pub fn get_mut_frame(&mut self, frame: usize) -> &mut [u8] {
-        for f in self.memory.iter_mut() {
-            if f.0 == frame {
-                return &mut f.1;
-            }
+    pub fn get_frame(&self, frame: usize) -> RuntimeResult<&[u8]> {
+        match self.memory.get(&frame) {
+            Some(data) => Ok(data),
+            None => Err(self.get_missing_frame_error(frame)),
         }
+    }
 
-        panic!("Frame not in Heap!");
+    /// Gets a frame's data with the specified frame id
+    pub fn get_mut_frame(&mut self, frame: usize) -> RuntimeResult<&mut [u8]> {
+        if !self.memory.contains_key(&frame) {
+            return Err(self.get_missing_frame_error(frame));
+        }
+        Ok(self.memory.get_mut(&frame).unwrap())
     }
 
     /// Gets a single byte out of a frame
-    pub fn index(&self, frame: usize, position: usize) -> u8 {
-        self.get_frame(frame)[position]
+    pub fn index(&self, frame: usize, position: usize) -> RuntimeResult<u8> {
+        Ok(self.index_slice(frame, position, position + 1)?[0])
     }
 
     /// Gets a slice out of a frame
-    pub fn index_slice(&self, frame: usize, start: usize, end: usize) -> &[u8] {
-        &self.get_frame(frame)[start..end]
+    pub fn index_slice(&self, frame: usize, start: usize, end: usize) -> RuntimeResult<&[u8]> {
+        self.get_frame(frame)?
+            .get(start..end)
+            .ok_or(RuntimeErrorKind::IndexOutOfFrame(frame, start, end))
+    }
+
+    /// Gets a mutable slice out of a frame
+    pub fn index_slice_mut(
+        &mut self,
+        frame: usize,
+        start: usize,
+        end: usize,
+    ) -> RuntimeResult<&mut [u8]> {
+        self.get_mut_frame(frame)?
+            .get_mut(start..end)
+            .ok_or(RuntimeErrorKind::IndexOutOfFrame(frame, start, end))
+    }
+
+    /// Returns every allocated frame and its id, ordered by slot
+    pub fn get_frames(&self) -> impl Iterator<Item = (usize, &[u8])> {
+        let mut frames: Vec<(usize, &[u8])> = self
+            .memory
+            .iter()
+            .map(|(frame, data)| (*frame, data.as_slice()))
+            .collect();
+        frames.sort_by_key(|(frame, _)| split_frame_id(*frame).0);
+        frames.into_iter()
     }
 
     /// Writes all data in the heap to a specified folder for debugging
     pub fn dump_bytes(&self, folder_name: &str) {
         fs::create_dir_all(folder_name).unwrap();
-        for i in self.memory.iter() {
-            dump_bytes(format!("{}/heap-{}.bin", folder_name, i.0).as_str(), &(i.1));
+        for (frame, data) in self.get_frames() {
+            dump_bytes(format!("{}/heap-{}.bin", folder_name, frame).as_str(), data);
         }
     }
 }
@@ -71,3 +161,31 @@ impl Default for HeapMemory {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::execution::RuntimeErrorKind;
+    use crate::test_util::{compile_ok, get_output, run};
+
+    #[test]
+    fn reuses_freed_frames() {
+        let source = "ptr p = 0\nalloc p int\np[int] = 3\nptr q = 0\nalloc q int\nq[int] = 4\nfree p\nalloc p int\np[int] = 5\nprint q[int]\nprint p[int]\nfor i in 0..1000\n    ptr t = 0\n    alloc t int\n    free t\nprint q[int]\n";
+        assert_eq!(get_output(source), "4\n5\n4\n");
+    }
+
+    #[test]
+    fn rejects_stale_frame_ids() {
+        let memory =
+            compile_ok("ptr p = 0\nalloc p int\nptr q = p\nfree p\nalloc p int\nprint q[int]\n");
+        assert!(matches!(
+            run(memory).unwrap_err().kind,
+            RuntimeErrorKind::UseAfterFree(_)
+        ));
+
+        let memory = compile_ok("ptr p = 0\nalloc p int\nfree p\nfree p\n");
+        assert!(matches!(
+            run(memory).unwrap_err().kind,
+            RuntimeErrorKind::DoubleFree(_)
+        ));
+    }
+}
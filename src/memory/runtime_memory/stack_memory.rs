@@ -1,74 +1,91 @@
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
 use crate::memory::runtime_memory::dump_bytes;
-use std::collections::LinkedList;
 use std::fs;
 
+/// Number of bytes reserved for the stack before it first needs to grow
+const INITIAL_STACK_CAPACITY: usize = 1024;
+
+/// Stack of frames, newest first. Addresses are relative to the start of the newest frame and
+/// continue into older frames once they pass the end of a frame
+///
+/// Frames are stored contiguously, growing down from the end of `memory`, so the newest frame
+/// starts at the stack pointer and is immediately followed by the frames below it. This makes
+/// resolving an address a single addition
 pub struct StackMemory {
-    memory: LinkedList<Vec<u8>>,
+    memory: Vec<u8>,
+    /// Distance from the start of each frame to the end of `memory`, oldest first. Stored
+    /// relative to the end so that frames don't move when `memory` grows
+    frame_depths: Vec<usize>,
     current_stack: usize,
 }
 
 impl StackMemory {
     pub fn new() -> Self {
         Self {
-            memory: LinkedList::new(),
+            memory: Vec::new(),
+            frame_depths: Vec::new(),
             current_stack: 0,
         }
     }
 
+    /// Total size of every frame
+    fn get_depth(&self) -> usize {
+        self.frame_depths.last().copied().unwrap_or(0)
+    }
+
+    /// Position of the start of the newest frame in `memory`
+    fn get_stack_pointer(&self) -> usize {
+        self.memory.len() - self.get_depth()
+    }
+
     /// Creates a new stack with a specified size
     pub fn create_stack(&mut self, size: usize) {
-        self.memory.push_front(vec![0; size]);
+        let depth = self.get_depth() + size;
+        if depth > self.memory.len() {
+            //? Move existing frames to the end of a larger buffer
+            let capacity = depth.max(self.memory.len() * 2).max(INITIAL_STACK_CAPACITY);
+            let mut memory = vec![0; capacity];
+            memory[capacity - self.get_depth()..]
+                .copy_from_slice(&self.memory[self.get_stack_pointer()..]);
+            self.memory = memory;
+        }
+        let stack_pointer = self.memory.len() - depth;
+        self.memory[stack_pointer..stack_pointer + size].fill(0);
+        self.frame_depths.push(depth);
     }
 
     /// Returns the stack and the position in that stack of a given address
-    pub fn get_stack(&self, mut position: usize) -> (&[u8], usize) {
-        for m in self.memory.iter() {
-            //.skip(self.memory.len() - self.current_stack) {
-            if position >= m.len() {
-                position -= m.len();
-                continue;
-            }
-            return (m, position);
+    ///
+    /// The returned stack starts at the newest frame and includes every older frame so the
+    /// position is the address itself
+    pub fn get_stack(&self, position: usize) -> RuntimeResult<(&[u8], usize)> {
+        if position >= self.get_depth() {
+            return Err(RuntimeErrorKind::IndexOutOfStack(position));
         }
-
-        panic!("Index out of stack!");
+        Ok((&self.memory[self.get_stack_pointer()..], position))
     }
 
     /// Returns the stack and the position in that stack of a given address
-    pub fn get_stack_mut(&mut self, mut position: usize) -> (&mut [u8], usize) {
-        // TODO: Optimise?
-        // let len = self.memory.len();
-        for m in self.memory.iter_mut() {
-            //.skip(len - self.current_stack) {
-            if position >= m.len() {
-                position -= m.len();
-                continue;
-            }
-            return (m, position);
+    pub fn get_stack_mut(&mut self, position: usize) -> RuntimeResult<(&mut [u8], usize)> {
+        if position >= self.get_depth() {
+            return Err(RuntimeErrorKind::IndexOutOfStack(position));
         }
-
-        panic!("Index out of stack!");
+        let stack_pointer = self.get_stack_pointer();
+        Ok((&mut self.memory[stack_pointer..], position))
     }
 
     /// Returns a single byte at a given address
-    pub fn index(&self, position: usize) -> u8 {
-        let (stack, transformed_position) = self.get_stack(position);
-        stack[transformed_position]
+    pub fn index(&self, position: usize) -> RuntimeResult<u8> {
+        let (stack, transformed_position) = self.get_stack(position)?;
+        Ok(stack[transformed_position])
     }
 
     /// Returns a slice of the data in a stack
-    pub fn index_slice(&self, mut start: usize, mut end: usize) -> &[u8] {
-        for m in self.memory.iter() {
-            //.skip(self.memory.len() - self.current_stack) {
-            if start >= m.len() {
-                start -= m.len();
-                end -= m.len();
-                continue;
-            }
-            return &m[start..end];
-        }
-
-        panic!("Index out of stack!");
+    pub fn index_slice(&self, start: usize, end: usize) -> RuntimeResult<&[u8]> {
+        let stack = &self.memory[self.get_stack_pointer()..];
+        stack
+            .get(start..end)
+            .ok_or(RuntimeErrorKind::IndexOutOfStack(start.max(stack.len())))
     }
 
     /// DEPRECIATED
@@ -77,23 +94,30 @@ impl StackMemory {
     }
 
     /// Removes a stack
-    pub fn stack_down_and_delete(&mut self) {
-        self.memory
-            .pop_front()
-            .expect("Tried to stack down when there are no stacks!");
+    pub fn stack_down_and_delete(&mut self) -> RuntimeResult<()> {
+        self.frame_depths.pop().ok_or(RuntimeErrorKind::NoStack)?;
         self.current_stack -= 1;
+        Ok(())
     }
 
     /// Gets the current stack depth
     pub fn get_current_level(&self) -> usize {
-        // self.current_stack
-        self.memory.len()
+        self.frame_depths.len()
+    }
+
+    /// Returns every stack, newest first
+    pub fn get_stacks(&self) -> impl Iterator<Item = &[u8]> {
+        let end = self.memory.len();
+        (0..self.frame_depths.len()).rev().map(move |i| {
+            let older_depth = i.checked_sub(1).map_or(0, |j| self.frame_depths[j]);
+            &self.memory[end - self.frame_depths[i]..end - older_depth]
+        })
     }
 
     /// Writes all data to a specified folder for debugging
     pub fn dump_bytes(&self, folder_name: &str) {
         fs::create_dir_all(folder_name).unwrap();
-        for i in self.memory.iter().enumerate() {
+        for i in self.get_stacks().enumerate() {
             dump_bytes(format!("{}/stack-{}.bin", folder_name, i.0).as_str(), i.1);
         }
     }
@@ -104,3 +128,20 @@ impl Default for StackMemory {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::get_output;
+
+    #[test]
+    fn recursive_calls_keep_their_frames() {
+        let source = "fn fib (int n) -> int\n    if n < 2\n        return n\n    int a = fib (n - 1)\n    int b = fib (n - 2)\n    return a + b\nprint fib (15)\n";
+        assert_eq!(get_output(source), "610\n");
+    }
+
+    #[test]
+    fn calls_leave_caller_variables_unchanged() {
+        let source = "fn inner (int x) -> int\n    int y = x * 3\n    return y\nfn outer (int x) -> int\n    int y = x + 1\n    int z = inner (y)\n    print y\n    return z\nint y = 100\nprint outer (4)\nprint y\n";
+        assert_eq!(get_output(source), "5\n15\n100\n");
+    }
+}
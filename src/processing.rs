@@ -2,8 +2,10 @@ pub mod arithmetic;
 pub mod blocks;
 pub mod instructions;
 pub mod lines;
+pub mod optimiser;
 pub mod preprocessor;
 pub mod processor;
 pub mod reference_manager;
+pub mod std_library;
 pub mod symbols;
 pub mod types;
@@ -1,10 +1,15 @@
+use crate::address::{Address, CloneableBox};
+use crate::errors::{CompileError, CompileResult, ErrorCode};
 use crate::memory::MemoryManager;
 use crate::processing::blocks::StackSizes;
 use crate::processing::reference_manager::ReferenceStack;
 use crate::processing::symbols::{Keyword, Operator, Symbol, TypeSymbol};
 use crate::processing::types::{Type, TypeFactory};
+use crate::util::must_use_option::MustUseOption;
 use crate::util::ref_or_box::RefOrBox;
 
+mod expression_parser;
+
 /*
 macro_rules! get_variable {
     ($output: expr, $symbol: expr, $program_memory: expr, $reference_stack: expr, $stack_sizes: expr) => {
@@ -21,7 +26,7 @@ macro_rules! get_variable {
                 store = evaluate_arithmetic_to_any_type(section, $program_memory, $reference_stack, $stack_sizes)?;
                 Ok(&store)
             }
-            _ => Err("Operator must be followed by a Literal or Name".to_string())
+            _ => Err("Operator must be followed by a Literal or Name".to_string().into())
         };
     };
 }
@@ -68,7 +73,7 @@ pub fn evaluate_arithmetic_into_type(
     stack_sizes: &mut StackSizes,
     // run_before_last_step: Option<fn(&mut MemoryManager, &mut StackSizes)>,
     // offset: usize
-) -> Result<(), String> {
+) -> CompileResult<()> {
     evaluate_arithmetic_section(
         section,
         &ReturnOptions::IntoType(destination), //, run_before_last_step, offset),
@@ -86,7 +91,7 @@ pub fn evaluate_arithmetic_to_types<'a>(
     program_memory: &mut MemoryManager,
     reference_stack: &'a ReferenceStack,
     stack_sizes: &mut StackSizes,
-) -> Result<RefOrBox<'a, dyn Type + 'a>, String> {
+) -> CompileResult<RefOrBox<'a, dyn Type + 'a>> {
     Ok(evaluate_arithmetic_section(
         section,
         &ReturnOptions::OneOfTypes(return_type_options),
@@ -103,7 +108,7 @@ pub fn evaluate_arithmetic_to_any_type<'a>(
     program_memory: &mut MemoryManager,
     reference_stack: &'a ReferenceStack,
     stack_sizes: &mut StackSizes,
-) -> Result<RefOrBox<'a, dyn Type + 'a>, String> {
+) -> CompileResult<RefOrBox<'a, dyn Type + 'a>> {
     Ok(evaluate_arithmetic_section(
         section,
         &ReturnOptions::AnyType,
@@ -120,14 +125,16 @@ fn evaluate_arithmetic_section<'a>(
     program_memory: &mut MemoryManager,
     reference_stack: &'a ReferenceStack,
     stack_sizes: &mut StackSizes,
-) -> Result<Option<RefOrBox<'a, dyn Type + 'a>>, String> {
+) -> CompileResult<Option<RefOrBox<'a, dyn Type + 'a>>> {
     //noinspection SpellCheckingInspection
-    fn get_formatting_error() -> String {
-        "Arithmetic sections must be formated [Operator] [Value], [Value] [Operator] [Value] or [Value] as [Type]".to_string()
+    fn get_formatting_error() -> CompileError {
+        "Arithmetic sections must be formated [Operator] [Value], [Value] [Operator] [Value] or [Value] as [Type]".into()
     }
 
     if section.is_empty() {
-        return Err("Cannot evaluate a section with no symbols".to_string());
+        return Err("Cannot evaluate a section with no symbols"
+            .to_string()
+            .into());
     }
 
     // ? No operation
@@ -141,11 +148,31 @@ fn evaluate_arithmetic_section<'a>(
         );
     }
 
+    // ? Group by operator precedence so that only one operation is handled at a time
+    let grouped = expression_parser::group_section(section)?;
+    let section = grouped.as_slice();
+
+    // ? Function call e.g. add (a, b)
+    if let [Symbol::Name(name), arguments] = section {
+        if let Some(arguments) = arguments.get_list_items() {
+            return handle_call(
+                name,
+                arguments,
+                return_options,
+                program_memory,
+                reference_stack,
+                stack_sizes,
+            );
+        }
+    }
+
     match &section[0] {
         // ? Prefix operator e.g. ! A
         Symbol::Operator(operator) => {
             if section.len() != 2 {
-                return Err("Operator must be followed by a Literal or Name".to_string());
+                return Err("Operator must be followed by a Literal or Name"
+                    .to_string()
+                    .into());
             }
 
             let return_option = if let Some(preference) = return_options.get_prefered_type() {
@@ -238,7 +265,7 @@ fn evaluate_arithmetic_section<'a>(
     }
 }
 
-fn incorrect_type_error(expected: &[TypeSymbol], received: &[TypeSymbol]) -> String {
+fn incorrect_type_error(expected: &[TypeSymbol], received: &[TypeSymbol]) -> CompileError {
     let mut expected_text = "[any]".to_string();
     if !expected.is_empty() {
         expected_text = "[".to_string();
@@ -257,9 +284,12 @@ fn incorrect_type_error(expected: &[TypeSymbol], received: &[TypeSymbol]) -> Str
         received_text = received_text[..received_text.len() - 2].to_string();
     }
 
-    format!(
-        "Expected type {}, received {}",
-        expected_text, received_text
+    CompileError::new(
+        ErrorCode::TypeMismatch,
+        format!(
+            "Expected type {}, received {}",
+            expected_text, received_text
+        ),
     )
 }
 
@@ -269,7 +299,7 @@ fn handle_single_symbol<'a>(
     program_memory: &mut MemoryManager,
     reference_stack: &'a ReferenceStack,
     stack_sizes: &mut StackSizes,
-) -> Result<Option<RefOrBox<'a, dyn Type + 'a>>, String> {
+) -> CompileResult<Option<RefOrBox<'a, dyn Type + 'a>>> {
     match symbol {
         Symbol::Name(name) => {
             let variable = reference_stack.get_reference(name)?.get_variable_ref()?;
@@ -287,7 +317,7 @@ fn handle_single_symbol<'a>(
                 }
                 ReturnOptions::OneOfTypes(types) => {
                     let variable_type = variable.get_type_symbol();
-                    if !types.is_empty() && !types.iter().any(|t| *t == variable_type) {
+                    if !types.is_empty() && !types.contains(&variable_type) {
                         Err(incorrect_type_error(types, &[variable_type]))
                     } else {
                         Ok(Some(RefOrBox::from_ref(variable)))
@@ -330,7 +360,7 @@ fn handle_single_symbol<'a>(
                         None,
                     )?;
                     let default_type_type = default_type.get_type_symbol();
-                    if !types.is_empty() && !types.iter().any(|t| *t == default_type_type) {
+                    if !types.is_empty() && !types.contains(&default_type_type) {
                         Err(incorrect_type_error(types, &[default_type_type]))
                     } else {
                         Ok(Some(RefOrBox::from_box(default_type)))
@@ -338,7 +368,75 @@ fn handle_single_symbol<'a>(
                 }
             }
         }
+        Symbol::Indexer(..) | Symbol::Member(..) => {
+            let item = match symbol {
+                Symbol::Indexer(indexed, index) => {
+                    evaluate_indexer(indexed, index, program_memory, reference_stack, stack_sizes)?
+                }
+                Symbol::Member(value, path) => {
+                    evaluate_member(value, path, program_memory, reference_stack, stack_sizes)?
+                }
+                _ => unreachable!(),
+            };
+            match return_options {
+                ReturnOptions::IntoType(output) => {
+                    output.runtime_copy_from(item.as_ref(), program_memory)?;
+                    Ok(None)
+                }
+                ReturnOptions::AnyType | ReturnOptions::PreferType(_) => {
+                    Ok(Some(RefOrBox::from_box(item)))
+                }
+                ReturnOptions::OneOfTypes(types) => {
+                    let item_type = item.get_type_symbol();
+                    if !types.is_empty() && !types.contains(&item_type) {
+                        Err(incorrect_type_error(types, &[item_type]))
+                    } else {
+                        Ok(Some(RefOrBox::from_box(item)))
+                    }
+                }
+            }
+        }
+        //? Initialises each field of a class instance or item of an array e.g. `Point p = (1, 2)`
+        Symbol::List(items) => {
+            let ReturnOptions::IntoType(output) = return_options else {
+                return Err(
+                    "Lists can only be used to initialise a class instance or array"
+                        .to_string()
+                        .into(),
+                );
+            };
+
+            initialise_from_list(items, *output, program_memory, reference_stack, stack_sizes)?;
+            Ok(None)
+        }
         Symbol::BracketedSection(section) => {
+            //? A single bracketed value initialises a type with fields as a list of one item e.g.
+            //? `Box b = (1)`, unless it is a variable of that type e.g. `Point p = (q)`
+            if let ReturnOptions::IntoType(output) = return_options {
+                let has_fields = output
+                    .get_list_items()
+                    .is_ok_and(|fields| !fields.is_empty());
+                let is_same_type = match section.as_slice() {
+                    [Symbol::Name(name)] => reference_stack
+                        .get_reference(name)
+                        .and_then(|reference| reference.get_variable_ref())
+                        .is_ok_and(|variable| {
+                            variable.get_type_symbol() == output.get_type_symbol()
+                        }),
+                    _ => false,
+                };
+                if has_fields && !is_same_type {
+                    initialise_from_list(
+                        std::slice::from_ref(section),
+                        *output,
+                        program_memory,
+                        reference_stack,
+                        stack_sizes,
+                    )?;
+                    return Ok(None);
+                }
+            }
+
             // return_options.remove_return_into_type_options();
             evaluate_arithmetic_section(
                 section,
@@ -348,20 +446,197 @@ fn handle_single_symbol<'a>(
                 stack_sizes,
             )
         }
-        _ => Err("Expected an expression".to_string()),
+        _ => Err("Expected an expression".to_string().into()),
+    }
+}
+
+/// Initialises each field of a class instance or item of an array from a list of values
+fn initialise_from_list(
+    items: &[Vec<Symbol>],
+    output: &dyn Type,
+    program_memory: &mut MemoryManager,
+    reference_stack: &ReferenceStack,
+    stack_sizes: &mut StackSizes,
+) -> CompileResult<()> {
+    let fields = output.get_list_items()?;
+    if fields.is_empty() {
+        return Err(CompileError::new(
+            ErrorCode::TypeMismatch,
+            format!(
+                "Lists cannot be used to initialise {}",
+                output.get_type_symbol()
+            ),
+        ));
+    }
+    if fields.len() != items.len() {
+        return Err(format!(
+            "Expected {} values - received {}",
+            fields.len(),
+            items.len()
+        )
+        .into());
+    }
+
+    for (item, field) in items.iter().zip(&fields) {
+        evaluate_arithmetic_into_type(
+            item,
+            field.as_ref(),
+            program_memory,
+            reference_stack,
+            stack_sizes,
+        )?;
+    }
+    Ok(())
+}
+
+fn handle_call<'a>(
+    name: &[String],
+    arguments: Vec<Vec<Symbol>>,
+    return_options: &ReturnOptions,
+    program_memory: &mut MemoryManager,
+    reference_stack: &'a ReferenceStack,
+    stack_sizes: &mut StackSizes,
+) -> CompileResult<Option<RefOrBox<'a, dyn Type + 'a>>> {
+    let (function_reference, arguments) =
+        reference_stack.get_function_and_arguments(name, arguments)?;
+
+    let (returned, incomplete_function_call) = function_reference.call(
+        &arguments,
+        true,
+        program_memory,
+        reference_stack,
+        stack_sizes,
+    )?;
+    if let MustUseOption::Some(incomplete_function_call) = incomplete_function_call {
+        function_reference.add_incomplete_function_call(incomplete_function_call);
+    }
+    let returned = returned.unwrap();
+
+    match return_options {
+        ReturnOptions::IntoType(output) => {
+            output.runtime_copy_from(returned.as_ref(), program_memory)?;
+            Ok(None)
+        }
+        ReturnOptions::AnyType | ReturnOptions::PreferType(_) => {
+            Ok(Some(RefOrBox::from_box(returned)))
+        }
+        ReturnOptions::OneOfTypes(types) => {
+            let returned_type = returned.get_type_symbol();
+            if !types.is_empty() && !types.contains(&returned_type) {
+                Err(incorrect_type_error(types, &[returned_type]))
+            } else {
+                Ok(Some(RefOrBox::from_box(returned)))
+            }
+        }
+    }
+}
+
+/// Evaluates an indexer e.g. `text[i]` to the item it refers to
+pub fn evaluate_indexer(
+    indexed: &Symbol,
+    index: &[Symbol],
+    program_memory: &mut MemoryManager,
+    reference_stack: &ReferenceStack,
+    stack_sizes: &mut StackSizes,
+) -> CompileResult<Box<dyn Type>> {
+    let indexed = evaluate_arithmetic_to_any_type(
+        std::slice::from_ref(indexed),
+        program_memory,
+        reference_stack,
+        stack_sizes,
+    )?;
+
+    //? Dereferencing a pointer to a heap frame e.g. `p[int]`
+    if let [index] = index {
+        if let Ok(mut dereferenced) =
+            TypeFactory::get_unallocated_type_from_symbol(index, reference_stack)
+        {
+            if indexed.as_ref().get_type_symbol() != TypeSymbol::Pointer {
+                return Err(format!(
+                    "Only {} can be dereferenced - received {}",
+                    TypeSymbol::Pointer,
+                    indexed.as_ref().get_type_symbol()
+                )
+                .into());
+            }
+
+            let start = Address::Immediate(Vec::from(0usize.to_le_bytes()));
+            dereferenced.set_address(Address::HeapIndexed(
+                CloneableBox::new(indexed.as_ref().get_address().clone()),
+                CloneableBox::new(start.clone()),
+                CloneableBox::new(start),
+            ));
+            return Ok(dereferenced);
+        }
     }
+
+    let index = evaluate_arithmetic_to_types(
+        index,
+        &[TypeSymbol::Integer, TypeSymbol::Pointer],
+        program_memory,
+        reference_stack,
+        stack_sizes,
+    )?;
+
+    indexed
+        .as_ref()
+        .get_indexed(index.as_ref(), program_memory, stack_sizes)
+}
+
+/// Evaluates the field of a value that isn't a variable e.g. `h[Point].x`
+pub fn evaluate_member(
+    value: &Symbol,
+    path: &[String],
+    program_memory: &mut MemoryManager,
+    reference_stack: &ReferenceStack,
+    stack_sizes: &mut StackSizes,
+) -> CompileResult<Box<dyn Type>> {
+    let value = evaluate_arithmetic_to_any_type(
+        std::slice::from_ref(value),
+        program_memory,
+        reference_stack,
+        stack_sizes,
+    )?;
+
+    let mut member = value.as_ref().duplicate();
+    for name in path {
+        member = get_field(member.as_ref(), name)?;
+    }
+    Ok(member)
+}
+
+/// Returns the field of `value` called `name`
+pub fn get_field(value: &dyn Type, name: &str) -> CompileResult<Box<dyn Type>> {
+    value
+        .get_fields()?
+        .into_iter()
+        .find_map(|(field_name, field)| (field_name == name).then_some(field))
+        .ok_or_else(|| {
+            CompileError::new(
+                ErrorCode::UnknownReference,
+                format!(
+                    "{} has no field '{}'",
+                    value
+                        .get_class_name()
+                        .map_or_else(|| value.get_type_symbol().to_string(), str::to_string),
+                    name
+                ),
+            )
+            .with_name(&[name.to_string()])
+        })
 }
 
 fn operator_not_implemented_error(
     lhs: &TypeSymbol,
     operator: &Operator,
     rhs: Option<&TypeSymbol>,
-) -> String {
-    if let Some(rhs) = rhs {
+) -> CompileError {
+    let message = if let Some(rhs) = rhs {
         format!("{} not supported between {} and {}", operator, lhs, rhs)
     } else {
         format!("{} not supported on {}", operator, lhs)
-    }
+    };
+    CompileError::new(ErrorCode::TypeMismatch, message)
 }
 
 // TODO: Consider removing unused arguments
@@ -371,7 +646,7 @@ fn handle_prefix_operation<'a>(
     return_options: &ReturnOptions,
     program_memory: &mut MemoryManager,
     stack_sizes: &mut StackSizes,
-) -> Result<Option<RefOrBox<'a, dyn Type + 'a>>, String> {
+) -> CompileResult<Option<RefOrBox<'a, dyn Type + 'a>>> {
     let operand = operand.as_ref();
 
     match return_options {
@@ -393,7 +668,7 @@ fn handle_prefix_operation<'a>(
                 ))
             } else {
                 let mut new_type = TypeFactory::get_unallocated_type(&return_types[0])?;
-                new_type.allocate_variable(stack_sizes, program_memory)?;
+                TypeFactory::allocate_temporary(new_type.as_mut(), stack_sizes, program_memory)?;
                 operand.operate_prefix(operator, new_type.as_ref(), program_memory, stack_sizes)?;
 
                 Ok(Some(RefOrBox::from_box(new_type)))
@@ -421,7 +696,7 @@ fn handle_prefix_operation<'a>(
 
             if let Some(return_type) = return_type {
                 let mut new_type = TypeFactory::get_unallocated_type(return_type)?;
-                new_type.allocate_variable(stack_sizes, program_memory)?;
+                TypeFactory::allocate_temporary(new_type.as_mut(), stack_sizes, program_memory)?;
                 operand.operate_prefix(operator, new_type.as_ref(), program_memory, stack_sizes)?;
 
                 Ok(Some(RefOrBox::from_box(new_type)))
@@ -439,7 +714,7 @@ fn handle_operation<'a>(
     return_options: &ReturnOptions,
     program_memory: &mut MemoryManager,
     stack_sizes: &mut StackSizes,
-) -> Result<Option<RefOrBox<'a, dyn Type + 'a>>, String> {
+) -> CompileResult<Option<RefOrBox<'a, dyn Type + 'a>>> {
     let lhs = lhs.as_ref();
     let rhs = rhs.as_ref();
 
@@ -462,7 +737,7 @@ fn handle_operation<'a>(
                 ))
             } else {
                 let mut new_type = TypeFactory::get_unallocated_type(&return_types[0])?;
-                new_type.allocate_variable(stack_sizes, program_memory)?;
+                TypeFactory::allocate_temporary(new_type.as_mut(), stack_sizes, program_memory)?;
                 lhs.operate(
                     operator,
                     rhs,
@@ -496,7 +771,7 @@ fn handle_operation<'a>(
 
             if let Some(return_type) = return_type {
                 let mut new_type = TypeFactory::get_unallocated_type(return_type)?;
-                new_type.allocate_variable(stack_sizes, program_memory)?;
+                TypeFactory::allocate_temporary(new_type.as_mut(), stack_sizes, program_memory)?;
                 lhs.operate(
                     operator,
                     rhs,
@@ -520,7 +795,7 @@ fn handle_casting<'a>(
     program_memory: &mut MemoryManager,
     reference_stack: &ReferenceStack,
     stack_sizes: &mut StackSizes,
-) -> Result<Option<RefOrBox<'a, dyn Type + 'a>>, String> {
+) -> CompileResult<Option<RefOrBox<'a, dyn Type + 'a>>> {
     match symbol {
         Symbol::Literal(literal) => {
             // ? Ignore cast if going into correct type
@@ -532,20 +807,25 @@ fn handle_casting<'a>(
             }
 
             let mut new_type = TypeFactory::get_unallocated_type(type_symbol)?;
-            new_type.allocate_variable(stack_sizes, program_memory)?;
+            TypeFactory::allocate_temporary(new_type.as_mut(), stack_sizes, program_memory)?;
             new_type.runtime_copy_from_literal(literal, program_memory)?;
 
             match return_options {
                 ReturnOptions::IntoType(output) => {
-                    output.runtime_copy_from(new_type.as_ref(), program_memory)?;
+                    output.runtime_cast_from(new_type.as_ref(), program_memory)?;
                     Ok(None)
                 }
                 ReturnOptions::OneOfTypes(return_types) => {
-                    if return_types.iter().any(|t| *t == *type_symbol) {
+                    if return_types.contains(type_symbol) {
                         Ok(Some(RefOrBox::from_box(new_type)))
                     } else {
-                        let return_type = TypeFactory::get_unallocated_type(&return_types[0])?;
-                        return_type.runtime_copy_from(new_type.as_ref(), program_memory)?;
+                        let mut return_type = TypeFactory::get_unallocated_type(&return_types[0])?;
+                        TypeFactory::allocate_temporary(
+                            return_type.as_mut(),
+                            stack_sizes,
+                            program_memory,
+                        )?;
+                        return_type.runtime_cast_from(new_type.as_ref(), program_memory)?;
                         Ok(Some(RefOrBox::from_box(return_type)))
                     }
                 }
@@ -556,7 +836,7 @@ fn handle_casting<'a>(
         }
         _ => {
             let value = evaluate_arithmetic_to_any_type(
-                &[symbol.clone()],
+                std::slice::from_ref(symbol),
                 program_memory,
                 reference_stack,
                 stack_sizes,
@@ -566,26 +846,31 @@ fn handle_casting<'a>(
             // ? Ignore cast if going into correct type
             if let ReturnOptions::IntoType(output) = return_options {
                 if output.get_type_symbol() == *type_symbol {
-                    output.runtime_copy_from(value, program_memory)?;
+                    output.runtime_cast_from(value, program_memory)?;
                     return Ok(None);
                 }
             }
 
             let mut new_type = TypeFactory::get_unallocated_type(type_symbol)?;
-            new_type.allocate_variable(stack_sizes, program_memory)?;
-            new_type.runtime_copy_from(value, program_memory)?;
+            TypeFactory::allocate_temporary(new_type.as_mut(), stack_sizes, program_memory)?;
+            new_type.runtime_cast_from(value, program_memory)?;
 
             match return_options {
                 ReturnOptions::IntoType(output) => {
-                    output.runtime_copy_from(new_type.as_ref(), program_memory)?;
+                    output.runtime_cast_from(new_type.as_ref(), program_memory)?;
                     Ok(None)
                 }
                 ReturnOptions::OneOfTypes(return_types) => {
-                    if return_types.iter().any(|t| *t == *type_symbol) {
+                    if return_types.contains(type_symbol) {
                         Ok(Some(RefOrBox::from_box(new_type)))
                     } else {
-                        let return_type = TypeFactory::get_unallocated_type(&return_types[0])?;
-                        return_type.runtime_copy_from(new_type.as_ref(), program_memory)?;
+                        let mut return_type = TypeFactory::get_unallocated_type(&return_types[0])?;
+                        TypeFactory::allocate_temporary(
+                            return_type.as_mut(),
+                            stack_sizes,
+                            program_memory,
+                        )?;
+                        return_type.runtime_cast_from(new_type.as_ref(), program_memory)?;
                         Ok(Some(RefOrBox::from_box(return_type)))
                     }
                 }
@@ -596,3 +881,78 @@ fn handle_casting<'a>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{compile_err, get_output};
+
+    /// Runs `source` followed by a print of `value`, checking it prints `expected`
+    fn assert_value(source: &str, value: &str, expected: i64) {
+        assert_eq!(
+            get_output(&format!("{}print {}\n", source, value)),
+            format!("{}\n", expected)
+        );
+    }
+
+    #[test]
+    fn initialises_single_field_class_from_bracketed_value() {
+        assert_value("class Box\n    int v\nBox b = (7)\nint v = b.v\n", "v", 7);
+        assert_value(
+            "class Box\n    int v\nint x = 3\nBox b = (x + 1)\nint v = b.v\n",
+            "v",
+            4,
+        );
+    }
+
+    #[test]
+    fn initialises_single_item_array_from_bracketed_value() {
+        assert_value("int[1] a = (5)\nint v = a[0]\n", "v", 5);
+    }
+
+    #[test]
+    fn initialises_nested_single_field_classes() {
+        assert_value(
+            "class Point\n    int x\n    int y\nclass Wrapper\n    Point p\nWrapper w = ((1, 2))\nint v = w.p.y\n",
+            "v",
+            2,
+        );
+    }
+
+    #[test]
+    fn copies_bracketed_variable_of_same_type() {
+        assert_value(
+            "class Box\n    int v\nBox b = (7)\nBox c = (b)\nint v = c.v\n",
+            "v",
+            7,
+        );
+        assert_value(
+            "class Point\n    int x\n    int y\nPoint p = (1, 2)\nPoint q = (p)\nint v = q.y\n",
+            "v",
+            2,
+        );
+    }
+
+    #[test]
+    fn rejects_too_few_values() {
+        let errors = compile_err("class Point\n    int x\n    int y\nPoint p = (3)\n");
+        assert_eq!(errors[0].message, "Expected 2 values - received 1");
+    }
+
+    #[test]
+    fn accesses_fields_of_dereferenced_values() {
+        assert_eq!(
+            get_output(
+                "class Point\n    int x\n    int y\nclass Line\n    Point a\n    Point b\nptr h = 0\nalloc h Point\nh[Point] = (1, 2)\nprint h[Point].x\nh[Point].y = 5\nh[Point].x += 10\nprint h[Point].x + h[Point].y\nptr l = 0\nalloc l Line\nl[Line].b.y = 7\nprint l[Line].b.y\nptr s = 0\nalloc s str\ns[str] = \"hey\"\nprint s[str].length\n"
+            ),
+            "1\n16\n7\n3\n"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields_of_dereferenced_values() {
+        let errors = compile_err("ptr h = 0\nalloc h int\nprint h[int].x\n");
+        assert_eq!(errors[0].message, "Integer has no field 'x'");
+        let errors = compile_err("ptr s = 0\nalloc s str\ns[str].length = 3\n");
+        assert_eq!(errors[0].message, "length is read-only");
+    }
+}
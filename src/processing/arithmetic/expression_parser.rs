@@ -0,0 +1,98 @@
+use crate::errors::CompileResult;
+use crate::processing::symbols::{Keyword, Symbol, CAST_BINDING_POWER, PREFIX_BINDING_POWER};
+
+/// Groups a section by operator precedence so that every `BracketedSection` in the result is
+/// formatted [Operator] [Value], [Value] [Operator] [Value], [Value] as [Type] or [Name] [List]
+/// e.g. `a + b * - c` -> `(a + (b * (- c)))`
+pub fn group_section(section: &[Symbol]) -> CompileResult<Vec<Symbol>> {
+    let mut position = 0;
+    let grouped = parse_expression(section, &mut position, 0)?;
+
+    if position != section.len() {
+        return Err(format!(
+            "Unexpected symbol '{}' in arithmetic section",
+            section[position]
+        )
+        .into());
+    }
+
+    Ok(match grouped {
+        Symbol::BracketedSection(grouped) => grouped,
+        other => vec![other],
+    })
+}
+
+/// Pratt parser - parses operations with a binding power of at least `min_binding_power`
+fn parse_expression(
+    section: &[Symbol],
+    position: &mut usize,
+    min_binding_power: u8,
+) -> CompileResult<Symbol> {
+    let Some(first) = section.get(*position) else {
+        return Err("Expected a value at the end of the arithmetic section"
+            .to_string()
+            .into());
+    };
+    *position += 1;
+
+    let mut lhs = match first {
+        // ? Prefix operator e.g. ! A
+        Symbol::Operator(operator) => {
+            if !operator.is_prefix() {
+                return Err(format!("Operator {} cannot be used as a prefix", operator).into());
+            }
+            let operand = parse_expression(section, position, PREFIX_BINDING_POWER)?;
+            Symbol::BracketedSection(vec![first.clone(), operand])
+        }
+        // ? Function call e.g. add (a, b)
+        Symbol::Name(_)
+            if section
+                .get(*position)
+                .is_some_and(|s| s.get_list_items().is_some()) =>
+        {
+            *position += 1;
+            Symbol::BracketedSection(vec![first.clone(), section[*position - 1].clone()])
+        }
+        Symbol::Keyword(_) | Symbol::Punctuation(_) | Symbol::Type(_) => {
+            return Err(format!("Expected a value - received '{}'", first).into());
+        }
+        value => value.clone(),
+    };
+
+    while let Some(next) = section.get(*position) {
+        match next {
+            // ? Casting e.g. A as bool
+            Symbol::Keyword(Keyword::As) => {
+                if CAST_BINDING_POWER < min_binding_power {
+                    break;
+                }
+                let Some(type_symbol @ Symbol::Type(_)) = section.get(*position + 1) else {
+                    return Err("'as' must be followed by a Type".to_string().into());
+                };
+                *position += 2;
+                lhs = Symbol::BracketedSection(vec![lhs, next.clone(), type_symbol.clone()]);
+            }
+            // ? Normal operation e.g. A + B
+            Symbol::Operator(operator) => {
+                let Some((left_binding_power, right_binding_power)) =
+                    operator.get_infix_binding_power()
+                else {
+                    return Err(
+                        format!("Operator {} cannot be used between values", operator).into(),
+                    );
+                };
+                if left_binding_power < min_binding_power {
+                    break;
+                }
+                *position += 1;
+                let rhs = parse_expression(section, position, right_binding_power)?;
+                lhs = Symbol::BracketedSection(vec![lhs, next.clone(), rhs]);
+            }
+            other => {
+                return Err(format!("Expected an operator or 'as' - received '{}'", other).into())
+            }
+        }
+    }
+
+    Ok(lhs)
+}
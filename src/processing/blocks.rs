@@ -1,7 +1,10 @@
+use crate::errors::CompileResult;
 pub mod base_block;
 pub mod class_block;
+pub mod for_block;
 pub mod function_block;
 pub mod if_block;
+pub mod loop_block;
 pub mod while_block;
 
 use crate::memory::MemoryManager;
@@ -14,8 +17,10 @@ use crate::util::warn;
 pub enum BlockType {
     Base,
     Class,
+    For,
     Function,
     If,
+    Loop,
     While,
 }
 
@@ -29,7 +34,7 @@ pub trait BlockHandler {
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         symbol_line: &[Symbol],
-    ) -> Result<(), String>;
+    ) -> CompileResult<()>;
 
     /// Try to exit block
     /// Returns `Ok(true)` if block exit is successful
@@ -39,7 +44,7 @@ pub trait BlockHandler {
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         _symbol_line: &[Symbol],
-    ) -> Result<bool, String> {
+    ) -> CompileResult<bool> {
         self.on_forced_exit(program_memory, reference_stack, stack_sizes)?;
         Ok(true)
     }
@@ -50,26 +55,37 @@ pub trait BlockHandler {
         program_memory: &mut MemoryManager,
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
-    ) -> Result<(), String>;
+    ) -> CompileResult<()>;
 
     /// Break from block e.g. while
-    fn on_break(&mut self, _program_memory: &mut MemoryManager) -> Result<bool, String> {
+    fn on_break(&mut self, _program_memory: &mut MemoryManager) -> CompileResult<bool> {
         Ok(false)
     }
 
     /// Continue block e.g. while
-    fn on_continue(&mut self, _program_memory: &mut MemoryManager) -> Result<bool, String> {
+    fn on_continue(&mut self, _program_memory: &mut MemoryManager) -> CompileResult<bool> {
+        Ok(false)
+    }
+
+    /// Return from block e.g. function. `value` is the (possibly empty) expression to return
+    fn on_return(
+        &mut self,
+        _program_memory: &mut MemoryManager,
+        _reference_stack: &mut ReferenceStack,
+        _stack_sizes: &mut StackSizes,
+        _value: &[Symbol],
+    ) -> CompileResult<bool> {
         Ok(false)
     }
 
     /// Called every time a new line is processed with the block below itself. Used
     /// for preventing certain block inside other block e.g. only functions being allowed
     /// in classes
-    fn update_sub_block(&mut self, _block_type: Option<BlockType>) -> Result<(), String> {
+    fn update_sub_block(&mut self, _block_type: Option<BlockType>) -> CompileResult<()> {
         Ok(())
     }
 
-    fn handle_line(&mut self, _line: &[Symbol]) -> Result<(), String> {
+    fn handle_line(&mut self, _line: &[Symbol]) -> CompileResult<()> {
         panic!("This block can't handle lines!");
     }
 }
@@ -91,6 +107,11 @@ impl StackSizes {
         self.sizes.pop();
     }
 
+    /// Returns the number of stacks
+    pub fn get_depth(&self) -> usize {
+        self.sizes.len()
+    }
+
     /// Returns the size of the topmost stack
     pub fn get_stack_size(&self) -> usize {
         *self
@@ -170,20 +191,33 @@ impl BlockCoordinator {
         mut handler: Box<dyn BlockHandler>,
         program_memory: &mut MemoryManager,
         symbol_line: &[Symbol],
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
+        let reference_depth_limit = self.reference_stack.get_reference_depth_limit();
+        let stack_depth = self.stack_sizes.get_depth();
         self.reference_stack.add_handler();
+        let reference_depth = self.reference_stack.get_depth();
+
         let (reference_stack, stack_sizes) = self.get_reference_stack_and_stack_sizes();
-        let r = handler.on_entry(program_memory, reference_stack, stack_sizes, symbol_line);
+        if let Err(e) = handler.on_entry(program_memory, reference_stack, stack_sizes, symbol_line)
+        {
+            //? The block was never entered so can't be exited - undo what it set up instead
+            self.reference_stack
+                .set_reference_depth_limit(reference_depth_limit);
+            for _ in reference_depth..=self.reference_stack.get_depth() {
+                self.reference_stack.remove_handler(program_memory);
+            }
+            while self.stack_sizes.get_depth() > stack_depth {
+                self.stack_sizes.remove_stack();
+            }
+            return Err(e);
+        }
         self.stack.push(handler);
         // self.stack_sizes.add_stack();
-        r
+        Ok(())
     }
 
     /// Break from block e.g. while
-    pub fn break_block_handler(
-        &mut self,
-        program_memory: &mut MemoryManager,
-    ) -> Result<(), String> {
+    pub fn break_block_handler(&mut self, program_memory: &mut MemoryManager) -> CompileResult<()> {
         let mut success = false;
         for h in self.stack.iter_mut().rev() {
             if h.on_break(program_memory)? {
@@ -193,7 +227,9 @@ impl BlockCoordinator {
         }
 
         if !success {
-            return Err("None of the scopes 'break' is in support breaking".to_string());
+            return Err("None of the scopes 'break' is in support breaking"
+                .to_string()
+                .into());
         }
         Ok(())
     }
@@ -202,7 +238,7 @@ impl BlockCoordinator {
     pub fn continue_block_handler(
         &mut self,
         program_memory: &mut MemoryManager,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         let mut success = false;
         for h in self.stack.iter_mut().rev() {
             if h.on_continue(program_memory)? {
@@ -212,7 +248,36 @@ impl BlockCoordinator {
         }
 
         if !success {
-            return Err("None of the scopes 'continue' is in support continuing".to_string());
+            return Err("None of the scopes 'continue' is in support continuing"
+                .to_string()
+                .into());
+        }
+        Ok(())
+    }
+
+    /// Return from block e.g. function
+    pub fn return_block_handler(
+        &mut self,
+        program_memory: &mut MemoryManager,
+        value: &[Symbol],
+    ) -> CompileResult<()> {
+        let mut success = false;
+        for h in self.stack.iter_mut().rev() {
+            if h.on_return(
+                program_memory,
+                &mut self.reference_stack,
+                &mut self.stack_sizes,
+                value,
+            )? {
+                success = true;
+                break;
+            }
+        }
+
+        if !success {
+            return Err("None of the scopes 'return' is in support returning"
+                .to_string()
+                .into());
         }
         Ok(())
     }
@@ -224,7 +289,7 @@ impl BlockCoordinator {
         &mut self,
         program_memory: &mut MemoryManager,
         symbol_line: &[Symbol],
-    ) -> Result<bool, String> {
+    ) -> CompileResult<bool> {
         let mut handler = self
             .stack
             .pop()
@@ -234,17 +299,14 @@ impl BlockCoordinator {
 
         let result = handler.on_exit(program_memory, reference_stack, stack_sizes, symbol_line);
 
-        if let Ok(r) = result {
-            return if !r {
-                // Cancel stack removing
-                self.stack.push(handler);
-                Ok(false)
-            } else {
-                self.reference_stack.remove_handler();
-                // self.stack_sizes.remove_stack();
-                Ok(true)
-            };
+        if let Ok(false) = result {
+            // Cancel stack removing
+            self.stack.push(handler);
+            return Ok(false);
         }
+        //? The block is removed even if exiting fails so that processing can continue
+        self.reference_stack.remove_handler(program_memory);
+        // self.stack_sizes.remove_stack();
         result
     }
 
@@ -252,7 +314,7 @@ impl BlockCoordinator {
     pub fn force_exit_block_handler(
         &mut self,
         program_memory: &mut MemoryManager,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         let mut handler = self
             .stack
             .pop()
@@ -262,7 +324,7 @@ impl BlockCoordinator {
 
         let result = handler.on_forced_exit(program_memory, reference_stack, stack_sizes);
 
-        self.reference_stack.remove_handler();
+        self.reference_stack.remove_handler(program_memory);
         // self.stack_sizes.remove_stack();
 
         result
@@ -271,7 +333,7 @@ impl BlockCoordinator {
     /// Should be called after every line is processed. Informs all blocks of the
     /// blocks beneath them. Can be skipped by setting `BlockCoordinator.skip_sub_block_check`
     /// to true
-    pub fn on_line_processed(&mut self) -> Result<(), String> {
+    pub fn on_line_processed(&mut self) -> CompileResult<()> {
         if self.skip_sub_block_check {
             self.skip_sub_block_check = false;
             return Ok(());
@@ -315,16 +377,16 @@ impl BlockCoordinator {
         &mut self,
         reference: Reference,
         name: Vec<String>,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         self.reference_stack.register_reference(reference, name)
     }
 
     /// Searches for a variable going up the reference stack
-    pub fn get_reference(&self, name: &[String]) -> Result<&Reference, String> {
+    pub fn get_reference(&self, name: &[String]) -> CompileResult<&Reference> {
         self.reference_stack.get_reference(name)
     }
 
-    pub fn get_reference_and_offset(&self, name: &[String]) -> Result<(&Reference, usize), String> {
+    pub fn get_reference_and_offset(&self, name: &[String]) -> CompileResult<(&Reference, usize)> {
         self.reference_stack.get_reference_and_offset(name)
     }
 
@@ -334,8 +396,8 @@ impl BlockCoordinator {
     }
 
     /// Removes a reference handler (removes a variable scope)
-    pub fn remove_reference_handler(&mut self) {
-        self.reference_stack.remove_handler()
+    pub fn remove_reference_handler(&mut self, program_memory: &mut MemoryManager) {
+        self.reference_stack.remove_handler(program_memory)
     }
 
     pub fn get_stack_sizes_and_reference_stack(
@@ -353,3 +415,99 @@ impl Drop for BlockCoordinator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{compile_err, get_output};
+
+    /// Compiles a program whose first block fails to start, checking that the failure is the
+    /// only error and that the code after the block still compiles
+    fn assert_block_fails(header: &str) {
+        let source = format!(
+            "int s = 0\nint e = 3\n{}\n    int x = 1\nint y = s\nfn foo(int a) -> int\n    return a\nint z = foo(y)\n",
+            header
+        );
+        let errors = compile_err(&source);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].location.as_ref().unwrap().line_index, 2);
+    }
+
+    #[test]
+    fn function_with_unknown_parameter_type() {
+        assert_block_fails("fn foo (int a, bogus b)");
+    }
+
+    #[test]
+    fn function_without_parameters() {
+        assert_block_fails("fn foo");
+    }
+
+    #[test]
+    fn while_without_boolean() {
+        assert_block_fails("while 5");
+    }
+
+    #[test]
+    fn for_without_range() {
+        assert_block_fails("for j in s");
+    }
+
+    #[test]
+    fn if_without_boolean() {
+        assert_block_fails("if 5");
+    }
+
+    #[test]
+    fn loop_with_condition() {
+        assert_block_fails("loop 5");
+    }
+
+    #[test]
+    fn class_without_name() {
+        assert_block_fails("class");
+    }
+
+    #[test]
+    fn failed_blocks_inside_other_blocks() {
+        let errors = compile_err(
+            "fn foo(int a)\n    while 5\n        int x = 1\n    int y = a\nfoo(1)\nclass P\n    int x\n    int y\n    fn bad(bogus b)\n        int z = 1\n    fn sum() -> int\n        return self.x + self.y\nP p = (1, 2)\nint s = p.sum()\n",
+        );
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| e.location.as_ref().unwrap().line_index)
+                .collect::<Vec<_>>(),
+            vec![1, 8]
+        );
+    }
+
+    #[test]
+    fn functions_must_return_on_every_path() {
+        for source in [
+            "fn noret(int n) -> int\n    int x = n\n",
+            "fn noret(int n) -> int\n    if n > 0\n        return 1\nint after = 1\n",
+            "fn noret(int n) -> int\n    while n > 0\n        return 1\n",
+            "class P\n    int x\n    fn noret() -> int\n        if self.x > 0\n            return 1\nint after = 1\n",
+        ] {
+            let errors = compile_err(source);
+            assert_eq!(errors.len(), 1, "{}", source);
+            assert!(
+                errors[0]
+                    .message
+                    .ends_with("noret' can reach its end without returning a value"),
+                "{}",
+                errors[0].message
+            );
+        }
+    }
+
+    #[test]
+    fn functions_returning_on_every_path() {
+        assert_eq!(
+            get_output(
+                "fn branches(int n) -> int\n    if n > 0\n        return 1\n    else\n        return 2\nfn looped(int n) -> int\n    loop\n        if n > 3\n            return n\n        n += 1\nfn recursive(int n) -> int\n    if n == 0\n        return 0\n    return recursive(n - 1) + branches(n)\nprint branches(0)\nprint looped(0)\nprint recursive(3)\n"
+            ),
+            "2\n4\n3\n"
+        );
+    }
+}
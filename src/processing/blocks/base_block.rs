@@ -1,4 +1,5 @@
 use crate::bx;
+use crate::errors::CompileResult;
 use crate::memory::MemoryManager;
 use crate::processing::blocks::{BlockHandler, BlockType, StackSizes};
 use crate::processing::instructions::stack_create_0::StackCreateInstruction;
@@ -30,7 +31,7 @@ impl BlockHandler for BaseBlock {
         _reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         _symbol_line: &[Symbol],
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         self.stack_create_instruction = Some(StackCreateInstruction::new_alloc(program_memory, 0));
         StackUpInstruction::new_alloc(program_memory);
         stack_sizes.add_stack();
@@ -42,7 +43,9 @@ impl BlockHandler for BaseBlock {
         program_memory: &mut MemoryManager,
         _reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
+        let end = program_memory.get_position();
+        program_memory.pointer_map.end_temporaries(0..end);
         StackDownInstruction::new_alloc(program_memory);
         self.stack_create_instruction
             .as_mut()
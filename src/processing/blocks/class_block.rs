@@ -1,9 +1,10 @@
 use crate::bx;
+use crate::errors::CompileResult;
 use crate::memory::MemoryManager;
 use crate::processing::blocks::{BlockHandler, BlockType, StackSizes};
 use crate::processing::reference_manager::class::ClassReference;
 use crate::processing::reference_manager::{Reference, ReferenceStack};
-use crate::processing::symbols::{Block, Symbol, CLASS_SELF_NAME, TypeSymbol};
+use crate::processing::symbols::{Block, Symbol, CLASS_SELF_NAME};
 
 pub struct ClassBlock {
     name: Option<String>,
@@ -13,7 +14,11 @@ pub struct ClassBlock {
 
 impl ClassBlock {
     pub fn new_block() -> Box<dyn BlockHandler> {
-        bx!(Self { name: None, properties_phase: true, allow_line: false })
+        bx!(Self {
+            name: None,
+            properties_phase: true,
+            allow_line: false
+        })
     }
 }
 
@@ -28,18 +33,19 @@ impl BlockHandler for ClassBlock {
         reference_stack: &mut ReferenceStack,
         _stack_sizes: &mut StackSizes,
         symbol_line: &[Symbol],
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         if symbol_line.len() != 2 {
             return Err(format!(
                 "Class declaration must be formatted {} [Name]",
                 Block::Class.get_code_representation()
-            ));
+            )
+            .into());
         }
 
         let name = match &symbol_line[1] {
             Symbol::Name(name) => {
                 if name.len() != 1 {
-                    return Err("Class names cannot have separators".to_string());
+                    return Err("Class names cannot have separators".to_string().into());
                 }
                 name[0].clone()
             }
@@ -47,7 +53,8 @@ impl BlockHandler for ClassBlock {
                 return Err(format!(
                     "Class declaration must be formatted {} [Name]",
                     Block::Class.get_code_representation()
-                ))
+                )
+                .into())
             }
         };
 
@@ -69,7 +76,7 @@ impl BlockHandler for ClassBlock {
         _program_memory: &mut MemoryManager,
         reference_stack: &mut ReferenceStack,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         reference_stack
             .get_reference_handler_mut(&[CLASS_SELF_NAME.to_string()])
             .unwrap()
@@ -77,20 +84,34 @@ impl BlockHandler for ClassBlock {
         Ok(())
     }
 
-    fn update_sub_block(&mut self, block_type: Option<BlockType>) -> Result<(), String> {
-        if self.allow_line { self.allow_line = false; return Ok(()); }
+    fn update_sub_block(&mut self, block_type: Option<BlockType>) -> CompileResult<()> {
+        if self.allow_line {
+            self.allow_line = false;
+            return Ok(());
+        }
         self.allow_line = false;
 
         match block_type {
             Some(BlockType::Function) => {
                 self.properties_phase = false;
                 Ok(())
-            },
-            _ => Err("Classes can only contain function or attributes (before the first function)".to_string()),
+            }
+            _ => Err(
+                "Classes can only contain function or attributes (before the first function)"
+                    .to_string()
+                    .into(),
+            ),
         }
     }
 
-    fn handle_line(&mut self, line: &[Symbol]) -> Result<(), String> {
+    fn handle_line(&mut self, _line: &[Symbol]) -> CompileResult<()> {
+        if !self.properties_phase {
+            return Err(
+                "Class properties must be declared before the first function"
+                    .to_string()
+                    .into(),
+            );
+        }
         self.allow_line = true;
         Ok(())
     }
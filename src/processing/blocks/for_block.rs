@@ -0,0 +1,174 @@
+use crate::address::Address;
+use crate::bx;
+use crate::errors::{CompileError, CompileResult};
+use crate::memory::MemoryManager;
+use crate::processing::arithmetic::evaluate_arithmetic_into_type;
+use crate::processing::blocks::{BlockHandler, BlockType, StackSizes};
+use crate::processing::instructions::add_instruction_13::AddInstruction;
+use crate::processing::instructions::jump_if_not_9::JumpIfNotInstruction;
+use crate::processing::instructions::jump_instruction_10::JumpInstruction;
+use crate::processing::instructions::less_20::LessInstruction;
+use crate::processing::reference_manager::{Reference, ReferenceStack};
+use crate::processing::symbols::{Keyword, Punctuation, Symbol, TypeSymbol, CLASS_SELF_NAME};
+use crate::processing::types::integer::INTEGER_SIZE;
+use crate::processing::types::{Type, TypeFactory};
+
+/// Counted loop e.g. `for i in 0..10`. Lowered to a `while i < end` loop that increments `i`
+/// after the body
+pub struct ForBlock {
+    counter: Option<Box<dyn Type>>,
+    jump_end_instruction: Option<JumpIfNotInstruction>,
+    jump_end_instructions: Vec<JumpInstruction>,
+    jump_increment_instructions: Vec<JumpInstruction>,
+    start_position: Option<usize>,
+}
+
+impl ForBlock {
+    pub fn new_block() -> Box<dyn BlockHandler> {
+        bx!(Self {
+            counter: None,
+            jump_end_instruction: None,
+            jump_end_instructions: Vec::new(),
+            jump_increment_instructions: Vec::new(),
+            start_position: None,
+        })
+    }
+}
+
+impl BlockHandler for ForBlock {
+    fn get_block_type(&self) -> BlockType {
+        BlockType::For
+    }
+
+    fn on_entry(
+        &mut self,
+        program_memory: &mut MemoryManager,
+        reference_stack: &mut ReferenceStack,
+        stack_sizes: &mut StackSizes,
+        symbol_line: &[Symbol],
+    ) -> CompileResult<()> {
+        fn formatting_error() -> CompileError {
+            "For loops must be formatted 'for [Name] in [Value]..[Value]'".into()
+        }
+
+        let (name, range) = match symbol_line {
+            [_, Symbol::Name(name), Symbol::Keyword(Keyword::In), range @ ..]
+                if name.len() == 1 =>
+            {
+                (name, range)
+            }
+            _ => return Err(formatting_error()),
+        };
+        if name[0] == CLASS_SELF_NAME {
+            return Err(format!("Name '{}' is reserved", CLASS_SELF_NAME).into());
+        }
+
+        let Some(separator) = range
+            .iter()
+            .position(|s| matches!(s, Symbol::Punctuation(Punctuation::Range)))
+        else {
+            return Err(formatting_error());
+        };
+        let (start, end) = (&range[..separator], &range[separator + 1..]);
+        if start.is_empty() || end.is_empty() {
+            return Err(formatting_error());
+        }
+
+        //? Both bounds are evaluated once, before the counter is in scope
+        let mut counter = TypeFactory::get_unallocated_type(&TypeSymbol::Integer)?;
+        counter.allocate_variable(stack_sizes, program_memory)?;
+        evaluate_arithmetic_into_type(
+            start,
+            counter.as_ref(),
+            program_memory,
+            reference_stack,
+            stack_sizes,
+        )?;
+
+        let mut end_value = TypeFactory::get_unallocated_type(&TypeSymbol::Integer)?;
+        end_value.allocate_variable(stack_sizes, program_memory)?;
+        evaluate_arithmetic_into_type(
+            end,
+            end_value.as_ref(),
+            program_memory,
+            reference_stack,
+            stack_sizes,
+        )?;
+
+        let mut condition = TypeFactory::get_unallocated_type(&TypeSymbol::Boolean)?;
+        condition.allocate_variable(stack_sizes, program_memory)?;
+
+        //? Save position before condition evaluation
+        self.start_position = Some(program_memory.get_position());
+
+        LessInstruction::new_alloc(
+            program_memory,
+            counter.get_address(),
+            end_value.get_address(),
+            condition.get_address(),
+            INTEGER_SIZE,
+            true,
+        );
+
+        //? Create instruction to leave loop if the counter has reached the end
+        self.jump_end_instruction = Some(JumpIfNotInstruction::new_alloc(
+            program_memory,
+            condition.get_address(),
+            0,
+        ));
+
+        reference_stack
+            .register_reference(Reference::Variable(counter.duplicate()), name.clone())?;
+        self.counter = Some(counter);
+
+        Ok(())
+    }
+
+    fn on_forced_exit(
+        &mut self,
+        program_memory: &mut MemoryManager,
+        _reference_stack: &mut ReferenceStack,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        //? `continue` jumps to the increment
+        let increment_position = program_memory.get_position();
+        let counter = self.counter.as_ref().unwrap();
+        AddInstruction::new_alloc(
+            program_memory,
+            counter.get_address(),
+            &Address::Immediate(Vec::from(1i64.to_le_bytes())),
+            counter.get_address(),
+            INTEGER_SIZE,
+        );
+
+        //? Insert looping instruction
+        JumpInstruction::new_alloc(program_memory, self.start_position.unwrap());
+
+        //? Set all instructions to jump to correct locations
+        self.jump_end_instruction
+            .as_mut()
+            .unwrap()
+            .set_destination(program_memory.get_position(), program_memory);
+        for i in self.jump_end_instructions.iter_mut() {
+            i.set_destination(program_memory.get_position(), program_memory);
+        }
+        for i in self.jump_increment_instructions.iter_mut() {
+            i.set_destination(increment_position, program_memory);
+        }
+        Ok(())
+    }
+
+    fn on_break(&mut self, program_memory: &mut MemoryManager) -> CompileResult<bool> {
+        // Go to end of loop
+        self.jump_end_instructions
+            .push(JumpInstruction::new_alloc(program_memory, 0));
+        Ok(true)
+    }
+
+    fn on_continue(&mut self, program_memory: &mut MemoryManager) -> CompileResult<bool> {
+        // Go to increment of loop
+        self.jump_increment_instructions
+            .push(JumpInstruction::new_alloc(program_memory, 0));
+        Ok(true)
+    }
+}
@@ -1,24 +1,32 @@
 use crate::bx;
-use crate::memory::MemoryManager;
+use crate::errors::CompileResult;
+use crate::memory::{DebugFunction, MemoryManager};
+use crate::processing::arithmetic::evaluate_arithmetic_into_type;
 use crate::processing::blocks::{BlockHandler, BlockType, StackSizes};
 use crate::processing::instructions::dynamic_jump_11::DynamicJumpInstruction;
 use crate::processing::instructions::jump_instruction_10::JumpInstruction;
 use crate::processing::instructions::stack_create_0::StackCreateInstruction;
 use crate::processing::instructions::stack_up_1::StackUpInstruction;
 use crate::processing::lines::variable_initialisation::VariableInitialisationLine;
+use crate::processing::optimiser::reaches_end;
 use crate::processing::reference_manager::function::FunctionReference;
 use crate::processing::reference_manager::{Reference, ReferenceStack};
-use crate::processing::symbols::{Block, Symbol, CLASS_SELF_NAME};
+use crate::processing::symbols::{
+    Block, Punctuation, Symbol, CLASS_SELF_NAME, RETURN_TYPE_SEPARATOR,
+};
 use crate::processing::types::pointer::PointerType;
-use crate::processing::types::Type;
+use crate::processing::types::{Type, TypeFactory};
 
 pub struct FunctionBlock {
     name: Option<Vec<String>>,
+    /// Name shown in debug info e.g. `Point.length` for methods
+    debug_name: Option<String>,
     start_position: Option<usize>,
     previous_reference_limit: Option<usize>,
     skip_instruction: Option<JumpInstruction>,
     stack_create_instruction: Option<StackCreateInstruction>,
     return_pointer: Option<PointerType>,
+    return_value: Option<Box<dyn Type>>,
     stack_size_insertion_addresses: Vec<usize>,
 }
 
@@ -26,11 +34,13 @@ impl FunctionBlock {
     pub fn new_block() -> Box<dyn BlockHandler> {
         bx!(Self {
             name: None,
+            debug_name: None,
             start_position: None,
             previous_reference_limit: None,
             skip_instruction: None,
             stack_create_instruction: None,
             return_pointer: None,
+            return_value: None,
             stack_size_insertion_addresses: Vec::new(),
         })
     }
@@ -57,7 +67,7 @@ impl BlockHandler for FunctionBlock {
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         symbol_line: &[Symbol],
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         //? Add skip instruction to skip function in normal execution
         self.skip_instruction = Some(JumpInstruction::new_alloc(program_memory, 0));
 
@@ -79,15 +89,19 @@ impl BlockHandler for FunctionBlock {
         // return_pointer.allocate_variable(stack_sizes, program_memory).unwrap();
         // self.return_pointer = Some(return_pointer);
 
-        fn declaration_error() -> Result<(), String> {
+        fn declaration_error() -> CompileResult<()> {
             Err(format!(
-                "Function declaration must be formatted {} [Name] [Parameter List]",
-                Block::Function.get_code_representation()
-            ))
+                "Function declaration must be formatted {} [Name] [Parameter List] or {} [Name] [Parameter List] {} [Type]",
+                Block::Function.get_code_representation(),
+                Block::Function.get_code_representation(),
+                RETURN_TYPE_SEPARATOR
+            ).into())
         }
 
-        if symbol_line.len() != 3 {
-            return declaration_error();
+        match symbol_line {
+            [_, _, _] => {}
+            [_, _, _, Symbol::Punctuation(Punctuation::ReturnType), _] => {}
+            _ => return declaration_error(),
         }
 
         self.name = Some(match &symbol_line[1] {
@@ -95,7 +109,8 @@ impl BlockHandler for FunctionBlock {
                 if name.len() != 1 {
                     return Err(
                         "Invalid function name - function names cannot contain separators"
-                            .to_string(),
+                            .to_string()
+                            .into(),
                     );
                 }
                 name.clone()
@@ -107,10 +122,19 @@ impl BlockHandler for FunctionBlock {
         self.previous_reference_limit = Some(reference_stack.get_reference_depth_limit());
 
         //? If in class
-        if reference_stack
-            .get_reference(&[CLASS_SELF_NAME.to_string()])
-            .is_ok()
-        {
+        let class_instance = match reference_stack.get_reference(&[CLASS_SELF_NAME.to_string()]) {
+            Ok(reference) => Some(reference.get_class_ref()?.instantiate()),
+            Err(_) => None,
+        };
+        let function_name = self.name.as_ref().unwrap()[0].clone();
+        self.debug_name = Some(
+            match class_instance.as_ref().and_then(|c| c.get_class_name()) {
+                Some(class_name) => format!("{}.{}", class_name, function_name),
+                None => function_name,
+            },
+        );
+
+        if class_instance.is_some() {
             //? Add to class if in class
             self.name
                 .as_mut()
@@ -128,12 +152,27 @@ impl BlockHandler for FunctionBlock {
         return_pointer.allocate_variable(stack_sizes, program_memory)?;
         self.return_pointer = Some(return_pointer);
 
-        let parameter_list = match &symbol_line[2] {
-            Symbol::List(parameters) => parameters,
-            _ => return declaration_error(),
+        if let Some(return_type) = symbol_line.get(4) {
+            let mut return_value =
+                TypeFactory::get_unallocated_type_from_symbol(return_type, reference_stack)?;
+            return_value.allocate_variable(stack_sizes, program_memory)?;
+            self.return_value = Some(return_value);
+        }
+
+        let Some(parameter_list) = symbol_line[2].get_list_items() else {
+            return declaration_error();
+        };
+
+        //? Methods take the instance they are called on as a hidden first parameter
+        let self_parameter = match class_instance {
+            Some(mut instance) => {
+                instance.allocate_variable(stack_sizes, program_memory)?;
+                Some(instance)
+            }
+            None => None,
         };
 
-        for parameter in parameter_list {
+        for parameter in &parameter_list {
             VariableInitialisationLine::handle_initialisation(
                 parameter,
                 program_memory,
@@ -145,7 +184,10 @@ impl BlockHandler for FunctionBlock {
 
         //? Add parameters to FunctionReference
         let parameters = reference_stack.get_top_stack();
-        let mut cloned_parameters = Vec::with_capacity(parameters.len());
+        let mut cloned_parameters = Vec::with_capacity(parameters.len() + 1);
+        if let Some(self_parameter) = &self_parameter {
+            cloned_parameters.push((CLASS_SELF_NAME.to_string(), self_parameter.duplicate()));
+        }
         for p in parameters {
             cloned_parameters.push((
                 p.name.clone(),
@@ -172,6 +214,7 @@ impl BlockHandler for FunctionBlock {
         let function_reference = FunctionReference::new(
             self.start_position.unwrap(),
             self.return_pointer.as_ref().unwrap().duplicate_known(),
+            self.return_value.as_ref().map(|r| r.duplicate()),
             cloned_parameters,
             None,
         );
@@ -186,31 +229,46 @@ impl BlockHandler for FunctionBlock {
             )?;
         }
 
+        //? Registered after the function so that the function is added to the class, not `self`
+        if let Some(self_parameter) = self_parameter {
+            reference_stack.register_reference(
+                Reference::Variable(self_parameter),
+                vec![CLASS_SELF_NAME.to_string()],
+            )?;
+        }
+
         //? Add new stack to separate parameters from function body
         reference_stack.add_handler();
 
         Ok(())
     }
 
-    fn on_exit(
-        &mut self,
-        program_memory: &mut MemoryManager,
-        reference_stack: &mut ReferenceStack,
-        stack_sizes: &mut StackSizes,
-        _symbol_line: &[Symbol],
-    ) -> Result<bool, String> {
-        self.on_forced_exit(program_memory, reference_stack, stack_sizes)?;
-        Ok(true)
-    }
-
     fn on_forced_exit(
         &mut self,
         program_memory: &mut MemoryManager,
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
+        //? Checked before the jump back is added
+        let missing_return = match self.return_value {
+            Some(_) => reaches_end(
+                program_memory,
+                self.start_position.unwrap(),
+                program_memory.get_position(),
+            )
+            .map(|reaches_end| {
+                reaches_end.then(|| {
+                    format!(
+                        "Function '{}' can reach its end without returning a value",
+                        self.debug_name.as_ref().unwrap()
+                    )
+                })
+            }),
+            None => Ok(None),
+        };
+
         //? Remove extra handler
-        reference_stack.remove_handler();
+        reference_stack.remove_handler(program_memory);
 
         // // Stack down
         // self.stack_create_instruction
@@ -245,16 +303,63 @@ impl BlockHandler for FunctionBlock {
             .as_mut()
             .unwrap()
             .set_destination(program_memory.get_position(), program_memory);
-        Ok(())
+
+        let body = self.start_position.unwrap()..program_memory.get_position();
+        program_memory.pointer_map.end_temporaries(body.clone());
+        program_memory.pointer_map.add_function(body.clone());
+        if let Some(name) = self.debug_name.take() {
+            program_memory
+                .debug_info
+                .add_function(DebugFunction { name, body });
+        }
+
+        match missing_return? {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn on_return(
+        &mut self,
+        program_memory: &mut MemoryManager,
+        reference_stack: &mut ReferenceStack,
+        stack_sizes: &mut StackSizes,
+        value: &[Symbol],
+    ) -> CompileResult<bool> {
+        match (&self.return_value, value.is_empty()) {
+            (Some(return_value), false) => evaluate_arithmetic_into_type(
+                value,
+                return_value.as_ref(),
+                program_memory,
+                reference_stack,
+                stack_sizes,
+            )?,
+            (None, true) => {}
+            (Some(_), true) => return Err("Function must return a value".to_string().into()),
+            (None, false) => {
+                return Err(format!(
+                    "Function does not return a value (declare a return type with {} [Type])",
+                    RETURN_TYPE_SEPARATOR
+                )
+                .into())
+            }
+        }
+
+        //? Jump back
+        DynamicJumpInstruction::new_alloc(
+            program_memory,
+            self.return_pointer.as_ref().unwrap().get_address(),
+        );
+        Ok(true)
     }
 
     // Don't allow break to propagate
-    fn on_break(&mut self, _program_memory: &mut MemoryManager) -> Result<bool, String> {
-        Err("Can't break out of a function".to_string())
+    fn on_break(&mut self, _program_memory: &mut MemoryManager) -> CompileResult<bool> {
+        Err("Can't break out of a function".to_string().into())
     }
 
     // Don't allow continue to propagate
-    fn on_continue(&mut self, _program_memory: &mut MemoryManager) -> Result<bool, String> {
-        Err("Can't continue a function".to_string())
+    fn on_continue(&mut self, _program_memory: &mut MemoryManager) -> CompileResult<bool> {
+        Err("Can't continue a function".to_string().into())
     }
 }
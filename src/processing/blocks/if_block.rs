@@ -1,4 +1,5 @@
 use crate::bx;
+use crate::errors::CompileResult;
 use crate::memory::MemoryManager;
 use crate::processing::arithmetic::evaluate_arithmetic_to_types;
 use crate::processing::blocks::StackSizes;
@@ -33,7 +34,7 @@ impl BlockHandler for IfBlock {
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         symbol_line: &[Symbol],
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         //? Extract condition boolean
         let result = evaluate_arithmetic_to_types(
             &symbol_line[1..],
@@ -61,13 +62,13 @@ impl BlockHandler for IfBlock {
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         symbol_line: &[Symbol],
-    ) -> Result<bool, String> {
+    ) -> CompileResult<bool> {
         fn exit_with_cleanup(
             this: &mut IfBlock,
             program_memory: &mut MemoryManager,
             reference_stack: &mut ReferenceStack,
             stack_sizes: &mut StackSizes,
-        ) -> Result<bool, String> {
+        ) -> CompileResult<bool> {
             this.on_forced_exit(program_memory, reference_stack, stack_sizes)?;
             Ok(true)
         }
@@ -93,7 +94,8 @@ impl BlockHandler for IfBlock {
                         "{} cannot follow an {} block as it will never be reached",
                         Block::Elif,
                         Block::Else
-                    ));
+                    )
+                    .into());
                 }
 
                 // Add instruction to skip to end if previous if/elif condition was met and executed
@@ -107,18 +109,21 @@ impl BlockHandler for IfBlock {
                 // Reuse if handling
                 self.on_entry(program_memory, reference_stack, stack_sizes, symbol_line)?;
                 // Create new scope
-                reference_stack.remove_handler();
+                reference_stack.remove_handler(program_memory);
                 reference_stack.add_handler();
                 Ok(false)
             }
             Block::Else => {
                 if symbol_line.len() > 1 {
-                    return Err("Else cannot be followed by any other symbol".to_string());
+                    return Err("Else cannot be followed by any other symbol"
+                        .to_string()
+                        .into());
                 }
                 if self.jump_next_instruction.is_none() {
                     return Err(
                         "'else' cannot follow an 'else' block as it will never be reached"
-                            .to_string(),
+                            .to_string()
+                            .into(),
                     );
                 }
                 // Add instruction to skip to end if previous if/elif condition was met and executed
@@ -132,7 +137,7 @@ impl BlockHandler for IfBlock {
                 // Else block cannot be skipped
                 self.jump_next_instruction = None;
                 // Create new scope
-                reference_stack.remove_handler();
+                reference_stack.remove_handler(program_memory);
                 reference_stack.add_handler();
                 Ok(false)
             }
@@ -145,7 +150,7 @@ impl BlockHandler for IfBlock {
         program_memory: &mut MemoryManager,
         _reference_stack: &mut ReferenceStack,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         /*
         If :: Jump to next if not
             content
@@ -0,0 +1,78 @@
+use crate::bx;
+use crate::errors::CompileResult;
+use crate::memory::MemoryManager;
+use crate::processing::blocks::{BlockHandler, BlockType, StackSizes};
+use crate::processing::instructions::jump_instruction_10::JumpInstruction;
+use crate::processing::reference_manager::ReferenceStack;
+use crate::processing::symbols::Symbol;
+
+/// Unconditional loop - only left through `break` (or `return`)
+pub struct LoopBlock {
+    jump_end_instructions: Vec<JumpInstruction>,
+    jump_start_instructions: Vec<JumpInstruction>,
+    start_position: Option<usize>,
+}
+
+impl LoopBlock {
+    pub fn new_block() -> Box<dyn BlockHandler> {
+        bx!(Self {
+            jump_end_instructions: Vec::new(),
+            jump_start_instructions: Vec::new(),
+            start_position: None,
+        })
+    }
+}
+
+impl BlockHandler for LoopBlock {
+    fn get_block_type(&self) -> BlockType {
+        BlockType::Loop
+    }
+
+    fn on_entry(
+        &mut self,
+        program_memory: &mut MemoryManager,
+        _reference_stack: &mut ReferenceStack,
+        _stack_sizes: &mut StackSizes,
+        symbol_line: &[Symbol],
+    ) -> CompileResult<()> {
+        if symbol_line.len() != 1 {
+            return Err("Loop must be formatted 'loop'".to_string().into());
+        }
+
+        self.start_position = Some(program_memory.get_position());
+        Ok(())
+    }
+
+    fn on_forced_exit(
+        &mut self,
+        program_memory: &mut MemoryManager,
+        _reference_stack: &mut ReferenceStack,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        //? Insert looping instruction
+        JumpInstruction::new_alloc(program_memory, self.start_position.unwrap());
+
+        //? Set all instructions to jump to correct locations
+        for i in self.jump_end_instructions.iter_mut() {
+            i.set_destination(program_memory.get_position(), program_memory);
+        }
+        for i in self.jump_start_instructions.iter_mut() {
+            i.set_destination(self.start_position.unwrap(), program_memory);
+        }
+        Ok(())
+    }
+
+    fn on_break(&mut self, program_memory: &mut MemoryManager) -> CompileResult<bool> {
+        // Go to end of loop
+        self.jump_end_instructions
+            .push(JumpInstruction::new_alloc(program_memory, 0));
+        Ok(true)
+    }
+
+    fn on_continue(&mut self, program_memory: &mut MemoryManager) -> CompileResult<bool> {
+        // Go to start of loop
+        self.jump_start_instructions
+            .push(JumpInstruction::new_alloc(program_memory, 0));
+        Ok(true)
+    }
+}
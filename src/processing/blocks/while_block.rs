@@ -1,4 +1,5 @@
 use crate::bx;
+use crate::errors::CompileResult;
 use crate::memory::MemoryManager;
 use crate::processing::arithmetic::evaluate_arithmetic_to_types;
 use crate::processing::blocks::{BlockHandler, BlockType, StackSizes};
@@ -36,7 +37,7 @@ impl BlockHandler for WhileBlock {
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         symbol_line: &[Symbol],
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         //? Save position before boolean evaluation
         self.start_position = Some(program_memory.get_position());
 
@@ -66,7 +67,7 @@ impl BlockHandler for WhileBlock {
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         _symbol_line: &[Symbol],
-    ) -> Result<bool, String> {
+    ) -> CompileResult<bool> {
         self.on_forced_exit(program_memory, reference_stack, stack_sizes)?;
         Ok(true)
     }
@@ -76,7 +77,7 @@ impl BlockHandler for WhileBlock {
         program_memory: &mut MemoryManager,
         _reference_stack: &mut ReferenceStack,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         //? Insert looping instruction
         JumpInstruction::new_alloc(program_memory, self.start_position.unwrap());
 
@@ -94,14 +95,14 @@ impl BlockHandler for WhileBlock {
         Ok(())
     }
 
-    fn on_break(&mut self, program_memory: &mut MemoryManager) -> Result<bool, String> {
+    fn on_break(&mut self, program_memory: &mut MemoryManager) -> CompileResult<bool> {
         // Go to end of while
         self.jump_end_instructions
             .push(JumpInstruction::new_alloc(program_memory, 0));
         Ok(true)
     }
 
-    fn on_continue(&mut self, program_memory: &mut MemoryManager) -> Result<bool, String> {
+    fn on_continue(&mut self, program_memory: &mut MemoryManager) -> CompileResult<bool> {
         // Go to start of while
         self.jump_start_instructions
             .push(JumpInstruction::new_alloc(program_memory, 0));
@@ -1,55 +1,242 @@
-use crate::memory::RuntimeMemoryManager;
-
-pub mod add_instruction_13;
-pub mod binary_and_8;
-pub mod binary_not_7;
-pub mod binary_or_12;
-pub mod copy_3;
-pub mod dump_5;
-pub mod dynamic_jump_11;
-pub mod equality_14;
-pub mod heap_alloc_2;
-pub mod jump_if_not_9;
-pub mod jump_instruction_10;
-pub mod not_equal_15;
-pub mod stack_create_0;
-pub mod stack_down_4;
-pub mod stack_up_1;
-pub mod view_memory_6;
-pub mod view_memory_dec_16;
+use crate::address::{Address, ADDRESS_CODE_LENGTH};
+use crate::execution::RuntimeResult;
+use crate::memory::{MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::print_22::PrintFormat;
+use crate::processing::types::boolean::BOOLEAN_SIZE;
+use crate::util::{get_usize, USIZE_BYTES};
+
+/// Declares the instruction set. Each entry is `module::Instruction = CODE [operands]` where
+/// `module` is the file defining the instruction, `CODE` is its instruction code constant and
+/// `operands` lists the `OperandKind`s that follow the code in program memory. The braces hold
+/// the instruction's `Effect`, `Faults` and `Flow`, which the optimiser relies on
+///
+/// Every instruction must implement `Execute`. Instructions are encoded with
+/// `append_instruction`, decoded with `decode_operands` and disassembled with
+/// `debug_instruction`, all following the registered layout. The executor, disassembler and
+/// optimiser dispatch through the functions generated here, so registering an instruction is a
+/// single line. Duplicate codes are rejected as unreachable patterns
+macro_rules! instruction_set {
+    ($($module: ident :: $instruction: ident = $code: ident [$($kind: ident $(($length: ident))?),*] {$effect: ident, $faults: ident $(($read: literal))?, $flow: ident}),* $(,)?) => {
+        $(pub mod $module;)*
+
+        /// Returns the operands that follow the instruction code. Returns `None` if the code is
+        /// unknown
+        pub fn get_operand_layout(code: InstructionCodeType) -> Option<&'static [OperandKind]> {
+            match code {
+                $($module::$code => {
+                    const LAYOUT: &[OperandKind] = &[$(OperandKind::$kind $((OperandLength::$length))?),*];
+                    Some(LAYOUT)
+                })*
+                _ => None,
+            }
+        }
+
+        /// Returns the declared properties of the instruction with the given code. Returns `None`
+        /// if the code is unknown
+        pub fn get_instruction_properties(code: InstructionCodeType) -> Option<InstructionProperties> {
+            match code {
+                $($module::$code => Some(InstructionProperties {
+                    effect: Effect::$effect,
+                    faults: Faults::$faults $(($read))?,
+                    flow: Flow::$flow,
+                }),)*
+                _ => None,
+            }
+        }
+
+        /// Executes the instruction with the given code. Returns `None` if the code is unknown
+        pub fn execute_instruction(
+            code: InstructionCodeType,
+            memory: &mut RuntimeMemoryManager,
+            pointer: &mut usize,
+        ) -> Option<RuntimeResult<()>> {
+            match code {
+                $($module::$code => Some(<$module::$instruction as Execute>::execute(memory, pointer)),)*
+                _ => None,
+            }
+        }
+
+        /// Returns the name of the instruction with the given code
+        pub fn get_instruction_name(code: InstructionCodeType) -> Option<&'static str> {
+            match code {
+                $($module::$code => Some(stringify!($instruction)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+instruction_set!(
+    stack_create_0::StackCreateInstruction = STACK_CREATE_INSTRUCTION_CODE [StackSize] {Impure, Never, Next},
+    stack_up_1::StackUpInstruction = STACK_UP_INSTRUCTION_CODE [] {Impure, Never, Next},
+    heap_alloc_2::HeapAllocInstruction = HEAP_ALLOC_INSTRUCTION_CODE [Read(Usize), Write(Usize)] {Impure, Maybe, Next},
+    copy_3::CopyInstruction = COPY_INSTRUCTION_CODE [Size, Read(Size), Write(Size)] {Pure, Never, Next},
+    stack_down_4::StackDownInstruction = STACK_DOWN_INSTRUCTION_CODE [] {Impure, Never, Next},
+    dump_5::DumpInstruction = DUMP_INSTRUCTION_CODE [] {Impure, Never, Next},
+    view_memory_6::ViewMemoryInstruction = VIEW_MEMORY_INSTRUCTION_CODE [Size, Read(Size)] {Impure, Never, Next},
+    binary_not_7::BinaryNotInstruction = BINARY_NOT_INSTRUCTION_CODE [Size, Read(Size), Write(Size)] {Pure, Never, Next},
+    binary_and_8::BinaryAndInstruction = BINARY_AND_INSTRUCTION_CODE [Size, Read(Size), Read(Size), Write(Size)] {Pure, Never, Next},
+    jump_if_not_9::JumpIfNotInstruction = JUMP_IF_NOT_INSTRUCTION_CODE [Target, Read(Boolean)] {Impure, Never, Branch},
+    jump_instruction_10::JumpInstruction = JUMP_INSTRUCTION_CODE [Target] {Impure, Never, Jump},
+    dynamic_jump_11::DynamicJumpInstruction = DYNAMIC_JUMP_INSTRUCTION_CODE [Read(Usize)] {Impure, Never, Jump},
+    binary_or_12::BinaryOrInstruction = BINARY_OR_INSTRUCTION_CODE [Size, Read(Size), Read(Size), Write(Size)] {Pure, Never, Next},
+    add_instruction_13::AddInstruction = ADD_INSTRUCTION_CODE [Size, Read(Size), Read(Size), Write(Size)] {Pure, Never, Next},
+    equality_14::EqualityInstruction = EQUALITY_INSTRUCTION_CODE [Size, Read(Size), Read(Size), Write(Boolean)] {Pure, Never, Next},
+    not_equal_15::NotEqualInstruction = NOT_EQUAL_INSTRUCTION_CODE [Size, Read(Size), Read(Size), Write(Boolean)] {Pure, Never, Next},
+    view_memory_dec_16::ViewMemoryDecInstruction = VIEW_MEMORY_DEC_INSTRUCTION_CODE [Size, Read(Size)] {Impure, Never, Next},
+    subtract_17::SubtractInstruction = SUBTRACT_INSTRUCTION_CODE [Size, Read(Size), Read(Size), Write(Size)] {Pure, Never, Next},
+    multiply_18::MultiplyInstruction = MULTIPLY_INSTRUCTION_CODE [Size, Read(Size), Read(Size), Write(Size)] {Pure, Never, Next},
+    divide_19::DivideInstruction = DIVIDE_INSTRUCTION_CODE [Size, Signed, Read(Size), Read(Size), Write(Size)] {Pure, OnZero(1), Next},
+    less_20::LessInstruction = LESS_INSTRUCTION_CODE [Size, Signed, Read(Size), Read(Size), Write(Boolean)] {Pure, Never, Next},
+    less_equal_21::LessEqualInstruction = LESS_EQUAL_INSTRUCTION_CODE [Size, Signed, Read(Size), Read(Size), Write(Boolean)] {Pure, Never, Next},
+    print_22::PrintInstruction = PRINT_INSTRUCTION_CODE [Format, NewLine, Size, Read(Size)] {Impure, Maybe, Next},
+    input_23::InputInstruction = INPUT_INSTRUCTION_CODE [Format, Size, Write(Size)] {Impure, Maybe, Next},
+    heap_free_24::HeapFreeInstruction = HEAP_FREE_INSTRUCTION_CODE [Read(Usize)] {Impure, Maybe, Next},
+    modulo_25::ModuloInstruction = MODULO_INSTRUCTION_CODE [Size, Signed, Read(Size), Read(Size), Write(Size)] {Pure, OnZero(1), Next},
+    index_check_26::IndexCheckInstruction = INDEX_CHECK_INSTRUCTION_CODE [Size, Read(Size), Read(Size)] {Pure, Maybe, Next},
+    string_less_27::StringLessInstruction = STRING_LESS_INSTRUCTION_CODE [Size, Read(Size), Read(Size), Write(Boolean)] {Pure, Never, Next},
+);
+
+/// Decodes the instruction with the given code for display. Returns `None` if the code is
+/// unknown
+pub fn debug_instruction(
+    code: InstructionCodeType,
+    program_memory: &[u8],
+    pointer: &mut usize,
+) -> Option<Result<String, String>> {
+    let operands = decode_operands(code, program_memory, pointer, &[])?;
+    Some(operands.and_then(|operands| format_instruction(code, &operands)))
+}
+
+/// Formats an instruction as its name, its sizes and flags in brackets, the operands it reads
+/// and the operands it writes or jumps to after an arrow e.g.
+/// `AddInstruction (8 bytes) #1, #2 -> &0`
+fn format_instruction(code: InstructionCodeType, operands: &[Operand]) -> Result<String, String> {
+    let layout = get_operand_layout(code).unwrap();
+    let mut details = Vec::new();
+    let mut reads = Vec::new();
+    let mut outputs = Vec::new();
+    for (kind, operand) in layout.iter().zip(operands) {
+        match (kind, operand) {
+            (OperandKind::Size, Operand::Size(size)) => details.push(format!("{} bytes", size)),
+            (OperandKind::StackSize, Operand::StackSize(size)) => {
+                details.push(format!("stack size {}", size))
+            }
+            (OperandKind::Signed, Operand::Byte(signed)) => {
+                details.push(if *signed != 0 { "signed" } else { "unsigned" }.to_string())
+            }
+            (OperandKind::Format, Operand::Byte(format)) => details.push(
+                PrintFormat::from_byte(*format)
+                    .map_err(|e| e.to_string())?
+                    .get_name()
+                    .to_string(),
+            ),
+            (OperandKind::NewLine, Operand::Byte(new_line)) => {
+                if *new_line != 0 {
+                    details.push("new line".to_string());
+                }
+            }
+            (_, Operand::Read(address, _)) => reads.push(address.to_string()),
+            (_, Operand::CodePointer(target)) => reads.push(format_jump_target(*target)),
+            (_, Operand::Write(address, _)) => outputs.push(address.to_string()),
+            (_, Operand::Target(target)) => outputs.push(format_jump_target(*target)),
+            (kind, operand) => {
+                return Err(format!("Operand {:?} decoded as {:?}", operand, kind));
+            }
+        }
+    }
+
+    let mut output = get_instruction_name(code).unwrap().to_string();
+    if !details.is_empty() {
+        output += &format!(" ({})", details.join(", "));
+    }
+    if !reads.is_empty() {
+        output += &format!(" {}", reads.join(", "));
+    }
+    if !outputs.is_empty() {
+        output += &format!(" -> {}", outputs.join(", "));
+    }
+    Ok(output)
+}
+
+/// Encodes an instruction and its operands, converting positions in program memory with `map`.
+/// Also returns the positions of code pointers relative to the start of the instruction
+pub fn encode_instruction(
+    code: InstructionCodeType,
+    operands: &[Operand],
+    map: impl Fn(usize) -> usize,
+) -> (Vec<u8>, Vec<usize>) {
+    let mut bytes = Vec::from(code.to_le_bytes());
+    let mut code_pointers = Vec::new();
+    for operand in operands {
+        match operand {
+            Operand::Size(value) | Operand::StackSize(value) => bytes.extend(value.to_le_bytes()),
+            Operand::Byte(value) => bytes.push(*value),
+            Operand::Target(target) => bytes.extend(map(*target).to_le_bytes()),
+            Operand::CodePointer(target) => {
+                code_pointers.push(bytes.len() + ADDRESS_CODE_LENGTH);
+                bytes.extend(Address::Immediate(Vec::from(map(*target).to_le_bytes())).get_bytes())
+            }
+            Operand::Read(address, _) | Operand::Write(address, _) => {
+                bytes.extend(address.get_bytes())
+            }
+        }
+    }
+    (bytes, code_pointers)
+}
+
+/// Encodes an instruction and appends it to program memory, returning its address. Panics if
+/// the operands don't follow the instruction's registered layout or it would write to an
+/// immediate
+pub fn append_instruction(
+    program_memory: &mut MemoryManager,
+    code: InstructionCodeType,
+    operands: &[Operand],
+) -> usize {
+    let name = get_instruction_name(code).expect("Instruction code is not registered");
+    let layout = get_operand_layout(code).unwrap();
+    assert!(
+        layout.len() == operands.len()
+            && layout
+                .iter()
+                .zip(operands)
+                .all(|(kind, operand)| kind.matches(operand)),
+        "Operands of {} don't match its layout",
+        name
+    );
+    if operands
+        .iter()
+        .any(|operand| matches!(operand, Operand::Write(address, _) if address.is_immediate()))
+    {
+        panic!(
+            "Attempted to create {} that overwrites Immediate (program) memory!",
+            name
+        );
+    }
+
+    let (bytes, _) = encode_instruction(code, operands, |position| position);
+    program_memory.append(&bytes)
+}
 
 pub type InstructionCodeType = u16;
 pub const INSTRUCTION_CODE_LENGTH: usize = 2;
 
+/// Implements an instruction whose operands are all `usize`s e.g. `(destination, Target)`
 #[macro_export]
 macro_rules! default_instruction_impl {
-    ($name: ident, $caps_name: ident, $code: expr $(, ($arg:ident, $t:ty) )*) => {
+    ($name: ident, $caps_name: ident, $code: expr $(, ($arg:ident, $kind:ident) )*) => {
         pub const $caps_name: $crate::processing::instructions::InstructionCodeType = $code;
 
         impl $name {
-            pub fn new_alloc(program_memory: &mut $crate::memory::MemoryManager, $($arg: $t),*) -> Self {
-                #[allow(unused_mut)]
-                let mut instruction_memory = Vec::with_capacity(Self::get_size() + $crate::processing::instructions::INSTRUCTION_CODE_LENGTH);
-                instruction_memory.extend($caps_name.to_le_bytes());
-                $(instruction_memory.extend($arg.to_le_bytes());
-                )*
-
-                assert_eq!(instruction_memory.len() - $crate::processing::instructions::INSTRUCTION_CODE_LENGTH, Self::get_size());
-
-                let address = program_memory.append(&instruction_memory);
+            pub fn new_alloc(program_memory: &mut $crate::memory::MemoryManager, $($arg: usize),*) -> Self {
+                let address = $crate::processing::instructions::append_instruction(
+                    program_memory,
+                    $caps_name,
+                    &[$($crate::processing::instructions::Operand::$kind($arg)),*],
+                );
 
                 Self { address }
             }
-
-            pub fn get_size() -> usize {
-                0 $(+ std::mem::size_of::<$t>())*
-            }
-
-            #[allow(unused_variables)]
-            pub fn get_debug(program_memory: &[u8], pointer: &mut usize) -> String {
-                *pointer += Self::get_size();
-                stringify!($name).to_string()
-            }
         }
 
         impl $crate::processing::instructions::Instruction for $name {
@@ -60,11 +247,370 @@ macro_rules! default_instruction_impl {
     };
 }
 
+/// Reads `length` bytes of an instruction being decoded for display
+pub fn debug_bytes<'a>(
+    program_memory: &'a [u8],
+    pointer: &mut usize,
+    length: usize,
+) -> Result<&'a [u8], String> {
+    let bytes = program_memory
+        .get(*pointer..*pointer + length)
+        .ok_or_else(|| {
+            format!(
+                "Instruction operand at [{:05}] runs past the end of memory",
+                pointer
+            )
+        })?;
+    *pointer += length;
+    Ok(bytes)
+}
+
+/// Reads a single byte operand of an instruction being decoded for display
+pub fn debug_byte(program_memory: &[u8], pointer: &mut usize) -> Result<u8, String> {
+    Ok(debug_bytes(program_memory, pointer, 1)?[0])
+}
+
+/// Reads a `usize` operand of an instruction being decoded for display
+pub fn debug_usize(program_memory: &[u8], pointer: &mut usize) -> Result<usize, String> {
+    Ok(get_usize(
+        &mut 0,
+        debug_bytes(program_memory, pointer, USIZE_BYTES)?,
+    ))
+}
+
+/// Formats a jump destination. The disassembler replaces these with labels
+pub fn format_jump_target(destination: usize) -> String {
+    format!("@{:05}", destination)
+}
+
+/// Length of the data an address operand refers to
+#[derive(Clone, Copy, Debug)]
+pub enum OperandLength {
+    /// The instruction's `Size` operand
+    Size,
+    Boolean,
+    Usize,
+}
+
+/// Encoding of an operand of an instruction
+#[derive(Clone, Copy, Debug)]
+pub enum OperandKind {
+    /// `usize` length of the data the instruction operates on
+    Size,
+    /// Byte that is non-zero if the operands are signed
+    Signed,
+    /// Byte holding a `PrintFormat`
+    Format,
+    /// Byte that is non-zero if a new line is printed after the value
+    NewLine,
+    /// `usize` position in program memory to jump to
+    Target,
+    /// Stack size of a `StackCreateInstruction`
+    StackSize,
+    Read(OperandLength),
+    Write(OperandLength),
+}
+
+/// Effects of an instruction other than faulting and jumping
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    /// Only writes its `Write` operand so can be removed if that isn't read
+    Pure,
+    /// Has other effects e.g. printing or changing the stack
+    Impure,
+}
+
+/// Whether an instruction can raise a runtime error (other than for an invalid address)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Faults {
+    Never,
+    /// Only if the `Read` operand at this index (counting only reads) is zero e.g. a divisor
+    OnZero(usize),
+    Maybe,
+}
+
+/// Where execution continues after an instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    /// The next instruction
+    Next,
+    /// The next instruction or the instruction's `Target`
+    Branch,
+    /// Never the next instruction e.g. an unconditional jump
+    Jump,
+}
+
+/// Properties declared for each instruction in `instruction_set!`
+#[derive(Clone, Copy, Debug)]
+pub struct InstructionProperties {
+    pub effect: Effect,
+    pub faults: Faults,
+    pub flow: Flow,
+}
+
+impl OperandKind {
+    /// Returns whether `operand` can be encoded as this kind
+    pub fn matches(&self, operand: &Operand) -> bool {
+        matches!(
+            (self, operand),
+            (OperandKind::Size, Operand::Size(_))
+                | (
+                    OperandKind::Signed | OperandKind::Format | OperandKind::NewLine,
+                    Operand::Byte(_)
+                )
+                | (OperandKind::Target, Operand::Target(_))
+                | (OperandKind::StackSize, Operand::StackSize(_))
+                | (
+                    OperandKind::Read(_),
+                    Operand::Read(..) | Operand::CodePointer(_)
+                )
+                | (OperandKind::Write(_), Operand::Write(..))
+        )
+    }
+}
+
+/// Decoded operand of an instruction
+#[derive(Clone, Debug)]
+pub enum Operand {
+    Size(usize),
+    Byte(u8),
+    /// Position in program memory
+    Target(usize),
+    StackSize(usize),
+    /// Immediate holding a position in program memory e.g. a return address
+    CodePointer(usize),
+    /// Address read from and the length of the data read
+    Read(Address, usize),
+    /// Address written to and the length of the data written
+    Write(Address, usize),
+}
+
+/// Decodes the operands of the instruction with the given code using its registered layout.
+/// Immediates whose data starts at a position in `code_pointers` are decoded as code pointers.
+/// Returns `None` if the code is unknown
+pub fn decode_operands(
+    code: InstructionCodeType,
+    program_memory: &[u8],
+    pointer: &mut usize,
+    code_pointers: &[usize],
+) -> Option<Result<Vec<Operand>, String>> {
+    let layout = get_operand_layout(code)?;
+    let mut decode = || -> Result<Vec<Operand>, String> {
+        let mut size = 0;
+        let mut operands = Vec::with_capacity(layout.len());
+        for kind in layout {
+            let length = |length: &OperandLength| match length {
+                OperandLength::Size => size,
+                OperandLength::Boolean => BOOLEAN_SIZE,
+                OperandLength::Usize => USIZE_BYTES,
+            };
+
+            operands.push(match kind {
+                OperandKind::Size => {
+                    size = debug_usize(program_memory, pointer)?;
+                    Operand::Size(size)
+                }
+                OperandKind::Signed | OperandKind::Format | OperandKind::NewLine => {
+                    Operand::Byte(debug_byte(program_memory, pointer)?)
+                }
+                OperandKind::Target => Operand::Target(debug_usize(program_memory, pointer)?),
+                OperandKind::StackSize => Operand::StackSize(debug_usize(program_memory, pointer)?),
+                OperandKind::Read(l) => {
+                    let start = *pointer;
+                    let address = Address::from_bytes(program_memory, pointer, length(l))?;
+                    match &address {
+                        Address::Immediate(value)
+                            if code_pointers.contains(&(start + ADDRESS_CODE_LENGTH)) =>
+                        {
+                            Operand::CodePointer(get_usize(&mut 0, value))
+                        }
+                        _ => Operand::Read(address, length(l)),
+                    }
+                }
+                OperandKind::Write(l) => Operand::Write(
+                    Address::from_bytes(program_memory, pointer, length(l))?,
+                    length(l),
+                ),
+            });
+        }
+        Ok(operands)
+    };
+    Some(decode())
+}
+
 pub trait Instruction {
     /// Returns the address of the instruction in program memory
     fn get_address(&self) -> usize;
 }
 
 pub trait Execute {
-    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize);
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes an instruction with placeholder operands following its registered layout
+    fn encode_sample(code: InstructionCodeType, layout: &[OperandKind]) -> Vec<u8> {
+        let mut bytes = Vec::from(code.to_le_bytes());
+        for kind in layout {
+            match kind {
+                OperandKind::Size => bytes.extend(4usize.to_le_bytes()),
+                OperandKind::Signed | OperandKind::Format | OperandKind::NewLine => bytes.push(0),
+                OperandKind::Target | OperandKind::StackSize => bytes.extend(8usize.to_le_bytes()),
+                OperandKind::Read(_) | OperandKind::Write(_) => {
+                    bytes.extend(Address::StackDirect(16).get_bytes())
+                }
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn encodes_and_decodes_every_layout() {
+        let codes = (0..=InstructionCodeType::MAX)
+            .filter_map(|code| Some((code, get_operand_layout(code)?)))
+            .collect::<Vec<_>>();
+        assert!(!codes.is_empty());
+
+        for (code, layout) in codes {
+            let bytes = encode_sample(code, layout);
+
+            let mut pointer = INSTRUCTION_CODE_LENGTH;
+            let operands = decode_operands(code, &bytes, &mut pointer, &[])
+                .unwrap()
+                .unwrap();
+            assert_eq!(operands.len(), layout.len());
+            assert_eq!(pointer, bytes.len(), "decoding instruction [{}]", code);
+            assert_eq!(
+                encode_instruction(code, &operands, |position| position).0,
+                bytes,
+                "{} doesn't encode the operands it decodes",
+                get_instruction_name(code).unwrap()
+            );
+
+            let mut pointer = INSTRUCTION_CODE_LENGTH;
+            debug_instruction(code, &bytes, &mut pointer)
+                .unwrap()
+                .unwrap();
+            assert_eq!(pointer, bytes.len());
+        }
+    }
+
+    #[test]
+    fn formats_operands_by_kind() {
+        let mut program_memory = MemoryManager::new();
+        let print = append_instruction(
+            &mut program_memory,
+            print_22::PRINT_INSTRUCTION_CODE,
+            &[
+                Operand::Byte(PrintFormat::Signed.to_byte()),
+                Operand::Byte(1),
+                Operand::Size(8),
+                Operand::Read(Address::StackDirect(16), 8),
+            ],
+        );
+        let jump = append_instruction(
+            &mut program_memory,
+            jump_if_not_9::JUMP_IF_NOT_INSTRUCTION_CODE,
+            &[
+                Operand::Target(print),
+                Operand::Read(Address::StackDirect(0), BOOLEAN_SIZE),
+            ],
+        );
+
+        let debug = |position: usize| {
+            let memory = &program_memory.memory;
+            let mut pointer = position + INSTRUCTION_CODE_LENGTH;
+            let code =
+                InstructionCodeType::from_le_bytes(memory[position..pointer].try_into().unwrap());
+            debug_instruction(code, memory, &mut pointer)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(
+            debug(print),
+            format!(
+                "PrintInstruction (signed, new line, 8 bytes) {}",
+                Address::StackDirect(16)
+            )
+        );
+        assert_eq!(
+            debug(jump),
+            format!(
+                "JumpIfNotInstruction {} -> {}",
+                Address::StackDirect(0),
+                format_jump_target(print)
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "don't match its layout")]
+    fn rejects_operands_not_matching_layout() {
+        append_instruction(
+            &mut MemoryManager::new(),
+            copy_3::COPY_INSTRUCTION_CODE,
+            &[Operand::Size(8), Operand::Read(Address::StackDirect(0), 8)],
+        );
+    }
+
+    #[test]
+    fn properties_match_operand_layout() {
+        for code in 0..=InstructionCodeType::MAX {
+            let Some(layout) = get_operand_layout(code) else {
+                assert!(get_instruction_properties(code).is_none());
+                continue;
+            };
+            let properties = get_instruction_properties(code).unwrap();
+            let name = get_instruction_name(code).unwrap();
+            let reads = layout
+                .iter()
+                .filter(|kind| matches!(kind, OperandKind::Read(_)))
+                .count();
+            let writes = layout
+                .iter()
+                .filter(|kind| matches!(kind, OperandKind::Write(_)))
+                .count();
+            let has_target = layout
+                .iter()
+                .any(|kind| matches!(kind, OperandKind::Target));
+
+            if let Faults::OnZero(read) = properties.faults {
+                assert!(read < reads, "{} faults on a missing operand", name);
+            }
+            if properties.effect == Effect::Pure {
+                assert!(writes <= 1, "{} has several results", name);
+            }
+            if properties.flow == Flow::Branch {
+                assert!(has_target, "{} branches without a target", name);
+            }
+            if has_target {
+                assert_ne!(properties.flow, Flow::Next, "{} has an unused target", name);
+            }
+        }
+    }
+
+    #[test]
+    fn decodes_code_pointers() {
+        let layout = get_operand_layout(copy_3::COPY_INSTRUCTION_CODE).unwrap();
+        let mut bytes = Vec::from(copy_3::COPY_INSTRUCTION_CODE.to_le_bytes());
+        bytes.extend(USIZE_BYTES.to_le_bytes());
+        let immediate_start = bytes.len() + ADDRESS_CODE_LENGTH;
+        bytes.extend(Address::Immediate(Vec::from(40usize.to_le_bytes())).get_bytes());
+        bytes.extend(Address::StackDirect(0).get_bytes());
+
+        let mut pointer = INSTRUCTION_CODE_LENGTH;
+        let operands = decode_operands(
+            copy_3::COPY_INSTRUCTION_CODE,
+            &bytes,
+            &mut pointer,
+            &[immediate_start],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(operands.len(), layout.len());
+        assert!(matches!(operands[1], Operand::CodePointer(40)));
+    }
 }
@@ -1,7 +1,8 @@
 use crate::address::Address;
+use crate::execution::RuntimeResult;
 use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
 use crate::processing::instructions::{
-    Execute, Instruction, InstructionCodeType, INSTRUCTION_CODE_LENGTH,
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand, INSTRUCTION_CODE_LENGTH,
 };
 use crate::util::{get_usize, USIZE_BYTES};
 
@@ -18,24 +19,15 @@ impl CopyInstruction {
         address_to: &Address,
         size: usize,
     ) -> Self {
-        if address_to.is_immediate() {
-            panic!(
-                "Attempted to create CopyInstruction that overwrites Immediate (program) memory!"
-            );
-        }
-
-        let size_bytes = size.to_le_bytes();
-        let mut from_bytes = address_from.get_bytes();
-        let mut to_bytes = address_to.get_bytes();
-        let mut instruction_memory = Vec::with_capacity(
-            INSTRUCTION_CODE_LENGTH + from_bytes.len() + to_bytes.len() + size_bytes.len(),
+        let address = append_instruction(
+            program_memory,
+            COPY_INSTRUCTION_CODE,
+            &[
+                Operand::Size(size),
+                Operand::Read(address_from.clone(), size),
+                Operand::Write(address_to.clone(), size),
+            ],
         );
-        instruction_memory.extend(COPY_INSTRUCTION_CODE.to_le_bytes());
-        instruction_memory.extend(size_bytes.iter());
-        instruction_memory.append(&mut from_bytes);
-        instruction_memory.append(&mut to_bytes);
-
-        let address = program_memory.append(&instruction_memory);
 
         Self { address }
     }
@@ -51,25 +43,39 @@ impl CopyInstruction {
         );
     }
 
-    #[allow(unused_variables)]
-    pub fn get_debug(program_memory: &[u8], pointer: &mut usize) -> String {
-        let size = get_usize(pointer, program_memory);
-        *pointer += Address::get_address_size(program_memory, *pointer, size);
-        *pointer += Address::get_address_size(program_memory, *pointer, size);
-        "CopyInstruction".to_string()
+    pub fn get_destination_address(&self, program_memory: &MemoryManager) -> RuntimeResult<usize> {
+        let size = get_usize(
+            &mut (self.address + INSTRUCTION_CODE_LENGTH),
+            &program_memory.memory,
+        );
+        let source = self.get_source_address();
+        Ok(source + Address::get_address_size(&program_memory.memory, source, size)?)
+    }
+
+    pub fn set_destination(
+        &self,
+        new_destination: &Address,
+        program_memory: &mut MemoryManager,
+    ) -> RuntimeResult<()> {
+        program_memory.overwrite(
+            self.get_destination_address(program_memory)?,
+            &new_destination.get_bytes(),
+        );
+        Ok(())
     }
 }
 
 impl Execute for CopyInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
         let size = get_usize(pointer, memory.program_memory());
 
         let data =
-            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory);
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
         let data_destination =
-            Address::evaluate_address(pointer, &MemoryLocation::Program, &size, memory);
+            Address::evaluate_address(pointer, &MemoryLocation::Program, &size, memory)?;
         let data = Vec::from(data);
-        memory.overwrite_data(&data_destination.1, data_destination.0, &data);
+        memory.overwrite_data(&data_destination.1, data_destination.0, &data)?;
+        Ok(())
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::default_instruction_impl;
+use crate::execution::RuntimeResult;
 use crate::memory::RuntimeMemoryManager;
 use crate::processing::instructions::Execute;
 
@@ -9,7 +10,8 @@ pub struct DumpInstruction {
 default_instruction_impl!(DumpInstruction, DUMP_INSTRUCTION_CODE, 5);
 
 impl Execute for DumpInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, _pointer: &mut usize) {
+    fn execute(memory: &mut RuntimeMemoryManager, _pointer: &mut usize) -> RuntimeResult<()> {
         memory.dump_all("dump");
+        Ok(())
     }
 }
@@ -1,8 +1,10 @@
 use crate::address::Address;
+use crate::execution::RuntimeResult;
 use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
-use crate::processing::instructions::{Execute, Instruction, InstructionCodeType};
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
 use crate::util::USIZE_BYTES;
-use std::usize;
 
 pub struct DynamicJumpInstruction {
     address: usize,
@@ -11,14 +13,11 @@ pub struct DynamicJumpInstruction {
 pub const DYNAMIC_JUMP_INSTRUCTION_CODE: InstructionCodeType = 11;
 impl DynamicJumpInstruction {
     pub fn new_alloc(program_memory: &mut MemoryManager, destination: &Address) -> Self {
-        #[allow(unused_mut)]
-        let mut instruction_memory = Vec::new();
-        instruction_memory.extend(DYNAMIC_JUMP_INSTRUCTION_CODE.to_le_bytes());
-        instruction_memory.append(&mut destination.get_bytes());
-
-        // assert_eq!(instruction_memory.len() - INSTRUCTION_CODE_LENGTH, Self::get_size());
-
-        let address = program_memory.append(&instruction_memory);
+        let address = append_instruction(
+            program_memory,
+            DYNAMIC_JUMP_INSTRUCTION_CODE,
+            &[Operand::Read(destination.clone(), USIZE_BYTES)],
+        );
 
         Self { address }
     }
@@ -34,12 +33,6 @@ impl DynamicJumpInstruction {
     //         &new_destination.to_le_bytes(),
     //     );
     // }
-
-    #[allow(unused_variables)]
-    pub fn get_debug(program_memory: &[u8], pointer: &mut usize) -> String {
-        *pointer += Address::get_address_size(program_memory, *pointer, USIZE_BYTES);
-        stringify!(DynamicJumpInstruction).to_string()
-    }
 }
 
 impl Instruction for DynamicJumpInstruction {
@@ -49,17 +42,18 @@ impl Instruction for DynamicJumpInstruction {
 }
 
 impl Execute for DynamicJumpInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
         let dest = usize::from_le_bytes(
             Address::evaluate_address_to_data(
                 pointer,
                 &MemoryLocation::Program,
                 &USIZE_BYTES,
                 memory,
-            )
+            )?
             .try_into()
             .unwrap(),
         );
         *pointer = dest;
+        Ok(())
     }
 }
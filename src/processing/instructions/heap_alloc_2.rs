@@ -1,7 +1,8 @@
 use crate::address::Address;
-use crate::memory::{MemoryLocation, RuntimeMemoryManager};
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
 use crate::processing::instructions::{
-    Execute, Instruction, InstructionCodeType, INSTRUCTION_CODE_LENGTH,
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
 };
 use crate::util::{get_usize, USIZE_BYTES};
 
@@ -12,45 +13,49 @@ pub struct HeapAllocInstruction {
 pub const HEAP_ALLOC_INSTRUCTION_CODE: InstructionCodeType = 2;
 
 impl HeapAllocInstruction {
+    /// Allocates a heap frame with the size (`usize`) read from `size`
     pub fn new_alloc(
-        program_memory: &mut crate::memory::MemoryManager,
-        size: usize,
+        program_memory: &mut MemoryManager,
+        size: &Address,
         write_frame_id_to: &Address,
     ) -> Self {
-        if write_frame_id_to.is_immediate() {
-            panic!("Can't write frame id to Immediate address!");
-        }
-
-        let size_bytes = size.to_le_bytes();
-        let mut output_bytes = write_frame_id_to.get_bytes();
-        let mut instruction_memory =
-            Vec::with_capacity(INSTRUCTION_CODE_LENGTH + size_bytes.len() + output_bytes.len());
-        instruction_memory.extend(HEAP_ALLOC_INSTRUCTION_CODE.to_le_bytes());
-        instruction_memory.extend(size_bytes.iter());
-        instruction_memory.append(&mut output_bytes);
-
-        let address = program_memory.append(&instruction_memory);
+        let address = append_instruction(
+            program_memory,
+            HEAP_ALLOC_INSTRUCTION_CODE,
+            &[
+                Operand::Read(size.clone(), USIZE_BYTES),
+                Operand::Write(write_frame_id_to.clone(), USIZE_BYTES),
+            ],
+        );
 
         Self { address }
     }
-
-    #[allow(unused_variables)]
-    pub fn get_debug(program_memory: &[u8], pointer: &mut usize) -> String {
-        let _size = get_usize(pointer, program_memory);
-        *pointer += Address::get_address_size(program_memory, *pointer, USIZE_BYTES);
-        "HeapAllocInstruction".to_string()
-    }
 }
 
 impl Execute for HeapAllocInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) {
-        let size = get_usize(pointer, memory.program_memory());
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let position = *pointer;
+        let size = get_usize(
+            &mut 0,
+            Address::evaluate_address_to_data(
+                pointer,
+                &MemoryLocation::Program,
+                &USIZE_BYTES,
+                memory,
+            )?,
+        );
+        //? Sizes computed from negative integers wrap to values above `isize::MAX`
+        if size > isize::MAX as usize {
+            return Err(RuntimeErrorKind::InvalidFrameSize(size));
+        }
         let write_frame_id_to =
-            Address::evaluate_address(pointer, &MemoryLocation::Program, &size, memory);
+            Address::evaluate_address(pointer, &MemoryLocation::Program, &USIZE_BYTES, memory)?;
 
+        memory.on_heap_allocation(position);
         let id = memory.heap_memory().create_frame(size);
 
-        memory.overwrite_data(&write_frame_id_to.1, write_frame_id_to.0, &id.to_le_bytes())
+        memory.overwrite_data(&write_frame_id_to.1, write_frame_id_to.0, &id.to_le_bytes())?;
+        Ok(())
     }
 }
 
@@ -0,0 +1,46 @@
+use crate::address::Address;
+use crate::execution::RuntimeResult;
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::util::{get_usize, USIZE_BYTES};
+
+pub struct HeapFreeInstruction {
+    address: usize,
+}
+
+pub const HEAP_FREE_INSTRUCTION_CODE: InstructionCodeType = 24;
+
+impl HeapFreeInstruction {
+    pub fn new_alloc(program_memory: &mut MemoryManager, frame: &Address) -> Self {
+        let address = append_instruction(
+            program_memory,
+            HEAP_FREE_INSTRUCTION_CODE,
+            &[Operand::Read(frame.clone(), USIZE_BYTES)],
+        );
+
+        Self { address }
+    }
+}
+
+impl Execute for HeapFreeInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let frame_data = Address::evaluate_address_to_data(
+            pointer,
+            &MemoryLocation::Program,
+            &USIZE_BYTES,
+            memory,
+        )?;
+        let frame = get_usize(&mut 0, frame_data);
+
+        memory.heap_memory().free_frame(frame)?;
+        Ok(())
+    }
+}
+
+impl Instruction for HeapFreeInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
@@ -0,0 +1,71 @@
+use crate::address::Address;
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::less_20::LESS_MAX_SIZE;
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::util::{get_i128, get_usize};
+
+pub struct IndexCheckInstruction {
+    address: usize,
+}
+
+pub const INDEX_CHECK_INSTRUCTION_CODE: InstructionCodeType = 26;
+
+impl IndexCheckInstruction {
+    /// Halts the program if the signed `index` isn't in the range `0..length`
+    pub fn new_alloc(
+        program_memory: &mut MemoryManager,
+        index: &Address,
+        length: &Address,
+        size: usize,
+    ) -> Self {
+        assert!(
+            size <= LESS_MAX_SIZE,
+            "IndexCheckInstruction only supports values up to {} bytes",
+            LESS_MAX_SIZE
+        );
+
+        let address = append_instruction(
+            program_memory,
+            INDEX_CHECK_INSTRUCTION_CODE,
+            &[
+                Operand::Size(size),
+                Operand::Read(index.clone(), size),
+                Operand::Read(length.clone(), size),
+            ],
+        );
+
+        Self { address }
+    }
+}
+
+impl Execute for IndexCheckInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let size = get_usize(pointer, memory.program_memory());
+        let index = get_i128(Address::evaluate_address_to_data(
+            pointer,
+            &MemoryLocation::Program,
+            &size,
+            memory,
+        )?);
+        let length = get_i128(Address::evaluate_address_to_data(
+            pointer,
+            &MemoryLocation::Program,
+            &size,
+            memory,
+        )?);
+
+        if !(0..length).contains(&index) {
+            return Err(RuntimeErrorKind::IndexOutOfBounds(index, length));
+        }
+        Ok(())
+    }
+}
+
+impl Instruction for IndexCheckInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
@@ -0,0 +1,143 @@
+use crate::address::Address;
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::print_22::PrintFormat;
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::processing::types::boolean::{BOOL_FALSE, BOOL_TRUE};
+use crate::processing::types::character::CHAR_SIZE;
+use crate::processing::types::Type;
+use crate::util::{get_usize, USIZE_BYTES};
+
+pub struct InputInstruction {
+    address: usize,
+}
+
+pub const INPUT_INSTRUCTION_CODE: InstructionCodeType = 23;
+
+impl InputInstruction {
+    /// Reads a line from stdin and parses it into `destination` according to `format`
+    pub fn new_alloc(
+        program_memory: &mut MemoryManager,
+        destination: &dyn Type,
+        format: PrintFormat,
+    ) -> Self {
+        let length = destination.get_length();
+        let address = append_instruction(
+            program_memory,
+            INPUT_INSTRUCTION_CODE,
+            &[
+                Operand::Byte(format.to_byte()),
+                Operand::Size(length),
+                Operand::Write(destination.get_address().clone(), length),
+            ],
+        );
+
+        Self { address }
+    }
+}
+
+/// Parses `input` into `length` little-endian bytes
+fn parse_input(input: &str, format: PrintFormat, length: usize) -> Result<Vec<u8>, String> {
+    let bits = (length * 8) as u32;
+    match format {
+        PrintFormat::Unsigned => {
+            let value: u128 = input
+                .parse()
+                .map_err(|_| format!("'{}' is not a valid unsigned integer", input))?;
+            if bits < 128 && value >> bits != 0 {
+                return Err(format!("'{}' doesn't fit into {} bytes", input, length));
+            }
+            Ok(Vec::from(&value.to_le_bytes()[..length]))
+        }
+        PrintFormat::Signed => {
+            let value: i128 = input
+                .parse()
+                .map_err(|_| format!("'{}' is not a valid integer", input))?;
+            if bits < 128 && (value < -(1 << (bits - 1)) || value >= 1 << (bits - 1)) {
+                return Err(format!("'{}' doesn't fit into {} bytes", input, length));
+            }
+            Ok(Vec::from(&value.to_le_bytes()[..length]))
+        }
+        PrintFormat::Boolean => match input {
+            "true" => Ok(vec![BOOL_TRUE; length]),
+            "false" => Ok(vec![BOOL_FALSE; length]),
+            _ => Err(format!("'{}' is not a valid bool (true / false)", input)),
+        },
+        PrintFormat::String => {
+            let capacity = (length - USIZE_BYTES) / CHAR_SIZE;
+            let char_count = input.chars().count();
+            if char_count > capacity {
+                return Err(format!(
+                    "'{}' is longer than the string capacity ({})",
+                    input, capacity
+                ));
+            }
+            let mut data = Vec::with_capacity(length);
+            data.extend(char_count.to_le_bytes());
+            for c in input.chars() {
+                data.extend((c as u32).to_le_bytes());
+            }
+            data.resize(length, 0);
+            Ok(data)
+        }
+        PrintFormat::Character => {
+            let mut chars = input.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(Vec::from(&(c as u128).to_le_bytes()[..length])),
+                _ => Err(format!("'{}' is not a single character", input)),
+            }
+        }
+    }
+}
+
+impl Execute for InputInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let format = PrintFormat::from_byte(memory.program_memory()[*pointer])?;
+        *pointer += 1;
+        let length = get_usize(pointer, memory.program_memory());
+        let data_destination =
+            Address::evaluate_address(pointer, &MemoryLocation::Program, &length, memory)?;
+
+        let input = memory.read_input()?;
+        let data = parse_input(&input, format, length).map_err(RuntimeErrorKind::InvalidInput)?;
+
+        memory.overwrite_data(&data_destination.1, data_destination.0, &data)?;
+        Ok(())
+    }
+}
+
+impl Instruction for InputInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execution::RuntimeErrorKind;
+    use crate::test_util::{compile_ok, run_captured};
+
+    #[test]
+    fn reads_each_format() {
+        let (result, output) = run_captured(
+            compile_ok(
+                "int i = 0\nbool b = false\nchar c = 'a'\nstr[4] s = \"\"\ninput i\ninput b\ninput c\ninput s\nprint i\nprint b\nprint c\nprint s\n",
+            ),
+            false,
+            &["-12", "true", "x", "hey"],
+        );
+        result.unwrap();
+        assert_eq!(output, "-12\ntrue\nx\nhey\n");
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let (result, _) = run_captured(compile_ok("int i = 0\ninput i\n"), false, &["twelve"]);
+        assert!(matches!(
+            result.unwrap_err().kind,
+            RuntimeErrorKind::InvalidInput(_)
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::default_instruction_impl;
+use crate::execution::RuntimeResult;
 use crate::memory::{MemoryManager, RuntimeMemoryManager};
 use crate::processing::instructions::{Execute, INSTRUCTION_CODE_LENGTH};
 use crate::util::get_usize;
@@ -11,7 +12,7 @@ default_instruction_impl!(
     JumpInstruction,
     JUMP_INSTRUCTION_CODE,
     10,
-    (destination, usize)
+    (destination, Target)
 );
 
 impl JumpInstruction {
@@ -24,7 +25,8 @@ impl JumpInstruction {
 }
 
 impl Execute for JumpInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
         *pointer = get_usize(pointer, memory.program_memory());
+        Ok(())
     }
 }
@@ -0,0 +1,84 @@
+use crate::address::Address;
+use crate::execution::RuntimeResult;
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::processing::types::boolean::{BOOLEAN_SIZE, BOOL_FALSE, BOOL_TRUE};
+use crate::util::{get_i128, get_u128, get_usize};
+
+pub struct LessInstruction {
+    address: usize,
+}
+
+pub const LESS_INSTRUCTION_CODE: InstructionCodeType = 20;
+
+/// Largest operand size (in bytes) supported by the comparison instruction
+pub const LESS_MAX_SIZE: usize = 16;
+
+impl LessInstruction {
+    /// Writes whether `lhs` is less than `rhs` as a boolean. `signed` selects two's
+    /// complement or unsigned interpretation of the operands
+    pub fn new_alloc(
+        program_memory: &mut MemoryManager,
+        address_from_lhs: &Address,
+        address_from_rhs: &Address,
+        address_to: &Address,
+        size: usize,
+        signed: bool,
+    ) -> Self {
+        assert!(
+            size <= LESS_MAX_SIZE,
+            "LessInstruction only supports values up to {} bytes",
+            LESS_MAX_SIZE
+        );
+
+        let address = append_instruction(
+            program_memory,
+            LESS_INSTRUCTION_CODE,
+            &[
+                Operand::Size(size),
+                Operand::Byte(signed as u8),
+                Operand::Read(address_from_lhs.clone(), size),
+                Operand::Read(address_from_rhs.clone(), size),
+                Operand::Write(address_to.clone(), BOOLEAN_SIZE),
+            ],
+        );
+
+        Self { address }
+    }
+}
+
+impl Execute for LessInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let size = get_usize(pointer, memory.program_memory());
+        let signed = memory.program_memory()[*pointer] != 0;
+        *pointer += 1;
+        let data_lhs =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
+        let data_rhs =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
+
+        let result = if signed {
+            get_i128(data_lhs) < get_i128(data_rhs)
+        } else {
+            get_u128(data_lhs) < get_u128(data_rhs)
+        };
+
+        let data_destination =
+            Address::evaluate_address(pointer, &MemoryLocation::Program, &BOOLEAN_SIZE, memory)?;
+
+        memory.overwrite_data(
+            &data_destination.1,
+            data_destination.0,
+            &[if result { BOOL_TRUE } else { BOOL_FALSE }],
+        )?;
+        Ok(())
+    }
+}
+
+impl Instruction for LessInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
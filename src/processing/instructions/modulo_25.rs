@@ -0,0 +1,88 @@
+use crate::address::Address;
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::util::{get_i128, get_u128, get_usize};
+
+pub struct ModuloInstruction {
+    address: usize,
+}
+
+pub const MODULO_INSTRUCTION_CODE: InstructionCodeType = 25;
+
+/// Largest operand size (in bytes) supported by the modulo instruction
+pub const MODULO_MAX_SIZE: usize = 16;
+
+impl ModuloInstruction {
+    /// Calculates the remainder of dividing two `size` byte integers. The result takes the sign of
+    /// the dividend (lhs). `signed` selects two's complement or unsigned interpretation of the
+    /// operands. Dividing by zero is a runtime error
+    pub fn new_alloc(
+        program_memory: &mut MemoryManager,
+        address_from_lhs: &Address,
+        address_from_rhs: &Address,
+        address_to: &Address,
+        size: usize,
+        signed: bool,
+    ) -> Self {
+        assert!(
+            size <= MODULO_MAX_SIZE,
+            "ModuloInstruction only supports values up to {} bytes",
+            MODULO_MAX_SIZE
+        );
+
+        let address = append_instruction(
+            program_memory,
+            MODULO_INSTRUCTION_CODE,
+            &[
+                Operand::Size(size),
+                Operand::Byte(signed as u8),
+                Operand::Read(address_from_lhs.clone(), size),
+                Operand::Read(address_from_rhs.clone(), size),
+                Operand::Write(address_to.clone(), size),
+            ],
+        );
+
+        Self { address }
+    }
+}
+
+impl Execute for ModuloInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let size = get_usize(pointer, memory.program_memory());
+        let signed = memory.program_memory()[*pointer] != 0;
+        *pointer += 1;
+        let data_lhs =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
+        let data_rhs =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
+
+        let result = if signed {
+            let (lhs, rhs) = (get_i128(data_lhs), get_i128(data_rhs));
+            if rhs == 0 {
+                return Err(RuntimeErrorKind::DivisionByZero);
+            }
+            lhs.wrapping_rem(rhs).to_le_bytes()
+        } else {
+            let (lhs, rhs) = (get_u128(data_lhs), get_u128(data_rhs));
+            if rhs == 0 {
+                return Err(RuntimeErrorKind::DivisionByZero);
+            }
+            (lhs % rhs).to_le_bytes()
+        };
+
+        let data_destination =
+            Address::evaluate_address(pointer, &MemoryLocation::Program, &size, memory)?;
+
+        memory.overwrite_data(&data_destination.1, data_destination.0, &result[..size])?;
+        Ok(())
+    }
+}
+
+impl Instruction for ModuloInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
@@ -0,0 +1,78 @@
+use crate::address::Address;
+use crate::execution::RuntimeResult;
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::util::{get_u128, get_usize};
+
+pub struct MultiplyInstruction {
+    address: usize,
+}
+
+pub const MULTIPLY_INSTRUCTION_CODE: InstructionCodeType = 18;
+
+/// Largest operand size (in bytes) supported by the multiplication instruction
+pub const MULTIPLY_MAX_SIZE: usize = 16;
+
+impl MultiplyInstruction {
+    /// Multiplies two `size` byte integers, wrapping on overflow. As the result is truncated to
+    /// `size` bytes the same instruction works for both signed and unsigned values
+    pub fn new_alloc(
+        program_memory: &mut MemoryManager,
+        address_from_lhs: &Address,
+        address_from_rhs: &Address,
+        address_to: &Address,
+        size: usize,
+    ) -> Self {
+        assert!(
+            size <= MULTIPLY_MAX_SIZE,
+            "MultiplyInstruction only supports values up to {} bytes",
+            MULTIPLY_MAX_SIZE
+        );
+
+        let address = append_instruction(
+            program_memory,
+            MULTIPLY_INSTRUCTION_CODE,
+            &[
+                Operand::Size(size),
+                Operand::Read(address_from_lhs.clone(), size),
+                Operand::Read(address_from_rhs.clone(), size),
+                Operand::Write(address_to.clone(), size),
+            ],
+        );
+
+        Self { address }
+    }
+}
+
+impl Execute for MultiplyInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let size = get_usize(pointer, memory.program_memory());
+        let lhs = get_u128(Address::evaluate_address_to_data(
+            pointer,
+            &MemoryLocation::Program,
+            &size,
+            memory,
+        )?);
+        let rhs = get_u128(Address::evaluate_address_to_data(
+            pointer,
+            &MemoryLocation::Program,
+            &size,
+            memory,
+        )?);
+        let data_destination =
+            Address::evaluate_address(pointer, &MemoryLocation::Program, &size, memory)?;
+
+        let result = lhs.wrapping_mul(rhs).to_le_bytes();
+
+        memory.overwrite_data(&data_destination.1, data_destination.0, &result[..size])?;
+        Ok(())
+    }
+}
+
+impl Instruction for MultiplyInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
@@ -0,0 +1,145 @@
+use crate::address::Address;
+use crate::execution::{RuntimeErrorKind, RuntimeResult};
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::processing::symbols::TypeSymbol;
+use crate::processing::types::boolean::BOOL_FALSE;
+use crate::processing::types::character::CHAR_SIZE;
+use crate::processing::types::Type;
+use crate::util::{get_i128, get_u128, get_usize};
+
+pub struct PrintInstruction {
+    address: usize,
+}
+
+pub const PRINT_INSTRUCTION_CODE: InstructionCodeType = 22;
+
+/// How the bytes of a value are interpreted when printed or read from input
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum PrintFormat {
+    Unsigned,
+    Signed,
+    Boolean,
+    Character,
+    String,
+}
+
+impl PrintFormat {
+    pub fn from_type_symbol(type_symbol: &TypeSymbol) -> Option<Self> {
+        match type_symbol {
+            TypeSymbol::Integer => Some(PrintFormat::Signed),
+            TypeSymbol::Pointer => Some(PrintFormat::Unsigned),
+            TypeSymbol::Boolean => Some(PrintFormat::Boolean),
+            TypeSymbol::Character => Some(PrintFormat::Character),
+            TypeSymbol::String => Some(PrintFormat::String),
+            TypeSymbol::Class | TypeSymbol::Array => None,
+        }
+    }
+
+    pub fn to_byte(self) -> u8 {
+        match self {
+            PrintFormat::Unsigned => 0,
+            PrintFormat::Signed => 1,
+            PrintFormat::Boolean => 2,
+            PrintFormat::Character => 3,
+            PrintFormat::String => 4,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            PrintFormat::Unsigned => "unsigned",
+            PrintFormat::Signed => "signed",
+            PrintFormat::Boolean => "bool",
+            PrintFormat::Character => "char",
+            PrintFormat::String => "string",
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> RuntimeResult<Self> {
+        Ok(match byte {
+            0 => PrintFormat::Unsigned,
+            1 => PrintFormat::Signed,
+            2 => PrintFormat::Boolean,
+            3 => PrintFormat::Character,
+            4 => PrintFormat::String,
+            b => return Err(RuntimeErrorKind::UnknownPrintFormat(b)),
+        })
+    }
+
+    /// Formats raw little-endian data as text
+    pub fn format(&self, data: &[u8]) -> String {
+        match self {
+            PrintFormat::Unsigned => get_u128(data).to_string(),
+            PrintFormat::Signed => get_i128(data).to_string(),
+            PrintFormat::Boolean => {
+                if data.iter().all(|b| *b == BOOL_FALSE) {
+                    "false".to_string()
+                } else {
+                    "true".to_string()
+                }
+            }
+            PrintFormat::Character => char::from_u32(get_u128(data) as u32)
+                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                .to_string(),
+            PrintFormat::String => {
+                let mut pointer = 0;
+                let length = get_usize(&mut pointer, data);
+                data[pointer..]
+                    .chunks(CHAR_SIZE)
+                    .take(length)
+                    .map(|c| PrintFormat::Character.format(c))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl PrintInstruction {
+    /// Prints `to_print` formatted according to its type. `new_line` appends a line break
+    pub fn new_alloc(
+        program_memory: &mut MemoryManager,
+        to_print: &dyn Type,
+        format: PrintFormat,
+        new_line: bool,
+    ) -> Self {
+        let length = to_print.get_length();
+        let address = append_instruction(
+            program_memory,
+            PRINT_INSTRUCTION_CODE,
+            &[
+                Operand::Byte(format.to_byte()),
+                Operand::Byte(new_line as u8),
+                Operand::Size(length),
+                Operand::Read(to_print.get_address().clone(), length),
+            ],
+        );
+
+        Self { address }
+    }
+}
+
+impl Execute for PrintInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let format = PrintFormat::from_byte(memory.program_memory()[*pointer])?;
+        let new_line = memory.program_memory()[*pointer + 1] != 0;
+        *pointer += 2;
+        let length = get_usize(pointer, memory.program_memory());
+        let data =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &length, memory)?;
+
+        let mut text = format.format(data);
+        if new_line {
+            text.push('\n');
+        }
+        memory.write_output(&text)
+    }
+}
+
+impl Instruction for PrintInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
@@ -1,4 +1,5 @@
 use crate::default_instruction_impl;
+use crate::execution::RuntimeResult;
 use crate::memory::{MemoryManager, RuntimeMemoryManager};
 use crate::processing::instructions::{Execute, INSTRUCTION_CODE_LENGTH};
 use crate::util::get_usize;
@@ -11,7 +12,7 @@ default_instruction_impl!(
     StackCreateInstruction,
     STACK_CREATE_INSTRUCTION_CODE,
     0,
-    (size, usize)
+    (size, StackSize)
 );
 
 impl StackCreateInstruction {
@@ -35,8 +36,9 @@ impl StackCreateInstruction {
 }
 
 impl Execute for StackCreateInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
         let size = StackCreateInstruction::get_stack_size(pointer, memory);
         memory.stack_memory().create_stack(size);
+        Ok(())
     }
 }
@@ -1,4 +1,5 @@
 use crate::default_instruction_impl;
+use crate::execution::RuntimeResult;
 use crate::memory::RuntimeMemoryManager;
 use crate::processing::instructions::Execute;
 
@@ -9,8 +10,8 @@ pub struct StackDownInstruction {
 default_instruction_impl!(StackDownInstruction, STACK_DOWN_INSTRUCTION_CODE, 4);
 
 impl Execute for StackDownInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) {
-        memory.stack_memory().stack_down_and_delete();
-        *pointer += StackDownInstruction::get_size();
+    fn execute(memory: &mut RuntimeMemoryManager, _pointer: &mut usize) -> RuntimeResult<()> {
+        memory.stack_memory().stack_down_and_delete()?;
+        Ok(())
     }
 }
@@ -1,4 +1,5 @@
 use crate::default_instruction_impl;
+use crate::execution::RuntimeResult;
 use crate::memory::RuntimeMemoryManager;
 use crate::processing::instructions::Execute;
 
@@ -9,7 +10,8 @@ pub struct StackUpInstruction {
 default_instruction_impl!(StackUpInstruction, STACK_UP_INSTRUCTION_CODE, 1);
 
 impl Execute for StackUpInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, _pointer: &mut usize) {
+    fn execute(memory: &mut RuntimeMemoryManager, _pointer: &mut usize) -> RuntimeResult<()> {
         memory.stack_memory().stack_up();
+        Ok(())
     }
 }
@@ -0,0 +1,79 @@
+use crate::address::Address;
+use crate::execution::RuntimeResult;
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::processing::types::boolean::{BOOLEAN_SIZE, BOOL_FALSE, BOOL_TRUE};
+use crate::processing::types::character::CHAR_SIZE;
+use crate::util::get_usize;
+
+pub struct StringLessInstruction {
+    address: usize,
+}
+
+pub const STRING_LESS_INSTRUCTION_CODE: InstructionCodeType = 27;
+
+impl StringLessInstruction {
+    /// Writes whether the chars at `lhs` come before those at `rhs` as a boolean. `size` bytes
+    /// of chars are compared one char at a time by code point
+    pub fn new_alloc(
+        program_memory: &mut MemoryManager,
+        address_from_lhs: &Address,
+        address_from_rhs: &Address,
+        address_to: &Address,
+        size: usize,
+    ) -> Self {
+        assert_eq!(
+            size % CHAR_SIZE,
+            0,
+            "StringLessInstruction compares whole chars"
+        );
+
+        let address = append_instruction(
+            program_memory,
+            STRING_LESS_INSTRUCTION_CODE,
+            &[
+                Operand::Size(size),
+                Operand::Read(address_from_lhs.clone(), size),
+                Operand::Read(address_from_rhs.clone(), size),
+                Operand::Write(address_to.clone(), BOOLEAN_SIZE),
+            ],
+        );
+
+        Self { address }
+    }
+}
+
+impl Execute for StringLessInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let size = get_usize(pointer, memory.program_memory());
+        let data_lhs =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
+        let data_rhs =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
+
+        let chars = |data: &[u8]| {
+            data.chunks_exact(CHAR_SIZE)
+                .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+                .collect::<Vec<_>>()
+        };
+        let result = chars(data_lhs) < chars(data_rhs);
+
+        let data_destination =
+            Address::evaluate_address(pointer, &MemoryLocation::Program, &BOOLEAN_SIZE, memory)?;
+
+        memory.overwrite_data(
+            &data_destination.1,
+            data_destination.0,
+            &[if result { BOOL_TRUE } else { BOOL_FALSE }],
+        )?;
+        Ok(())
+    }
+}
+
+impl Instruction for StringLessInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
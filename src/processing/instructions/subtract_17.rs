@@ -0,0 +1,72 @@
+use crate::address::Address;
+use crate::execution::RuntimeResult;
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::instructions::{
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
+};
+use crate::util::get_usize;
+
+pub struct SubtractInstruction {
+    address: usize,
+}
+
+pub const SUBTRACT_INSTRUCTION_CODE: InstructionCodeType = 17;
+
+impl SubtractInstruction {
+    pub fn new_alloc(
+        program_memory: &mut MemoryManager,
+        address_from_lhs: &Address,
+        address_from_rhs: &Address,
+        address_to: &Address,
+        size: usize,
+    ) -> Self {
+        let address = append_instruction(
+            program_memory,
+            SUBTRACT_INSTRUCTION_CODE,
+            &[
+                Operand::Size(size),
+                Operand::Read(address_from_lhs.clone(), size),
+                Operand::Read(address_from_rhs.clone(), size),
+                Operand::Write(address_to.clone(), size),
+            ],
+        );
+
+        Self { address }
+    }
+}
+
+impl Execute for SubtractInstruction {
+    fn execute(memory: &mut RuntimeMemoryManager, pointer: &mut usize) -> RuntimeResult<()> {
+        let size = get_usize(pointer, memory.program_memory());
+        let data_lhs =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
+        let data_rhs =
+            Address::evaluate_address_to_data(pointer, &MemoryLocation::Program, &size, memory)?;
+        let data_destination =
+            Address::evaluate_address(pointer, &MemoryLocation::Program, &size, memory)?;
+
+        let mut output_buffer = Vec::with_capacity(size);
+
+        let mut borrow = false;
+        for i in 0..size {
+            let a = data_lhs[i];
+            let b = data_rhs[i];
+
+            let (result, first_borrow) = a.overflowing_sub(b);
+            let (result, second_borrow) = result.overflowing_sub(borrow as u8); // Borrow
+
+            borrow = first_borrow || second_borrow;
+
+            output_buffer.push(result);
+        }
+
+        memory.overwrite_data(&data_destination.1, data_destination.0, &output_buffer)?;
+        Ok(())
+    }
+}
+
+impl Instruction for SubtractInstruction {
+    fn get_address(&self) -> usize {
+        self.address
+    }
+}
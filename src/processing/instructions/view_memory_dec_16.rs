@@ -1,8 +1,9 @@
 use crate::address::Address;
 
+use crate::execution::RuntimeResult;
 use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
 use crate::processing::instructions::{
-    Execute, Instruction, InstructionCodeType, INSTRUCTION_CODE_LENGTH,
+    append_instruction, Execute, Instruction, InstructionCodeType, Operand,
 };
 use crate::processing::types::Type;
 use crate::util::get_usize;
@@ -15,29 +16,18 @@ pub const VIEW_MEMORY_DEC_INSTRUCTION_CODE: InstructionCodeType = 16;
 
 impl ViewMemoryDecInstruction {
     pub fn new_alloc(program_memory: &mut MemoryManager, to_dump: &dyn Type) -> Self {
-        let (address, length) = (to_dump.get_address(), to_dump.get_length());
-
-        #[allow(unused_mut)]
-        let mut instruction_memory = Vec::with_capacity(Self::get_size() + INSTRUCTION_CODE_LENGTH);
-        instruction_memory.extend(VIEW_MEMORY_DEC_INSTRUCTION_CODE.to_le_bytes());
-        instruction_memory.extend(length.to_le_bytes());
-        instruction_memory.extend(address.get_bytes());
-
-        let address = program_memory.append(&instruction_memory);
+        let length = to_dump.get_length();
+        let address = append_instruction(
+            program_memory,
+            VIEW_MEMORY_DEC_INSTRUCTION_CODE,
+            &[
+                Operand::Size(length),
+                Operand::Read(to_dump.get_address().clone(), length),
+            ],
+        );
 
         Self { address }
     }
-
-    pub fn get_size() -> usize {
-        0
-    }
-
-    #[allow(unused_variables)]
-    pub fn get_debug(program_memory: &[u8], pointer: &mut usize) -> String {
-        let size = get_usize(pointer, program_memory);
-        *pointer += Address::get_address_size(program_memory, *pointer, size);
-        stringify!(PrintDumpInstruction).to_string()
-    }
 }
 
 impl Instruction for ViewMemoryDecInstruction {
@@ -47,10 +37,10 @@ impl Instruction for ViewMemoryDecInstruction {
 }
 
 impl Execute for ViewMemoryDecInstruction {
-    fn execute(memory: &mut RuntimeMemoryManager, _pointer: &mut usize) {
+    fn execute(memory: &mut RuntimeMemoryManager, _pointer: &mut usize) -> RuntimeResult<()> {
         let length = get_usize(_pointer, memory.program_memory());
         let data =
-            Address::evaluate_address_to_data(_pointer, &MemoryLocation::Program, &length, memory);
+            Address::evaluate_address_to_data(_pointer, &MemoryLocation::Program, &length, memory)?;
 
         if data.len() > 16 {
             print!("Data too big for decimal representation - ");
@@ -65,5 +55,6 @@ impl Execute for ViewMemoryDecInstruction {
             }
             println!("{}", u128::from_le_bytes(data_full));
         }
+        Ok(())
     }
 }
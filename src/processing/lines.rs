@@ -1,10 +1,12 @@
 pub mod base_block;
 pub mod break_continue;
+pub mod builtin_call;
 pub mod call;
 pub mod class_line;
 pub mod dump;
 pub mod function_line;
 pub mod if_line;
+pub mod loop_line;
 pub mod variable_assignment;
 pub mod variable_initialisation;
 pub mod view_memory;
@@ -29,6 +29,12 @@ impl LineHandler for BreakContinueLine {
                         Err(e) => ProcessingResult::Failure(e),
                     }
                 }
+                Keyword::Return => {
+                    match block_coordinator.return_block_handler(memory_managers, &line[1..]) {
+                        Ok(_) => ProcessingResult::Success,
+                        Err(e) => ProcessingResult::Failure(e),
+                    }
+                }
                 _ => ProcessingResult::Unmatched,
             },
             _ => ProcessingResult::Unmatched,
@@ -0,0 +1,212 @@
+use crate::address::Address;
+use crate::errors::CompileError;
+use crate::memory::MemoryManager;
+use crate::processing::arithmetic::{
+    evaluate_arithmetic_to_any_type, evaluate_arithmetic_to_types,
+};
+use crate::processing::blocks::BlockCoordinator;
+use crate::processing::instructions::heap_alloc_2::HeapAllocInstruction;
+use crate::processing::instructions::heap_free_24::HeapFreeInstruction;
+use crate::processing::instructions::input_23::InputInstruction;
+use crate::processing::instructions::print_22::{PrintFormat, PrintInstruction};
+use crate::processing::lines::LineHandler;
+use crate::processing::processor::ProcessingResult;
+use crate::processing::symbols::{Builtin, Literal, Symbol, TypeSymbol};
+use crate::processing::types::TypeFactory;
+use crate::q;
+use crate::util::join_reference_name;
+
+pub struct BuiltinCallLine {}
+
+impl LineHandler for BuiltinCallLine {
+    fn process_line(
+        line: &[Symbol],
+        program_memory: &mut MemoryManager,
+        block_coordinator: &mut BlockCoordinator,
+    ) -> ProcessingResult {
+        if line.is_empty() {
+            return ProcessingResult::Unmatched;
+        }
+
+        let builtin = match line[0] {
+            Symbol::Builtin(builtin) => builtin,
+            _ => return ProcessingResult::Unmatched,
+        };
+
+        if line.len() < 2 {
+            return ProcessingResult::Failure(
+                format!(
+                    "{} must be followed by a value",
+                    builtin.get_code_representation()
+                )
+                .into(),
+            );
+        }
+
+        match builtin {
+            Builtin::Print | Builtin::PrintChars => {
+                let (stack_sizes, reference_stack) =
+                    block_coordinator.get_stack_sizes_and_reference_stack();
+
+                let value = q!(evaluate_arithmetic_to_any_type(
+                    &line[1..],
+                    program_memory,
+                    reference_stack,
+                    stack_sizes
+                ));
+                let value = value.as_ref();
+
+                let Some(format) = PrintFormat::from_type_symbol(&value.get_type_symbol()) else {
+                    return ProcessingResult::Failure(
+                        format!("{} cannot be printed", value.get_type_symbol()).into(),
+                    );
+                };
+
+                PrintInstruction::new_alloc(
+                    program_memory,
+                    value,
+                    format,
+                    builtin == Builtin::Print,
+                );
+            }
+            Builtin::Input => {
+                let name = match &line[1..] {
+                    [Symbol::Name(name)] => name,
+                    _ => {
+                        return ProcessingResult::Failure(
+                            "input must be followed by a variable".to_string().into(),
+                        )
+                    }
+                };
+
+                let variable = q!(q!(block_coordinator.get_reference(name)).get_variable_ref());
+                if variable.get_address().is_immediate() {
+                    return ProcessingResult::Failure(
+                        CompileError::from(format!("{} is a constant", join_reference_name(name)))
+                            .with_name(name),
+                    );
+                }
+
+                let Some(format) = PrintFormat::from_type_symbol(&variable.get_type_symbol())
+                else {
+                    return ProcessingResult::Failure(
+                        format!("{} cannot be read from input", variable.get_type_symbol()).into(),
+                    );
+                };
+
+                InputInstruction::new_alloc(program_memory, variable, format);
+            }
+            Builtin::Alloc => {
+                fn formatting_error() -> ProcessingResult {
+                    ProcessingResult::Failure(format!(
+                        "{} must be formatted {} [{} variable] [Type or size] or {} [array variable] [number of items]",
+                        Builtin::Alloc.get_code_representation(),
+                        Builtin::Alloc.get_code_representation(),
+                        TypeSymbol::Pointer,
+                        Builtin::Alloc.get_code_representation(),
+                    ).into())
+                }
+
+                //? Allows both `alloc p int` and `alloc(p, int)`
+                let arguments: Vec<&[Symbol]> = match &line[1..] {
+                    [Symbol::List(items)] => items.iter().map(|i| i.as_slice()).collect(),
+                    other => other.iter().map(std::slice::from_ref).collect(),
+                };
+                let [[Symbol::Name(name)], size] = arguments.as_slice() else {
+                    return formatting_error();
+                };
+
+                let (stack_sizes, reference_stack) =
+                    block_coordinator.get_stack_sizes_and_reference_stack();
+                let variable = q!(q!(reference_stack.get_reference(name)).get_variable_ref());
+
+                //? Heap-backed arrays are allocated with a number of items e.g. `alloc xs n`
+                if variable.get_type_symbol() == TypeSymbol::Array {
+                    let count = q!(evaluate_arithmetic_to_types(
+                        size,
+                        &[TypeSymbol::Integer],
+                        program_memory,
+                        reference_stack,
+                        stack_sizes
+                    ));
+                    q!(variable.runtime_heap_alloc(count.as_ref(), program_memory, stack_sizes));
+                    return ProcessingResult::Success;
+                }
+
+                let size = match size {
+                    [Symbol::Literal(Literal::Int(size))] => {
+                        let Ok(size) = usize::try_from(*size) else {
+                            return ProcessingResult::Failure(
+                                format!("Invalid size ({})", size).into(),
+                            );
+                        };
+                        size
+                    }
+                    [other] => q!(TypeFactory::get_unallocated_type_from_symbol(
+                        other,
+                        reference_stack
+                    ))
+                    .get_length(),
+                    _ => return formatting_error(),
+                };
+
+                if variable.get_type_symbol() != TypeSymbol::Pointer {
+                    return formatting_error();
+                }
+
+                HeapAllocInstruction::new_alloc(
+                    program_memory,
+                    &Address::Immediate(Vec::from(size.to_le_bytes())),
+                    variable.get_address(),
+                );
+            }
+            Builtin::Free => {
+                let (stack_sizes, reference_stack) =
+                    block_coordinator.get_stack_sizes_and_reference_stack();
+
+                let value = q!(evaluate_arithmetic_to_any_type(
+                    &line[1..],
+                    program_memory,
+                    reference_stack,
+                    stack_sizes
+                ));
+                if !value.as_ref().holds_heap_frame() {
+                    return ProcessingResult::Failure(
+                        format!(
+                            "{} must be followed by a {} or heap-backed array - received {}",
+                            Builtin::Free.get_code_representation(),
+                            TypeSymbol::Pointer,
+                            value.as_ref().get_type_symbol()
+                        )
+                        .into(),
+                    );
+                }
+
+                HeapFreeInstruction::new_alloc(program_memory, value.as_ref().get_address());
+            }
+        }
+
+        ProcessingResult::Success
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{compile_err, get_output};
+
+    #[test]
+    fn prints_each_type() {
+        assert_eq!(
+            get_output(
+                "int i = -12\nbool b = true\nchar c = 'x'\nstr s = \"hi\"\nptr p = 7\nprint i\nprint b\nprint c\nprint s\nprint p\nprintc c\nprintc 'y'\nprint i + 2\n"
+            ),
+            "-12\ntrue\nx\nhi\n7\nxy-10\n"
+        );
+    }
+
+    #[test]
+    fn rejects_input_into_constants() {
+        let errors = compile_err("int[3] xs\ninput xs.length\n");
+        assert_eq!(errors[0].message, "xs.length is a constant");
+    }
+}
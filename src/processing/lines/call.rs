@@ -18,14 +18,16 @@ impl LineHandler for CallLine {
     ) -> ProcessingResult {
         if line.len() < 2
             || !matches!(line[0], Symbol::Name(_))
-            || !matches!(line[1], Symbol::List(_))
+            || line[1].get_list_items().is_none()
         {
             return ProcessingResult::Unmatched;
         }
 
         if line.len() > 2 {
             return ProcessingResult::Failure(
-                "A call can't be followed by anything on the same line".to_string(),
+                "A call can't be followed by anything on the same line"
+                    .to_string()
+                    .into(),
             );
         }
 
@@ -34,28 +36,26 @@ impl LineHandler for CallLine {
             _ => panic!(),
         };
 
-        let args = match &line[1] {
-            Symbol::List(args) => args,
-            _ => panic!(),
-        };
+        let args = line[1].get_list_items().unwrap();
 
         // let (function_reference, offset) = q!(block_coordinator.get_reference_and_offset(name));
 
         let (stack_sizes, reference_stack) =
             block_coordinator.get_stack_sizes_and_reference_stack();
 
-        let function_reference = q!(q!(reference_stack.get_reference(name)).get_function_ref());
+        let (function_reference, arguments) =
+            q!(reference_stack.get_function_and_arguments(name, args));
 
-        let incomplete_function_call =
-            q!(function_reference.call(None, args, program_memory, reference_stack, stack_sizes));
+        let (_, incomplete_function_call) = q!(function_reference.call(
+            &arguments,
+            false,
+            program_memory,
+            reference_stack,
+            stack_sizes
+        ));
 
         if let MustUseOption::Some(incomplete_function_call) = incomplete_function_call {
-            reference_stack
-                .get_reference_mut(name)
-                .unwrap()
-                .get_function_mut()
-                .unwrap()
-                .add_incomplete_function_call(incomplete_function_call);
+            function_reference.add_incomplete_function_call(incomplete_function_call);
         }
 
         // q!(reference_stack.register_reference_with_offset(function_reference, offset));
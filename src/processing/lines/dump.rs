@@ -24,7 +24,9 @@ impl LineHandler for DumpLine {
 
         if line.len() > 1 {
             return ProcessingResult::Failure(
-                "Dump cannot be followed by anything else".to_string(),
+                "Dump cannot be followed by anything else"
+                    .to_string()
+                    .into(),
             );
         }
 
@@ -31,12 +31,15 @@ impl LineHandler for IfLine {
                 }
             }
             //? If not intercepted, there was no if
-            Symbol::Block(Block::Elif | Block::Else) => ProcessingResult::Failure(format!(
-                "{} and {} can only follow an {} statement",
-                Block::Elif,
-                Block::Else,
-                Block::If
-            )),
+            Symbol::Block(Block::Elif | Block::Else) => ProcessingResult::Failure(
+                format!(
+                    "{} and {} can only follow an {} statement",
+                    Block::Elif,
+                    Block::Else,
+                    Block::If
+                )
+                .into(),
+            ),
             _ => ProcessingResult::Unmatched,
         }
     }
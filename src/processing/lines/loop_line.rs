@@ -0,0 +1,34 @@
+use crate::memory::MemoryManager;
+
+use crate::processing::blocks::for_block::ForBlock;
+use crate::processing::blocks::loop_block::LoopBlock;
+use crate::processing::blocks::BlockCoordinator;
+
+use crate::processing::lines::LineHandler;
+use crate::processing::processor::ProcessingResult;
+use crate::processing::symbols::{Block, Symbol};
+use crate::q;
+
+pub struct LoopLine {}
+
+impl LineHandler for LoopLine {
+    fn process_line(
+        line: &[Symbol],
+        program_memory: &mut MemoryManager,
+        block_coordinator: &mut BlockCoordinator,
+    ) -> ProcessingResult {
+        if line.is_empty() {
+            return ProcessingResult::Unmatched;
+        }
+
+        let handler = match line[0] {
+            Symbol::Block(Block::Loop) => LoopBlock::new_block(),
+            Symbol::Block(Block::For) => ForBlock::new_block(),
+            _ => return ProcessingResult::Unmatched,
+        };
+
+        q!(block_coordinator.add_block_handler(handler, program_memory, line));
+
+        ProcessingResult::Success
+    }
+}
@@ -1,15 +1,37 @@
 use super::LineHandler;
+use crate::errors::{CompileError, CompileResult};
 use crate::memory::MemoryManager;
-use crate::processing::arithmetic::evaluate_arithmetic_into_type;
+use crate::processing::arithmetic::{
+    evaluate_arithmetic_into_type, evaluate_indexer, evaluate_member, get_field,
+};
 use crate::processing::blocks::BlockCoordinator;
 use crate::processing::processor::ProcessingResult;
 
-use crate::processing::symbols::Symbol;
+use crate::processing::symbols::{Symbol, TypeSymbol};
+use crate::processing::types::{Type, LENGTH_FIELD};
 
 use crate::q;
+use crate::util::join_reference_name;
 
 pub struct VariableAssignmentLine {}
 
+/// Errors if `field` of `parent` can't be assigned to. Changing the length of an array or string
+/// would let it be indexed past its end
+fn check_writable(parent: &dyn Type, field: &str, name: &[String]) -> CompileResult<()> {
+    if field == LENGTH_FIELD
+        && matches!(
+            parent.get_type_symbol(),
+            TypeSymbol::Array | TypeSymbol::String
+        )
+    {
+        return Err(
+            CompileError::from(format!("{} is read-only", join_reference_name(name)))
+                .with_name(name),
+        );
+    }
+    Ok(())
+}
+
 impl LineHandler for VariableAssignmentLine {
     fn process_line(
         line: &[Symbol],
@@ -17,7 +39,10 @@ impl LineHandler for VariableAssignmentLine {
         block_coordinator: &mut BlockCoordinator,
     ) -> ProcessingResult {
         if line.len() < 2
-            || !matches!(line[0], Symbol::Name(_))
+            || !matches!(
+                line[0],
+                Symbol::Name(_) | Symbol::Indexer(_, _) | Symbol::Member(_, _)
+            )
             || !matches!(line[1], Symbol::Assigner(_))
         {
             return ProcessingResult::Unmatched;
@@ -25,19 +50,60 @@ impl LineHandler for VariableAssignmentLine {
 
         if line.len() < 3 {
             return ProcessingResult::Failure(
-                "Name and Assigner must be followed by a value".to_string(),
+                "Name and Assigner must be followed by a value"
+                    .to_string()
+                    .into(),
             );
         }
 
-        let name = match &line[0] {
-            Symbol::Name(name) => name,
-            _ => panic!(),
-        };
-
         let (stack_sizes, reference_stack) =
             block_coordinator.get_stack_sizes_and_reference_stack();
 
-        let variable = q!(q!(reference_stack.get_reference(name)).get_variable_ref());
+        let evaluated;
+        let variable = match &line[0] {
+            Symbol::Name(name) => {
+                let variable = q!(q!(reference_stack.get_reference(name)).get_variable_ref());
+                //? e.g. the length of a fixed length array
+                if variable.get_address().is_immediate() {
+                    return ProcessingResult::Failure(
+                        CompileError::from(format!("{} is a constant", join_reference_name(name)))
+                            .with_name(name),
+                    );
+                }
+                if let [parent @ .., field] = name.as_slice() {
+                    if !parent.is_empty() {
+                        let parent =
+                            q!(q!(reference_stack.get_reference(parent)).get_variable_ref());
+                        q!(check_writable(parent, field, name));
+                    }
+                }
+                variable
+            }
+            Symbol::Indexer(applied_to, index) => {
+                evaluated = q!(evaluate_indexer(
+                    applied_to,
+                    index,
+                    program_memory,
+                    reference_stack,
+                    stack_sizes
+                ));
+                evaluated.as_ref()
+            }
+            Symbol::Member(value, path) => {
+                let (field, parent_path) = path.split_last().unwrap();
+                let parent = q!(evaluate_member(
+                    value,
+                    parent_path,
+                    program_memory,
+                    reference_stack,
+                    stack_sizes
+                ));
+                q!(check_writable(parent.as_ref(), field, path));
+                evaluated = q!(get_field(parent.as_ref(), field));
+                evaluated.as_ref()
+            }
+            _ => panic!(),
+        };
 
         let assigner = match &line[1] {
             Symbol::Assigner(assigner) => assigner,
@@ -1,11 +1,14 @@
 use super::LineHandler;
+use crate::address::Address;
+use crate::errors::CompileResult;
 use crate::memory::MemoryManager;
 use crate::processing::arithmetic::evaluate_arithmetic_into_type;
 use crate::processing::blocks::{BlockCoordinator, BlockType, StackSizes};
+use crate::processing::instructions::copy_3::CopyInstruction;
 use crate::processing::processor::ProcessingResult;
 use crate::processing::reference_manager::{Reference, ReferenceStack};
 
-use crate::processing::symbols::{Assigner, Symbol};
+use crate::processing::symbols::{Assigner, Symbol, CLASS_SELF_NAME};
 use crate::processing::types::TypeFactory;
 use crate::q;
 
@@ -18,19 +21,26 @@ impl VariableInitialisationLine {
         reference_stack: &mut ReferenceStack,
         stack_sizes: &mut StackSizes,
         has_value: bool,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         if has_value && line.len() < 4 {
             return Err(
                 "Type must be followed by a Name, '=' and value to initialise a variable"
-                    .to_string(),
+                    .to_string()
+                    .into(),
             );
         } else if !has_value && line.len() != 2 {
-            return Err("Variable must be formatted [Type] [Name]".to_string());
+            return Err("Variable must be formatted [Type] [Name]"
+                .to_string()
+                .into());
         }
 
         let name = match &line[1] {
             Symbol::Name(name) => name,
-            _ => return Err("Type must be followed by a Name to initialise a variable".to_string()),
+            _ => {
+                return Err("Type must be followed by a Name to initialise a variable"
+                    .to_string()
+                    .into())
+            }
         };
 
         if has_value {
@@ -39,16 +49,18 @@ impl VariableInitialisationLine {
                 _ => {
                     return Err(
                         "Type must be followed by a Name, '=' and value to initialise a variable"
-                            .to_string(),
+                            .to_string()
+                            .into(),
                     )
                 }
             };
         }
 
-        let mut object = match &line[0] {
-            Symbol::Type(type_symbol) => TypeFactory::get_unallocated_type(type_symbol)?,
-            _ => return Err(format!("Type expected, recieved {}", &line[0])),
-        };
+        if name[0] == CLASS_SELF_NAME {
+            return Err(format!("Name '{}' is reserved", CLASS_SELF_NAME).into());
+        }
+
+        let mut object = TypeFactory::get_unallocated_type_from_symbol(&line[0], reference_stack)?;
 
         object.allocate_variable(stack_sizes, program_memory)?;
 
@@ -66,6 +78,36 @@ impl VariableInitialisationLine {
 
         Ok(())
     }
+
+    /// Adds a property to the class currently being defined
+    fn handle_property(
+        line: &[Symbol],
+        program_memory: &mut MemoryManager,
+        reference_stack: &mut ReferenceStack,
+    ) -> CompileResult<()> {
+        if line.len() != 2 {
+            return Err("Class properties must be formatted [Type] [Name]"
+                .to_string()
+                .into());
+        }
+
+        let name = match &line[1] {
+            Symbol::Name(name) if name.len() == 1 && name[0] != CLASS_SELF_NAME => name[0].clone(),
+            _ => return Err("Invalid property name".to_string().into()),
+        };
+
+        let property = TypeFactory::get_unallocated_type_from_symbol(&line[0], reference_stack)?;
+
+        let class = reference_stack
+            .get_reference_mut(&[CLASS_SELF_NAME.to_string()])?
+            .get_class_mut()?;
+
+        if property.get_class_name() == Some(class.name.as_str()) {
+            return Err(format!("Class '{}' cannot contain itself", class.name).into());
+        }
+
+        class.add_property(name, property, program_memory)
+    }
 }
 
 impl LineHandler for VariableInitialisationLine {
@@ -74,28 +116,71 @@ impl LineHandler for VariableInitialisationLine {
         program_memory: &mut MemoryManager,
         block_coordinator: &mut BlockCoordinator,
     ) -> ProcessingResult {
-        if line.is_empty() || !matches!(line[0], Symbol::Type(_)) {
+        if line.is_empty() {
             return ProcessingResult::Unmatched;
         }
+        match &line[0] {
+            Symbol::Type(_) => {}
+            Symbol::Indexer(sized, _) if matches!(sized.as_ref(), Symbol::Type(_)) => {}
+            //? Class instance e.g. `Point p = (1, 2)`. Arrays of instances e.g. `Point[2] ps` are
+            //? matched so that `ArrayType::new` can report that they aren't supported
+            Symbol::Name(_) | Symbol::Indexer(_, _)
+                if matches!(line.get(1), Some(Symbol::Name(_))) => {}
+            _ => return ProcessingResult::Unmatched,
+        }
 
-        // println!("{}", block_coordinator.get_block_handler_type());
         if matches!(block_coordinator.get_block_handler_type(), BlockType::Class) {
-            println!("Inner");
             q!(block_coordinator.get_block_handler_mut().handle_line(line));
+            let (reference_stack, _) = block_coordinator.get_reference_stack_and_stack_sizes();
+            q!(VariableInitialisationLine::handle_property(
+                line,
+                program_memory,
+                reference_stack
+            ));
             return ProcessingResult::Success;
         }
 
         let (reference_stack, stack_sizes) =
             block_coordinator.get_reference_stack_and_stack_sizes();
 
+        //? Sized types can be declared without a value e.g. `int[10] xs` and start zeroed
+        let has_value = !(line.len() == 2 && matches!(line[0], Symbol::Indexer(_, _)));
+
         q!(VariableInitialisationLine::handle_initialisation(
             line,
             program_memory,
             reference_stack,
             stack_sizes,
-            true
+            has_value
         ));
 
+        if !has_value {
+            let Symbol::Name(name) = &line[1] else {
+                unreachable!()
+            };
+            let variable = q!(q!(reference_stack.get_reference(name)).get_variable_ref());
+            CopyInstruction::new_alloc(
+                program_memory,
+                &Address::Immediate(vec![0; variable.get_length()]),
+                variable.get_address(),
+                variable.get_length(),
+            );
+        }
+
         ProcessingResult::Success
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::compile_err;
+
+    #[test]
+    fn rejects_arrays_of_class_instances() {
+        let errors = compile_err("class Point\n    int x\nPoint[2] ps\n");
+        assert_eq!(
+            errors[0].message,
+            "Arrays can only hold int, bool, char or ptr items"
+        );
+    }
+}
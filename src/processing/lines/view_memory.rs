@@ -27,7 +27,9 @@ impl LineHandler for ViewMemoryLine {
         };
 
         if line.len() != 2 {
-            return ProcessingResult::Failure("viewmem must be followed by a variable".to_string());
+            return ProcessingResult::Failure(
+                "viewmem must be followed by a variable".to_string().into(),
+            );
         }
 
         let variable = match &line[1] {
@@ -36,7 +38,7 @@ impl LineHandler for ViewMemoryLine {
             }
             _ => {
                 return ProcessingResult::Failure(
-                    "viewmem must be followed by a variable".to_string(),
+                    "viewmem must be followed by a variable".to_string().into(),
                 )
             }
         };
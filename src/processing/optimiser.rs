@@ -0,0 +1,314 @@
+mod constant_folding;
+mod control_flow;
+mod copy_elimination;
+mod dead_code;
+mod instruction;
+mod stack_analysis;
+
+use crate::memory::MemoryManager;
+use crate::processing::optimiser::constant_folding::{
+    fold_conditional_jumps, fold_constants, propagate_constants,
+};
+use crate::processing::optimiser::control_flow::ControlFlow;
+use crate::processing::optimiser::copy_elimination::collapse_copies;
+use crate::processing::optimiser::dead_code::{
+    remove_dead_stores, remove_redundant_jumps, remove_unreachable, thread_jumps,
+};
+use crate::processing::optimiser::instruction::{decode_program, decode_range, DecodedInstruction};
+use crate::processing::optimiser::stack_analysis::StackAnalysis;
+
+/// How much the compiled program is optimised
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OptimisationLevel {
+    /// `-O0` - the program is left as compiled
+    None,
+    /// `-O1` - constant folding, copy elimination, jump threading and dead code elimination
+    Basic,
+}
+
+impl OptimisationLevel {
+    /// Parses a command line flag e.g. `-O1`
+    pub fn from_flag(flag: &str) -> Option<Self> {
+        match flag {
+            "-O0" => Some(Self::None),
+            "-O1" => Some(Self::Basic),
+            _ => None,
+        }
+    }
+}
+
+/// Optimises the compiled program in place. Positions in program memory (jumps, return
+/// addresses, the entry point, debug info and the pointer map) are relocated to match
+///
+/// The program is left unchanged if it contains instructions the optimiser doesn't understand
+pub fn optimise(
+    program_memory: &mut MemoryManager,
+    level: OptimisationLevel,
+) -> Result<(), String> {
+    if level == OptimisationLevel::None {
+        return Ok(());
+    }
+
+    let mut instructions = decode_program(program_memory)?;
+    let entry_point = program_memory.get_entry_point();
+
+    //? Each pass can create opportunities for the others e.g. propagating a constant allows an
+    //? operation to be folded, which allows a branch to be removed
+    loop {
+        let mut changed = false;
+
+        let (analysis, control_flow) = analyse(&instructions, program_memory, entry_point);
+        changed |= propagate_constants(&mut instructions, &analysis, &control_flow);
+        changed |= fold_constants(&mut instructions);
+        changed |= fold_conditional_jumps(&mut instructions);
+        changed |= thread_jumps(&mut instructions);
+
+        let (analysis, control_flow) = analyse(&instructions, program_memory, entry_point);
+        changed |= collapse_copies(&mut instructions, &analysis, &control_flow);
+
+        let (analysis, _) = analyse(&instructions, program_memory, entry_point);
+        changed |= remove_dead_stores(&mut instructions, &analysis);
+
+        let (_, control_flow) = analyse(&instructions, program_memory, entry_point);
+        changed |= remove_unreachable(&mut instructions, &control_flow);
+        changed |= remove_redundant_jumps(&mut instructions);
+
+        if !changed {
+            break;
+        }
+    }
+
+    relocate(program_memory, &instructions);
+    Ok(())
+}
+
+/// Returns whether execution of the code from `start` up to `end` in program memory can reach
+/// `end` e.g. whether a function body can finish without returning. Jumps out of the code are
+/// assumed not to come back, other than calls which return after the call
+pub fn reaches_end(
+    program_memory: &MemoryManager,
+    start: usize,
+    end: usize,
+) -> Result<bool, String> {
+    let mut instructions = decode_range(program_memory, start, end)?;
+    //? Stands in for whatever follows the code so that falling through and jumping to `end`
+    //? both lead to an instruction
+    instructions.push(DecodedInstruction::new_jump(end, end));
+    let control_flow = ControlFlow::new(&instructions, start);
+    Ok(control_flow.is_reachable(instructions.len() - 1))
+}
+
+fn analyse(
+    instructions: &[DecodedInstruction],
+    program_memory: &MemoryManager,
+    entry_point: usize,
+) -> (StackAnalysis, ControlFlow) {
+    (
+        StackAnalysis::new(instructions, &program_memory.debug_info),
+        ControlFlow::new(instructions, entry_point),
+    )
+}
+
+/// Replaces program memory with the optimised instructions
+fn relocate(program_memory: &mut MemoryManager, instructions: &[DecodedInstruction]) {
+    let mut positions = Vec::with_capacity(instructions.len());
+    let mut length = 0;
+    for instruction in instructions {
+        positions.push(length);
+        length += instruction.encode(|position| position).0.len();
+    }
+
+    //? Positions of removed instructions map to the next instruction that remains
+    let map = |position: usize| {
+        let index = instructions.partition_point(|instruction| instruction.origin < position);
+        positions.get(index).copied().unwrap_or(length)
+    };
+
+    let mut memory = Vec::with_capacity(length);
+    let mut code_pointers = Vec::new();
+    for instruction in instructions {
+        let (bytes, pointers) = instruction.encode(map);
+        code_pointers.extend(pointers.into_iter().map(|pointer| memory.len() + pointer));
+        memory.extend(bytes);
+    }
+
+    program_memory.memory = memory;
+    program_memory.code_pointers = code_pointers;
+    program_memory.set_entry_point(map(program_memory.get_entry_point()));
+    program_memory.debug_info.relocate(map);
+    program_memory.pointer_map.relocate(map);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::Address;
+    use crate::execution::RuntimeErrorKind;
+    use crate::processing::instructions::jump_if_not_9::JUMP_IF_NOT_INSTRUCTION_CODE;
+    use crate::processing::instructions::Operand;
+    use crate::processing::types::boolean::{BOOLEAN_SIZE, BOOL_TRUE};
+    use crate::test_util::{compile_ok, get_output, run};
+
+    /// Program that computes a value with a loop, a branch and a function call
+    const CHECKED_PROGRAM: &str = "\
+fn unused(int a) -> int
+    return a * 2
+
+fn square(int a) -> int
+    return a * a
+
+int total = 0
+int i = 0
+while i < 10
+    if i == 3
+        i += 1
+        continue
+    total += square(i)
+    i += 1
+print total
+int[2] check
+int unchanged = check[total - 275]
+";
+
+    fn copy(origin: usize, value: u8, destination: usize) -> DecodedInstruction {
+        DecodedInstruction::new_copy(
+            origin,
+            Address::Immediate(vec![value]),
+            Address::StackDirect(destination),
+            1,
+        )
+    }
+
+    fn target(instruction: &DecodedInstruction) -> usize {
+        instruction.targets().next().unwrap()
+    }
+
+    #[test]
+    fn parses_flags() {
+        assert_eq!(
+            OptimisationLevel::from_flag("-O0"),
+            Some(OptimisationLevel::None)
+        );
+        assert_eq!(
+            OptimisationLevel::from_flag("-O1"),
+            Some(OptimisationLevel::Basic)
+        );
+        assert_eq!(OptimisationLevel::from_flag("-O2"), None);
+    }
+
+    #[test]
+    fn o0_leaves_program_unchanged() {
+        let mut memory = compile_ok(CHECKED_PROGRAM);
+        let unoptimised = memory.memory.clone();
+        optimise(&mut memory, OptimisationLevel::None).unwrap();
+        assert_eq!(memory.memory, unoptimised);
+    }
+
+    #[test]
+    fn o1_preserves_behaviour() {
+        assert_eq!(get_output(CHECKED_PROGRAM), "276\n");
+
+        let mut memory = compile_ok(CHECKED_PROGRAM);
+        let unoptimised_length = memory.memory.len();
+        optimise(&mut memory, OptimisationLevel::Basic).unwrap();
+        assert!(memory.memory.len() < unoptimised_length);
+    }
+
+    #[test]
+    fn o1_preserves_runtime_errors() {
+        let source = CHECKED_PROGRAM.replace("275", "274");
+        let mut memory = compile_ok(&source);
+        optimise(&mut memory, OptimisationLevel::Basic).unwrap();
+        assert!(matches!(
+            run(memory).unwrap_err().kind,
+            RuntimeErrorKind::IndexOutOfBounds(2, 2)
+        ));
+    }
+
+    #[test]
+    fn removes_unreachable_code() {
+        let mut instructions = vec![
+            DecodedInstruction::new_jump(0, 20),
+            copy(10, 1, 0),
+            copy(20, 2, 0),
+        ];
+        let control_flow = ControlFlow::new(&instructions, 0);
+        assert!(remove_unreachable(&mut instructions, &control_flow));
+        assert_eq!(
+            instructions
+                .iter()
+                .map(|instruction| instruction.origin)
+                .collect::<Vec<_>>(),
+            vec![0, 20]
+        );
+
+        assert!(remove_redundant_jumps(&mut instructions));
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].get_copied_constant(), Some(&[2][..]));
+    }
+
+    #[test]
+    fn removes_unused_functions() {
+        let with_unused = compile_ok(CHECKED_PROGRAM);
+        let without_unused = compile_ok(
+            &CHECKED_PROGRAM.replace("fn unused(int a) -> int\n    return a * 2\n\n", ""),
+        );
+        assert!(with_unused.memory.len() > without_unused.memory.len());
+
+        let mut with_unused = with_unused;
+        let mut without_unused = without_unused;
+        optimise(&mut with_unused, OptimisationLevel::Basic).unwrap();
+        optimise(&mut without_unused, OptimisationLevel::Basic).unwrap();
+        assert_eq!(with_unused.memory.len(), without_unused.memory.len());
+    }
+
+    #[test]
+    fn threads_jumps() {
+        let mut instructions = vec![
+            DecodedInstruction::new_jump(0, 20),
+            copy(10, 1, 0),
+            DecodedInstruction::new_jump(20, 40),
+            copy(30, 2, 0),
+            DecodedInstruction::new_jump(40, 10),
+        ];
+        assert!(thread_jumps(&mut instructions));
+        assert_eq!(target(&instructions[0]), 10);
+        assert_eq!(target(&instructions[2]), 10);
+        assert_eq!(target(&instructions[4]), 10);
+        assert!(!thread_jumps(&mut instructions));
+    }
+
+    #[test]
+    fn threads_jumps_in_loops() {
+        let mut instructions = vec![
+            DecodedInstruction::new_jump(0, 10),
+            DecodedInstruction::new_jump(10, 0),
+        ];
+        assert!(!thread_jumps(&mut instructions));
+        assert_eq!(target(&instructions[0]), 10);
+        assert_eq!(target(&instructions[1]), 0);
+    }
+
+    #[test]
+    fn folds_constant_conditions() {
+        let jump_if_not = |origin, value| DecodedInstruction {
+            origin,
+            code: JUMP_IF_NOT_INSTRUCTION_CODE,
+            operands: vec![
+                Operand::Target(30),
+                Operand::Read(Address::Immediate(vec![value]), BOOLEAN_SIZE),
+            ],
+        };
+        let mut instructions = vec![
+            jump_if_not(0, BOOL_TRUE),
+            jump_if_not(10, 0),
+            copy(20, 1, 0),
+        ];
+
+        assert!(fold_conditional_jumps(&mut instructions));
+        assert_eq!(instructions.len(), 2);
+        assert!(instructions[0].is_jump());
+        assert_eq!(instructions[0].origin, 10);
+        assert_eq!(target(&instructions[0]), 30);
+    }
+}
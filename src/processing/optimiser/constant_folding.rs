@@ -0,0 +1,116 @@
+use crate::address::Address;
+use crate::execution::execute_step;
+use crate::memory::{MemoryLocation, MemoryManager, RuntimeMemoryManager};
+use crate::processing::optimiser::control_flow::ControlFlow;
+use crate::processing::optimiser::instruction::DecodedInstruction;
+use crate::processing::optimiser::stack_analysis::StackAnalysis;
+use crate::processing::types::boolean::BOOL_TRUE;
+
+/// Replaces reads of stack slots that always hold the same constant with the constant
+///
+/// A slot holds a constant if the only instruction writing to it copies an immediate and that
+/// instruction runs before every read of the slot
+pub fn propagate_constants(
+    instructions: &mut [DecodedInstruction],
+    analysis: &StackAnalysis,
+    control_flow: &ControlFlow,
+) -> bool {
+    let mut changed = false;
+    for definition in 0..instructions.len() {
+        let region = analysis.get_region(definition);
+        let Some(slot) = analysis.get_write(definition) else {
+            continue;
+        };
+        let Some(value) = instructions[definition].get_copied_constant() else {
+            continue;
+        };
+        if !analysis.is_safe(region)
+            || !slot.is_home()
+            || analysis.is_shared(region, &slot)
+            || analysis.get_overlapping_writes(region, &slot).len() != 1
+        {
+            continue;
+        }
+
+        let value = Vec::from(value);
+        for (user, operand) in analysis.get_overlapping_reads(region, &slot) {
+            //? Reads of part of the slot are left as they are
+            if analysis.get_reads(user)[operand] != Some(slot)
+                || !control_flow.dominates(definition, user)
+            {
+                continue;
+            }
+            instructions[user].set_read(operand, Address::Immediate(value.clone()));
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Evaluates operations with only immediate operands, replacing them with a copy of the result
+pub fn fold_constants(instructions: &mut [DecodedInstruction]) -> bool {
+    let mut changed = false;
+    for instruction in instructions.iter_mut() {
+        if instruction.is_copy()
+            || !instruction.is_pure()
+            || !instruction
+                .reads()
+                .all(|(address, _)| matches!(address, Address::Immediate(_)))
+        {
+            continue;
+        }
+        let Some(result) = evaluate(instruction) else {
+            continue;
+        };
+        let (destination, length) = instruction.write().unwrap();
+        *instruction = DecodedInstruction::new_copy(
+            instruction.origin,
+            Address::Immediate(result),
+            destination.clone(),
+            length,
+        );
+        changed = true;
+    }
+    changed
+}
+
+/// Executes an instruction on its own, returning the value it writes. Returns `None` if the
+/// instruction fails e.g. on overflow, leaving the error to happen at runtime
+fn evaluate(instruction: &DecodedInstruction) -> Option<Vec<u8>> {
+    let (_, length) = instruction.write()?;
+    let mut sandboxed = instruction.clone();
+    *sandboxed.write_mut()? = Address::StackDirect(0);
+
+    let (program, _) = sandboxed.encode(|position| position);
+    let mut memory = RuntimeMemoryManager::from_program_memory(MemoryManager::from_vec(program));
+    memory.stack_memory().create_stack(length);
+    execute_step(&mut memory, &mut 0).ok()?;
+    memory
+        .get_data(&MemoryLocation::Stack, 0, length)
+        .ok()
+        .map(Vec::from)
+}
+
+/// Removes conditional jumps that are never taken and makes ones that are always taken
+/// unconditional
+pub fn fold_conditional_jumps(instructions: &mut Vec<DecodedInstruction>) -> bool {
+    let mut changed = false;
+    let mut folded = Vec::with_capacity(instructions.len());
+    for instruction in instructions.drain(..) {
+        let condition = match instruction.reads().next() {
+            Some((Address::Immediate(value), _)) if instruction.is_jump_if_not() => Some(value[0]),
+            _ => None,
+        };
+        let Some(condition) = condition else {
+            folded.push(instruction);
+            continue;
+        };
+        if condition != BOOL_TRUE {
+            let target = instruction.targets().next().unwrap();
+            folded.push(DecodedInstruction::new_jump(instruction.origin, target));
+        }
+        changed = true;
+    }
+    *instructions = folded;
+    changed
+}
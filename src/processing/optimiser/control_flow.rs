@@ -0,0 +1,155 @@
+use crate::processing::optimiser::instruction::DecodedInstruction;
+use std::collections::HashSet;
+
+/// Returns the index of the instruction that execution reaches when jumping to `target` (a
+/// position in the original program memory). Removed instructions are skipped over. Returns
+/// `None` if `target` is the end of the program
+pub fn resolve(instructions: &[DecodedInstruction], target: usize) -> Option<usize> {
+    let index = instructions.partition_point(|instruction| instruction.origin < target);
+    (index < instructions.len()).then_some(index)
+}
+
+/// Graph of the instructions that can execute after each instruction
+pub struct ControlFlow {
+    successors: Vec<Vec<usize>>,
+    /// Instructions that can be reached other than by executing the previous instruction
+    jump_targets: HashSet<usize>,
+    /// Immediate dominator of each reachable instruction
+    dominators: Vec<Option<usize>>,
+    entry: Option<usize>,
+}
+
+impl ControlFlow {
+    //? A call jumps to the function and the function returns to the position stored by the
+    //? instruction holding the return address. The return is modelled as an edge from that
+    //? instruction so that code after a call is dominated by code before it
+    pub fn new(instructions: &[DecodedInstruction], entry_point: usize) -> Self {
+        let mut successors = Vec::with_capacity(instructions.len());
+        let mut jump_targets = HashSet::new();
+        for (i, instruction) in instructions.iter().enumerate() {
+            let mut next = Vec::new();
+            if instruction.falls_through() && i + 1 < instructions.len() {
+                next.push(i + 1);
+            }
+            for target in instruction.targets() {
+                if let Some(target) = resolve(instructions, target) {
+                    jump_targets.insert(target);
+                    next.push(target);
+                }
+            }
+            successors.push(next);
+        }
+
+        let entry = resolve(instructions, entry_point);
+        let mut new = Self {
+            successors,
+            jump_targets,
+            dominators: vec![None; instructions.len()],
+            entry,
+        };
+        new.find_dominators();
+        new
+    }
+
+    /// Returns whether the instruction at `index` can be jumped to
+    pub fn is_jump_target(&self, index: usize) -> bool {
+        self.jump_targets.contains(&index)
+    }
+
+    pub fn is_reachable(&self, index: usize) -> bool {
+        self.dominators[index].is_some()
+    }
+
+    /// Returns whether every path from the entry point to `index` passes through `dominator`
+    pub fn dominates(&self, dominator: usize, index: usize) -> bool {
+        let mut current = index;
+        loop {
+            if current == dominator {
+                return true;
+            }
+            match self.dominators[current] {
+                Some(next) if next != current => current = next,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Returns reachable instructions in reverse postorder
+    fn reverse_postorder(&self) -> Vec<usize> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+        let mut visited = vec![false; self.successors.len()];
+        let mut postorder = Vec::new();
+        //? (instruction, index of the next successor to visit)
+        let mut stack = vec![(entry, 0)];
+        visited[entry] = true;
+        while let Some((instruction, next)) = stack.pop() {
+            if let Some(&successor) = self.successors[instruction].get(next) {
+                stack.push((instruction, next + 1));
+                if !visited[successor] {
+                    visited[successor] = true;
+                    stack.push((successor, 0));
+                }
+            } else {
+                postorder.push(instruction);
+            }
+        }
+        postorder.reverse();
+        postorder
+    }
+
+    /// Finds immediate dominators using the iterative algorithm from "A Simple, Fast Dominance
+    /// Algorithm" (Cooper, Harvey and Kennedy)
+    fn find_dominators(&mut self) {
+        let Some(entry) = self.entry else {
+            return;
+        };
+        let order = self.reverse_postorder();
+        let mut order_index = vec![usize::MAX; self.successors.len()];
+        for (i, instruction) in order.iter().enumerate() {
+            order_index[*instruction] = i;
+        }
+
+        let mut predecessors = vec![Vec::new(); self.successors.len()];
+        for (instruction, successors) in self.successors.iter().enumerate() {
+            for successor in successors {
+                predecessors[*successor].push(instruction);
+            }
+        }
+
+        self.dominators[entry] = Some(entry);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for instruction in order.iter().skip(1) {
+                let mut new_dominator = None;
+                for predecessor in &predecessors[*instruction] {
+                    if self.dominators[*predecessor].is_none() {
+                        continue;
+                    }
+                    new_dominator = Some(match new_dominator {
+                        None => *predecessor,
+                        Some(current) => self.intersect(*predecessor, current, &order_index),
+                    });
+                }
+                if new_dominator.is_some() && self.dominators[*instruction] != new_dominator {
+                    self.dominators[*instruction] = new_dominator;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    fn intersect(&self, mut a: usize, mut b: usize, order_index: &[usize]) -> usize {
+        while a != b {
+            while order_index[a] > order_index[b] {
+                a = self.dominators[a].unwrap();
+            }
+            while order_index[b] > order_index[a] {
+                b = self.dominators[b].unwrap();
+            }
+        }
+        a
+    }
+}
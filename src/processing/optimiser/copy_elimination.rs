@@ -0,0 +1,101 @@
+use crate::processing::optimiser::control_flow::ControlFlow;
+use crate::processing::optimiser::instruction::DecodedInstruction;
+use crate::processing::optimiser::stack_analysis::{Slot, StackAnalysis};
+
+/// Removes temporaries that are only used to pass a value to the next instruction
+///
+/// `op -> S[t]; Copy S[t] -> D` becomes `op -> D` and `Copy X -> S[t]; op S[t]` becomes `op X`
+/// when `S[t]` isn't used anywhere else
+pub fn collapse_copies(
+    instructions: &mut Vec<DecodedInstruction>,
+    analysis: &StackAnalysis,
+    control_flow: &ControlFlow,
+) -> bool {
+    let mut removed = vec![false; instructions.len()];
+    //? Instructions already changed. The analysis doesn't reflect their new operands
+    let mut changed = vec![false; instructions.len()];
+
+    for first in 0..instructions.len().saturating_sub(1) {
+        let second = first + 1;
+        if changed[first] || changed[second] {
+            continue;
+        }
+        let Some(slot) = get_temporary(first, second, instructions, analysis, control_flow) else {
+            continue;
+        };
+        let operand = analysis
+            .get_reads(second)
+            .iter()
+            .position(|read| *read == Some(slot));
+        let Some(operand) = operand else {
+            continue;
+        };
+
+        if instructions[second].is_copy() && operand == 0 {
+            let (destination, length) = instructions[second].write().unwrap();
+            //? An instruction whose destination partially overlaps its source may overwrite
+            //? the source before it has been read
+            let partially_overlapping = analysis.get_write(second).is_some_and(|write| {
+                analysis
+                    .get_reads(first)
+                    .iter()
+                    .flatten()
+                    .any(|read| read.overlaps(&write) && *read != write)
+            });
+            if length != slot.length || partially_overlapping {
+                continue;
+            }
+            let destination = destination.clone();
+            *instructions[first].write_mut().unwrap() = destination;
+            removed[second] = true;
+        } else if instructions[first].is_copy() {
+            let (source, _) = instructions[first].reads().next().unwrap();
+            let source = source.clone();
+            instructions[second].set_read(operand, source);
+            removed[first] = true;
+        } else {
+            continue;
+        }
+        changed[first] = true;
+        changed[second] = true;
+    }
+
+    let mut index = 0;
+    instructions.retain(|_| {
+        index += 1;
+        !removed[index - 1]
+    });
+    removed.contains(&true)
+}
+
+/// Returns the slot written by `first` if it's only read by `second`, which runs immediately
+/// after it
+fn get_temporary(
+    first: usize,
+    second: usize,
+    instructions: &[DecodedInstruction],
+    analysis: &StackAnalysis,
+    control_flow: &ControlFlow,
+) -> Option<Slot> {
+    let region = analysis.get_region(first);
+    let slot = analysis.get_write(first)?;
+    if region != analysis.get_region(second)
+        || !analysis.is_safe(region)
+        || !instructions[first].is_pure()
+        || !slot.is_home()
+        || analysis.is_shared(region, &slot)
+        || control_flow.is_jump_target(second)
+        || analysis.get_overlapping_writes(region, &slot).len() != 1
+    {
+        return None;
+    }
+    let reads = analysis.get_overlapping_reads(region, &slot);
+    match reads.as_slice() {
+        [(user, operand)]
+            if *user == second && analysis.get_reads(second)[*operand] == Some(slot) =>
+        {
+            Some(slot)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,95 @@
+use crate::processing::optimiser::control_flow::{resolve, ControlFlow};
+use crate::processing::optimiser::instruction::DecodedInstruction;
+use crate::processing::optimiser::stack_analysis::StackAnalysis;
+use std::collections::HashSet;
+
+/// Removes instructions that write to stack slots that are never read
+pub fn remove_dead_stores(
+    instructions: &mut Vec<DecodedInstruction>,
+    analysis: &StackAnalysis,
+) -> bool {
+    let length = instructions.len();
+    let mut index = 0;
+    instructions.retain(|instruction| {
+        index += 1;
+        let i = index - 1;
+        let region = analysis.get_region(i);
+        let Some(slot) = analysis.get_write(i) else {
+            return true;
+        };
+        !(instruction.is_pure()
+            && analysis.is_safe(region)
+            && slot.is_home()
+            && !analysis.is_shared(region, &slot)
+            && analysis.get_overlapping_reads(region, &slot).is_empty())
+    });
+    instructions.len() != length
+}
+
+/// Removes instructions that can't be reached from the entry point e.g. code after a `break`
+/// or functions that are never called
+pub fn remove_unreachable(
+    instructions: &mut Vec<DecodedInstruction>,
+    control_flow: &ControlFlow,
+) -> bool {
+    let length = instructions.len();
+    let mut index = 0;
+    instructions.retain(|_| {
+        index += 1;
+        control_flow.is_reachable(index - 1)
+    });
+    instructions.len() != length
+}
+
+/// Retargets jumps that land on an unconditional jump to that jump's destination, leaving the
+/// intermediate jump to be removed if nothing else reaches it
+pub fn thread_jumps(instructions: &mut [DecodedInstruction]) -> bool {
+    let mut changed = false;
+    for i in 0..instructions.len() {
+        let instruction = &instructions[i];
+        if !(instruction.is_jump() || instruction.is_jump_if_not()) {
+            continue;
+        }
+        let Some(original) = instruction.targets().next() else {
+            continue;
+        };
+
+        //? Jumps that only lead to each other are an infinite loop so are left as they are
+        let mut visited = HashSet::from([i]);
+        let mut target = Some(original);
+        while let Some(index) = target.and_then(|target| resolve(instructions, target)) {
+            if !instructions[index].is_jump() {
+                break;
+            }
+            target = visited
+                .insert(index)
+                .then(|| instructions[index].targets().next().unwrap());
+        }
+
+        if let Some(target) = target.filter(|target| *target != original) {
+            instructions[i].set_target(target);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Removes jumps to the next instruction
+pub fn remove_redundant_jumps(instructions: &mut Vec<DecodedInstruction>) -> bool {
+    let mut changed = false;
+    let mut i = 0;
+    while i < instructions.len() {
+        let instruction = &instructions[i];
+        let redundant = (instruction.is_jump() || instruction.is_jump_if_not())
+            && instruction
+                .targets()
+                .all(|target| resolve(instructions, target).unwrap_or(instructions.len()) == i + 1);
+        if redundant {
+            instructions.remove(i);
+            changed = true;
+        } else {
+            i += 1;
+        }
+    }
+    changed
+}
@@ -0,0 +1,207 @@
+use crate::address::Address;
+use crate::memory::MemoryManager;
+use crate::processing::instructions::copy_3::COPY_INSTRUCTION_CODE;
+use crate::processing::instructions::dump_5::DUMP_INSTRUCTION_CODE;
+use crate::processing::instructions::jump_if_not_9::JUMP_IF_NOT_INSTRUCTION_CODE;
+use crate::processing::instructions::jump_instruction_10::JUMP_INSTRUCTION_CODE;
+use crate::processing::instructions::stack_create_0::STACK_CREATE_INSTRUCTION_CODE;
+use crate::processing::instructions::stack_down_4::STACK_DOWN_INSTRUCTION_CODE;
+use crate::processing::instructions::{
+    debug_bytes, decode_operands, encode_instruction, get_instruction_properties, Effect, Faults,
+    Flow, InstructionCodeType, InstructionProperties, Operand, INSTRUCTION_CODE_LENGTH,
+};
+
+/// Instruction decoded from program memory so that it can be rewritten
+#[derive(Clone, Debug)]
+pub struct DecodedInstruction {
+    /// Position of the instruction in the original program memory. Jumps refer to instructions
+    /// by this position
+    pub origin: usize,
+    pub code: InstructionCodeType,
+    pub operands: Vec<Operand>,
+}
+
+impl DecodedInstruction {
+    /// Creates a copy of `size` bytes from `source` to `destination`
+    pub fn new_copy(origin: usize, source: Address, destination: Address, size: usize) -> Self {
+        Self {
+            origin,
+            code: COPY_INSTRUCTION_CODE,
+            operands: vec![
+                Operand::Size(size),
+                Operand::Read(source, size),
+                Operand::Write(destination, size),
+            ],
+        }
+    }
+
+    /// Creates an unconditional jump to `target` (a position in the original program memory)
+    pub fn new_jump(origin: usize, target: usize) -> Self {
+        Self {
+            origin,
+            code: JUMP_INSTRUCTION_CODE,
+            operands: vec![Operand::Target(target)],
+        }
+    }
+
+    /// Returns the addresses read and the length of the data read from each
+    pub fn reads(&self) -> impl Iterator<Item = (&Address, usize)> {
+        self.operands.iter().filter_map(|operand| match operand {
+            Operand::Read(address, length) => Some((address, *length)),
+            _ => None,
+        })
+    }
+
+    /// Returns the address written to and the length of the data written
+    pub fn write(&self) -> Option<(&Address, usize)> {
+        self.operands.iter().find_map(|operand| match operand {
+            Operand::Write(address, length) => Some((address, *length)),
+            _ => None,
+        })
+    }
+
+    /// Replaces the address of the read operand at `index` (counting only read operands)
+    pub fn set_read(&mut self, index: usize, address: Address) {
+        if let Some(Operand::Read(read, _)) = self
+            .operands
+            .iter_mut()
+            .filter(|operand| matches!(operand, Operand::Read(..)))
+            .nth(index)
+        {
+            *read = address;
+        }
+    }
+
+    pub fn write_mut(&mut self) -> Option<&mut Address> {
+        self.operands.iter_mut().find_map(|operand| match operand {
+            Operand::Write(address, _) => Some(address),
+            _ => None,
+        })
+    }
+
+    /// Returns the positions in the original program memory that this instruction can jump to
+    pub fn targets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.operands.iter().filter_map(|operand| match operand {
+            Operand::Target(target) | Operand::CodePointer(target) => Some(*target),
+            _ => None,
+        })
+    }
+
+    /// Replaces the position of the first jump target
+    pub fn set_target(&mut self, position: usize) {
+        if let Some(Operand::Target(target)) = self
+            .operands
+            .iter_mut()
+            .find(|operand| matches!(operand, Operand::Target(_)))
+        {
+            *target = position;
+        }
+    }
+
+    /// Returns the properties declared for the instruction in `instruction_set!`
+    pub fn properties(&self) -> InstructionProperties {
+        get_instruction_properties(self.code).expect("Decoded instructions have registered codes")
+    }
+
+    /// Returns whether execution can continue to the next instruction
+    pub fn falls_through(&self) -> bool {
+        self.properties().flow != Flow::Jump
+    }
+
+    /// Returns whether the only effect of the instruction is writing its result
+    pub fn is_pure(&self) -> bool {
+        let properties = self.properties();
+        //? Runtime errors must be kept unless the operands are known not to cause one
+        let may_fault = match properties.faults {
+            Faults::Never => false,
+            Faults::OnZero(read) => !matches!(
+                self.reads().nth(read),
+                Some((Address::Immediate(data), _)) if data.iter().any(|b| *b != 0)
+            ),
+            Faults::Maybe => true,
+        };
+        properties.effect == Effect::Pure && !may_fault && !self.has_code_pointer()
+    }
+
+    /// Returns the value copied if the instruction copies an immediate
+    pub fn get_copied_constant(&self) -> Option<&[u8]> {
+        match (self.code, self.reads().next()) {
+            (COPY_INSTRUCTION_CODE, Some((Address::Immediate(value), _))) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn has_code_pointer(&self) -> bool {
+        self.operands
+            .iter()
+            .any(|operand| matches!(operand, Operand::CodePointer(_)))
+    }
+
+    pub fn is_stack_create(&self) -> Option<usize> {
+        match (self.code, self.operands.first()) {
+            (STACK_CREATE_INSTRUCTION_CODE, Some(Operand::StackSize(size))) => Some(*size),
+            _ => None,
+        }
+    }
+
+    pub fn is_stack_down(&self) -> bool {
+        self.code == STACK_DOWN_INSTRUCTION_CODE
+    }
+
+    pub fn is_copy(&self) -> bool {
+        self.code == COPY_INSTRUCTION_CODE
+    }
+
+    pub fn is_jump(&self) -> bool {
+        self.code == JUMP_INSTRUCTION_CODE
+    }
+
+    pub fn is_jump_if_not(&self) -> bool {
+        self.code == JUMP_IF_NOT_INSTRUCTION_CODE
+    }
+
+    pub fn is_dump(&self) -> bool {
+        self.code == DUMP_INSTRUCTION_CODE
+    }
+
+    /// Encodes the instruction, converting positions in the original program memory with `map`.
+    /// Also returns the positions of code pointers relative to the start of the instruction
+    pub fn encode(&self, map: impl Fn(usize) -> usize) -> (Vec<u8>, Vec<usize>) {
+        encode_instruction(self.code, &self.operands, map)
+    }
+}
+
+/// Decodes every instruction in `program_memory`. Fails if any instruction is unknown
+pub fn decode_program(program_memory: &MemoryManager) -> Result<Vec<DecodedInstruction>, String> {
+    decode_range(program_memory, 0, program_memory.memory.len())
+}
+
+/// Decodes the instructions from `start` up to `end` in program memory
+pub fn decode_range(
+    program_memory: &MemoryManager,
+    start: usize,
+    end: usize,
+) -> Result<Vec<DecodedInstruction>, String> {
+    let data = &program_memory.memory[..end];
+    let mut instructions = Vec::new();
+    let mut pointer = start;
+    while pointer < data.len() {
+        let origin = pointer;
+        let code = InstructionCodeType::from_le_bytes(
+            debug_bytes(data, &mut pointer, INSTRUCTION_CODE_LENGTH)?
+                .try_into()
+                .unwrap(),
+        );
+        let operands = decode_operands(code, data, &mut pointer, &program_memory.code_pointers)
+            .ok_or_else(|| {
+            format!("Unsupported instruction [{}] at [{:05}]", code, origin)
+        })??;
+
+        instructions.push(DecodedInstruction {
+            origin,
+            code,
+            operands,
+        });
+    }
+    Ok(instructions)
+}
@@ -0,0 +1,202 @@
+use crate::address::Address;
+use crate::memory::DebugInfo;
+use crate::processing::optimiser::instruction::DecodedInstruction;
+use std::collections::{HashMap, HashSet};
+
+/// Code that runs in a single stack frame - the entry point of a function or `None` for the
+/// top level of the program
+pub type Region = Option<usize>;
+
+/// Range of bytes in a stack frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Slot {
+    /// Number of frames above the frame of the region - `0` for the region's own frame, `1` or
+    /// more for the frames of functions being called
+    pub frame: usize,
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Slot {
+    pub fn overlaps(&self, other: &Slot) -> bool {
+        self.frame == other.frame
+            && self.start < other.start + other.length
+            && other.start < self.start + self.length
+    }
+
+    /// Returns whether the slot is in the region's own frame
+    pub fn is_home(&self) -> bool {
+        self.frame == 0
+    }
+}
+
+/// Resolves every stack address in a program to the frame it refers to. Stack addresses are
+/// relative to the newest frame so the same slot can be referred to by different addresses
+/// e.g. while the arguments of a call are being copied
+pub struct StackAnalysis {
+    regions: Vec<Region>,
+    /// Regions containing stack accesses that couldn't be resolved. Nothing is known about
+    /// the slots in these regions
+    unsafe_regions: HashSet<Region>,
+    reads: Vec<Vec<Option<Slot>>>,
+    writes: Vec<Option<Slot>>,
+    /// Slots of called functions' frames read by their callers e.g. return values. Any
+    /// function could be the callee so these are treated as read in every function
+    callee_reads: Vec<Slot>,
+    /// Slots of called functions' frames written by their callers e.g. arguments
+    callee_writes: Vec<Slot>,
+}
+
+impl StackAnalysis {
+    pub fn new(instructions: &[DecodedInstruction], debug_info: &DebugInfo) -> Self {
+        let mut analysis = Self {
+            regions: Vec::with_capacity(instructions.len()),
+            unsafe_regions: HashSet::new(),
+            reads: Vec::with_capacity(instructions.len()),
+            writes: Vec::with_capacity(instructions.len()),
+            callee_reads: Vec::new(),
+            callee_writes: Vec::new(),
+        };
+
+        //? Sizes of the frames currently on the stack in each region, oldest first. The size of
+        //? a function's own frame isn't known as the function creates it before jumping
+        let mut frames: HashMap<Region, Vec<Option<usize>>> = HashMap::new();
+
+        for instruction in instructions {
+            let region = debug_info
+                .get_function(instruction.origin)
+                .map(|function| function.body.start);
+            let region_frames = frames.entry(region).or_insert_with(|| match region {
+                Some(_) => vec![None],
+                None => Vec::new(),
+            });
+
+            let mut resolved = true;
+            let mut resolve = |address: &Address, length: usize| match address {
+                Address::Immediate(_) | Address::HeapDirect(_) | Address::HeapIndirect(_) => None,
+                Address::StackDirect(address) => {
+                    let slot = resolve_slot(region_frames, *address, length);
+                    resolved &= slot.is_some();
+                    slot
+                }
+                _ => {
+                    resolved = false;
+                    None
+                }
+            };
+
+            let reads: Vec<Option<Slot>> = instruction
+                .reads()
+                .map(|(address, length)| resolve(address, length))
+                .collect();
+            let write = instruction
+                .write()
+                .and_then(|(address, length)| resolve(address, length));
+
+            for slot in reads.iter().flatten().filter(|slot| !slot.is_home()) {
+                analysis.callee_reads.push(Slot { frame: 0, ..*slot });
+            }
+            if let Some(slot) = write.filter(|slot| !slot.is_home()) {
+                analysis.callee_writes.push(Slot { frame: 0, ..slot });
+            }
+
+            if let Some(size) = instruction.is_stack_create() {
+                region_frames.push(Some(size));
+            } else if instruction.is_stack_down() {
+                resolved &= region_frames.pop().is_some_and(|frame| frame.is_some());
+            }
+
+            if !resolved || instruction.is_dump() {
+                analysis.unsafe_regions.insert(region);
+            }
+            analysis.regions.push(region);
+            analysis.reads.push(reads);
+            analysis.writes.push(write);
+        }
+
+        analysis
+    }
+
+    pub fn get_region(&self, index: usize) -> Region {
+        self.regions[index]
+    }
+
+    /// Returns whether every stack access in the region is known
+    pub fn is_safe(&self, region: Region) -> bool {
+        !self.unsafe_regions.contains(&region)
+    }
+
+    /// Returns the slot written to by the instruction at `index`
+    pub fn get_write(&self, index: usize) -> Option<Slot> {
+        self.writes[index]
+    }
+
+    /// Returns the slot read by each read operand of the instruction at `index`
+    pub fn get_reads(&self, index: usize) -> &[Option<Slot>] {
+        &self.reads[index]
+    }
+
+    /// Returns the instructions in `region` that write to part of `slot`
+    pub fn get_overlapping_writes(&self, region: Region, slot: &Slot) -> Vec<usize> {
+        (0..self.writes.len())
+            .filter(|i| self.regions[*i] == region)
+            .filter(|i| self.writes[*i].is_some_and(|write| write.overlaps(slot)))
+            .collect()
+    }
+
+    /// Returns the instructions in `region` that read part of `slot`, along with the index of
+    /// the read operand
+    pub fn get_overlapping_reads(&self, region: Region, slot: &Slot) -> Vec<(usize, usize)> {
+        let mut overlapping = Vec::new();
+        for (i, reads) in self.reads.iter().enumerate() {
+            if self.regions[i] != region {
+                continue;
+            }
+            for (operand, read) in reads.iter().enumerate() {
+                if read.is_some_and(|read| read.overlaps(slot)) {
+                    overlapping.push((i, operand));
+                }
+            }
+        }
+        overlapping
+    }
+
+    /// Returns whether a home slot of `region` may be accessed by code outside of the region
+    /// i.e. by the callers of a function
+    pub fn is_shared(&self, region: Region, slot: &Slot) -> bool {
+        region.is_some()
+            && self
+                .callee_reads
+                .iter()
+                .chain(&self.callee_writes)
+                .any(|shared| shared.overlaps(slot))
+    }
+}
+
+/// Converts an address in the newest frame to a slot, walking down through the frames it
+/// extends past. Returns `None` if the address passes through a frame of unknown size
+fn resolve_slot(frames: &[Option<usize>], mut address: usize, length: usize) -> Option<Slot> {
+    if frames.is_empty() {
+        return None;
+    }
+    for (frame, size) in frames.iter().enumerate().skip(1).rev() {
+        let size = (*size)?;
+        if address < size {
+            //? Accesses spanning multiple frames aren't generated by the compiler
+            if address + length > size {
+                return None;
+            }
+            return Some(Slot {
+                frame,
+                start: address,
+                length,
+            });
+        }
+        address -= size;
+    }
+    Some(Slot {
+        frame: 0,
+        start: address,
+        length,
+    })
+}
@@ -1,11 +1,16 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
 use crate::bx;
-use crate::errors::create_simple_line_error;
+use crate::errors::{Diagnostic, ErrorCode, Note, SourceLocation, Span};
 use crate::file_loading::load_file;
+use crate::processing::std_library::{get_std_file_name, get_std_source, STD_LIBRARY_PREFIX};
 use crate::processing::symbols::{
     get_all_symbol, Keyword, Punctuation, Symbol, CHAR_DELIMITER, LIST_SEPARATOR_CHARACTER,
-    STRING_DELIMITER,
+    RANGE_SEPARATOR, STRING_DELIMITER,
 };
-use crate::util::join_file_name;
+use crate::util::{join_file_name, join_reference_name};
 
 pub const COMMENT_CHARACTER: char = '#';
 pub const OPEN_BRACKET_CHARACTER: char = '(';
@@ -14,28 +19,96 @@ pub const OPEN_INDEXER_CHARACTER: char = '[';
 pub const CLOSE_INDEXER_CHARACTER: char = ']';
 
 /// Takes a line of code and returns an array of symbols
-#[allow(clippy::single_match)]
 pub fn get_symbols_from_line(line: &str) -> Result<Vec<Symbol>, String> {
+    get_symbols_and_spans_from_line(line)
+        .map(|(symbols, _)| symbols)
+        .map_err(|(e, _)| e)
+}
+
+/// Takes a line of code and returns an array of symbols along with the span of each symbol in
+/// the line
+///
+/// Errors include the span of the section that couldn't be processed
+#[allow(clippy::single_match)]
+pub fn get_symbols_and_spans_from_line(
+    line: &str,
+) -> Result<(Vec<Symbol>, Vec<Span>), (String, Span)> {
     fn process_buffer(buffer: &mut String, symbol_line: &mut Vec<Symbol>) -> Result<(), String> {
         if buffer.is_empty() {
             return Ok(());
         }
 
-        let symbol = get_all_symbol(buffer)?;
+        let symbol = match get_all_symbol(buffer) {
+            Ok(symbol) => symbol,
+            //? Range without spaces e.g. `0..10`
+            Err(_) if buffer.contains(RANGE_SEPARATOR) => {
+                let (start, end) = buffer.split_once(RANGE_SEPARATOR).unwrap();
+                let (mut start, mut end) = (start.to_string(), end.to_string());
+                process_buffer(&mut start, symbol_line)?;
+                symbol_line.push(Symbol::Punctuation(Punctuation::Range));
+                process_buffer(&mut end, symbol_line)?;
+                buffer.clear();
+                return Ok(());
+            }
+            //? Field of an indexed value e.g. `h[Point].x`
+            Err(_)
+                if buffer.starts_with('.')
+                    && matches!(symbol_line.last(), Some(Symbol::Indexer(..))) =>
+            {
+                let field = &buffer[1..];
+                let Some(Ok(Symbol::Name(path))) =
+                    (!field.is_empty()).then(|| get_all_symbol(field))
+                else {
+                    return Err(format!("'{}' is not a valid field name", field));
+                };
+                let value = symbol_line.pop().unwrap();
+                symbol_line.push(Symbol::Member(bx!(value), path));
+                buffer.clear();
+                return Ok(());
+            }
+            //? Prefix operator directly before its operand e.g. `-a` or `!flag`
+            Err(e) => {
+                let Some(operator) = buffer
+                    .get(..1)
+                    .and_then(|prefix| get_all_symbol(prefix).ok())
+                    .filter(|s| matches!(s, Symbol::Operator(o) if o.is_prefix()))
+                else {
+                    return Err(e);
+                };
+                let mut rest = buffer[1..].to_string();
+                symbol_line.push(operator);
+                process_buffer(&mut rest, symbol_line)?;
+                buffer.clear();
+                return Ok(());
+            }
+        };
         symbol_line.push(symbol);
         buffer.clear();
         Ok(())
     }
 
+    /// Gives every symbol that doesn't have a span yet the span `start..end`
+    fn fill_spans(symbol_line: &[Symbol], spans: &mut Vec<Span>, start: usize, end: usize) {
+        spans.resize(symbol_line.len(), Span::new(start, end));
+    }
+
     let mut symbol_line = Vec::new();
+    let mut spans = Vec::new();
 
     let mut buffer = String::new();
+    //? Start of the top-level section currently being read
+    let mut section_start = 0;
     let mut in_string: Option<char> = None; // Option<delimiter>
     let mut bracket_depth = 0;
     let mut indexer_depth = 0;
     let mut next_character_escaped = false;
 
-    for c in line.chars() {
+    for (i, c) in line.char_indices() {
+        if buffer.is_empty() && in_string.is_none() && bracket_depth == 0 && indexer_depth == 0 {
+            section_start = i;
+        }
+        let span = Span::new(section_start, i);
+
         //? String handling
         if let Some(delimiter) = in_string {
             if next_character_escaped {
@@ -47,7 +120,12 @@ pub fn get_symbols_from_line(line: &str) -> Result<Vec<Symbol>, String> {
             if delimiter == c {
                 buffer.push(c);
                 in_string = None;
-                process_buffer(&mut buffer, &mut symbol_line)?;
+                //? Strings in brackets are processed with the rest of the bracketed section
+                if bracket_depth == 0 && indexer_depth == 0 {
+                    let span = Span::new(section_start, i + 1);
+                    process_buffer(&mut buffer, &mut symbol_line).map_err(|e| (e, span))?;
+                    fill_spans(&symbol_line, &mut spans, span.start, span.end);
+                }
                 continue;
             }
 
@@ -68,7 +146,8 @@ pub fn get_symbols_from_line(line: &str) -> Result<Vec<Symbol>, String> {
             match c {
                 //? Process buffer, ignore c
                 ' ' => {
-                    process_buffer(&mut buffer, &mut symbol_line)?;
+                    process_buffer(&mut buffer, &mut symbol_line).map_err(|e| (e, span))?;
+                    fill_spans(&symbol_line, &mut spans, span.start, span.end);
                     continue;
                 }
                 //? Process buffer, then process c
@@ -77,20 +156,17 @@ pub fn get_symbols_from_line(line: &str) -> Result<Vec<Symbol>, String> {
                 | OPEN_INDEXER_CHARACTER
                 | CLOSE_INDEXER_CHARACTER
                 | LIST_SEPARATOR_CHARACTER => {
-                    process_buffer(&mut buffer, &mut symbol_line)?;
+                    process_buffer(&mut buffer, &mut symbol_line).map_err(|e| (e, span))?;
+                    fill_spans(&symbol_line, &mut spans, span.start, span.end);
+                    section_start = i;
                 }
                 _ => {}
             };
         }
 
-        //? List separator
-        // if c == LIST_SEPARATOR_CHARACTER {
-        //
-        // }
-
         //? Start bracket
         if c == OPEN_BRACKET_CHARACTER {
-            if bracket_depth != 0 {
+            if bracket_depth != 0 || indexer_depth != 0 {
                 buffer.push(c);
             }
             bracket_depth += 1;
@@ -99,7 +175,7 @@ pub fn get_symbols_from_line(line: &str) -> Result<Vec<Symbol>, String> {
 
         //? Start bracket
         if c == OPEN_INDEXER_CHARACTER {
-            if indexer_depth != 0 {
+            if bracket_depth != 0 || indexer_depth != 0 {
                 buffer.push(c);
             }
             indexer_depth += 1;
@@ -111,16 +187,18 @@ pub fn get_symbols_from_line(line: &str) -> Result<Vec<Symbol>, String> {
             bracket_depth -= 1;
 
             match bracket_depth {
-                0 => {
-                    symbol_line.push(get_bracketed_symbols_type(get_symbols_from_line(
-                        buffer.as_str(),
-                    )?));
+                0 if indexer_depth == 0 => {
+                    let span = Span::new(section_start, i + 1);
+                    let symbols = get_symbols_from_line(buffer.as_str()).map_err(|e| (e, span))?;
+                    symbol_line.push(get_bracketed_symbols_type(symbols));
+                    fill_spans(&symbol_line, &mut spans, span.start, span.end);
                     buffer.clear();
                 }
                 i32::MIN..=-1 => {
-                    return Err(
+                    return Err((
                         "Closing bracket found with no corresponding opening bracket".to_string(),
-                    );
+                        Span::new(i, i + 1),
+                    ));
                 }
                 _ => {
                     buffer.push(c);
@@ -135,23 +213,27 @@ pub fn get_symbols_from_line(line: &str) -> Result<Vec<Symbol>, String> {
             indexer_depth -= 1;
 
             match indexer_depth {
-                0 => {
+                0 if bracket_depth == 0 => {
+                    let span = Span::new(section_start, i + 1);
                     if symbol_line.is_empty() {
-                        return Err("Indexers must be applied to something".to_string());
+                        return Err(("Indexers must be applied to something".to_string(), span));
                     }
 
                     let applied_to = symbol_line.pop().unwrap();
-                    let index = get_symbols_from_line(buffer.as_str())?;
+                    let applied_to_span = spans.pop().unwrap();
+                    let index = get_symbols_from_line(buffer.as_str()).map_err(|e| (e, span))?;
 
                     symbol_line.push(Symbol::Indexer(bx!(applied_to), index));
+                    spans.push(Span::new(applied_to_span.start, span.end));
 
                     buffer.clear();
                 }
                 i32::MIN..=-1 => {
-                    return Err(
+                    return Err((
                         "Closing indexing bracket found with no corresponding opening bracket"
                             .to_string(),
-                    );
+                        Span::new(i, i + 1),
+                    ));
                 }
                 _ => {
                     buffer.push(c);
@@ -164,20 +246,38 @@ pub fn get_symbols_from_line(line: &str) -> Result<Vec<Symbol>, String> {
         buffer.push(c);
     }
 
+    let remaining = Span::of_line(&line[section_start..]);
+    let remaining = Span::new(
+        section_start + remaining.start,
+        section_start + remaining.end,
+    );
+
     if in_string.is_some() {
-        return Err("Unclosed string".to_string());
+        return Err(("Unclosed string".to_string(), remaining));
     }
 
     if bracket_depth != 0 {
-        return Err("Unclosed brackets".to_string());
+        return Err(("Unclosed brackets".to_string(), remaining));
     }
 
     //? Push remaining data
     if !buffer.is_empty() {
-        process_buffer(&mut buffer, &mut symbol_line)?;
+        process_buffer(&mut buffer, &mut symbol_line).map_err(|e| (e, remaining))?;
+        fill_spans(&symbol_line, &mut spans, remaining.start, remaining.end);
     }
 
-    Ok(symbol_line)
+    Ok((symbol_line, spans))
+}
+
+/// Module imported by a line e.g. `import foo as f`
+pub struct Import {
+    /// Name the module is accessed through e.g. `f`
+    pub alias: String,
+    /// Index of the module, in order of first import
+    pub module: usize,
+    /// Index of the line after the module's lines, which directly follow the import line.
+    /// `None` if the module was already imported
+    pub end: Option<usize>,
 }
 
 pub struct Line {
@@ -185,6 +285,12 @@ pub struct Line {
     pub line_index: usize,
     pub indentation: usize,
     pub symbols: Vec<Symbol>,
+    /// Span of each symbol in `source`
+    pub spans: Vec<Span>,
+    /// Original text of the line (including indentation)
+    pub source: String,
+    /// Module imported by the line, if it is an import statement
+    pub import: Option<Import>,
 }
 
 impl Line {
@@ -193,14 +299,46 @@ impl Line {
         line_index: usize,
         indentation: usize,
         symbols: Vec<Symbol>,
+        spans: Vec<Span>,
+        source: String,
     ) -> Line {
         Line {
             file_name_index,
             line_index,
             indentation,
             symbols,
+            spans,
+            source,
+            import: None,
         }
     }
+
+    /// Span covering every symbol in the line
+    pub fn get_span(&self) -> Span {
+        match (self.spans.first(), self.spans.last()) {
+            (Some(first), Some(last)) => Span::new(first.start, last.end),
+            _ => Span::of_line(&self.source),
+        }
+    }
+
+    /// Span of the first top-level symbol referring to `name` e.g. `a.b` or `a.b[1]`
+    pub fn find_name_span(&self, name: &[String]) -> Option<Span> {
+        fn refers_to(symbol: &Symbol, name: &[String]) -> bool {
+            match symbol {
+                Symbol::Name(n) => n == name,
+                Symbol::Indexer(applied_to, _) | Symbol::Member(applied_to, _) => {
+                    refers_to(applied_to, name)
+                }
+                _ => false,
+            }
+        }
+
+        self.symbols
+            .iter()
+            .zip(&self.spans)
+            .find(|(symbol, _)| refers_to(symbol, name))
+            .map(|(_, span)| *span)
+    }
 }
 
 pub struct SymbolData {
@@ -221,37 +359,164 @@ impl SymbolData {
         self.file_names.len() - 1
     }
 
-    pub fn add_line(
-        &mut self,
-        file_name_index: usize,
-        line_index: usize,
-        indentation: usize,
-        line: Vec<Symbol>,
-    ) {
-        self.lines
-            .push(Line::new(file_name_index, line_index, indentation, line))
+    pub fn add_line(&mut self, line: Line) {
+        self.lines.push(line)
     }
 
-    pub fn get_error_path(&self, line_index: usize) -> String {
-        format!(
-            "{} - Line {}",
-            self.file_names[self.lines[line_index].file_name_index],
-            self.lines[line_index].line_index + 1
+    /// Returns the location of `span` in the line at `line_index`
+    pub fn get_location(&self, line_index: usize, span: Span) -> SourceLocation {
+        let line = &self.lines[line_index];
+        SourceLocation::new(
+            self.file_names[line.file_name_index].clone(),
+            line.line_index,
+            line.source.clone(),
+            span,
         )
     }
+
+    /// Returns the location of the whole line at `line_index`
+    pub fn get_line_location(&self, line_index: usize) -> SourceLocation {
+        self.get_location(line_index, self.lines[line_index].get_span())
+    }
 }
 
 /// Takes code as an input
 ///
 /// Returns `Vec<indentation, symbol line>`
-pub fn convert_to_symbols(file_name: String, symbol_data: &mut SymbolData) -> Result<(), String> {
+///
+/// Lines that can't be processed are skipped so that every error in every file is reported
+/// Imported files are searched for relative to the importing file, then in each directory of
+/// `library_path`
+pub fn convert_to_symbols(
+    file_name: String,
+    library_path: &[PathBuf],
+    symbol_data: &mut SymbolData,
+) -> Result<(), Vec<Diagnostic>> {
+    let mut errors = Vec::new();
+    let mut imports = Imports {
+        library_path,
+        in_progress: Vec::new(),
+        modules: HashMap::new(),
+    };
+    convert_file_to_symbols(file_name, symbol_data, &mut imports, &mut errors);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Files imported while converting to symbols
+struct Imports<'a> {
+    /// Directories searched for imported files not found relative to the importing file
+    library_path: &'a [PathBuf],
+    /// Files currently being converted as `(path, file name)`, starting with the main file
+    in_progress: Vec<(String, String)>,
+    /// Index of the module of each imported file, by path
+    modules: HashMap<String, usize>,
+}
+
+/// Path used to recognise a file however it is imported
+fn get_import_path(file_name: &str) -> String {
+    fs::canonicalize(file_name)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| file_name.to_string())
+}
+
+/// Finds the file `import_name` (ending in `why`) imported by `file_name`, searching relative to
+/// the importing file then in the library path. Errors list the locations searched
+///
+/// Names starting with `STD_LIBRARY_PREFIX` refer to the standard library instead
+fn resolve_import(
+    import_name: &[String],
+    file_name: &str,
+    library_path: &[PathBuf],
+) -> Result<String, String> {
+    if import_name[0] == STD_LIBRARY_PREFIX {
+        return get_std_file_name(&import_name[..(import_name.len() - 1)]);
+    }
+
+    let import_name = join_file_name(import_name);
+    let importing_directory = Path::new(file_name).parent().unwrap_or(Path::new(""));
+    let candidates: Vec<PathBuf> = [importing_directory]
+        .into_iter()
+        .chain(library_path.iter().map(PathBuf::as_path))
+        .map(|directory| directory.join(&import_name))
+        .collect();
+
+    match candidates.iter().find(|candidate| candidate.is_file()) {
+        Some(found) => Ok(found.to_string_lossy().into_owned()),
+        None => Err(format!(
+            "File '{}' not found - searched {}",
+            import_name,
+            candidates
+                .iter()
+                .map(|candidate| format!("'{}'", candidate.display()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Reads an import statement formatted `import [file name]` or `import [file name] as [alias]`,
+/// returning the file name (ending in `why`) and alias. The `.why` extension is optional and the
+/// alias defaults to the name of the file e.g. `math` for `import lib.math`
+fn parse_import(symbols: &[Symbol]) -> Result<(Vec<String>, String), String> {
+    let (name, alias) = match symbols {
+        [_, Symbol::Name(name)] => (name, None),
+        [_, Symbol::Name(name), Symbol::Keyword(Keyword::As), Symbol::Name(alias)] => {
+            if alias.len() != 1 {
+                return Err(format!(
+                    "Module alias '{}' cannot contain '.'",
+                    join_reference_name(alias)
+                ));
+            }
+            (name, Some(alias[0].clone()))
+        }
+        _ => {
+            return Err(
+                "Import statements must be formatted import [file name] (as [alias])".to_string(),
+            )
+        }
+    };
+
+    let mut name = name.clone();
+    if name.len() < 2 || name.last().unwrap() != "why" {
+        name.push("why".to_string());
+    }
+    let alias = alias.unwrap_or_else(|| name[name.len() - 2].clone());
+    Ok((name, alias))
+}
+
+fn convert_file_to_symbols(
+    file_name: String,
+    symbol_data: &mut SymbolData,
+    imports: &mut Imports,
+    errors: &mut Vec<Diagnostic>,
+) {
     println!("Reading file '{}'", file_name);
-    let data = load_file(&file_name)?;
+    let data = match get_std_source(&file_name)
+        .map(|source| Ok(source.to_string()))
+        .unwrap_or_else(|| load_file(&file_name))
+    {
+        Ok(data) => data,
+        Err(e) => {
+            errors.push(Diagnostic::new(ErrorCode::Import, e, None));
+            return;
+        }
+    };
 
     println!("Processing file '{}'", file_name);
     let file_name_index = symbol_data.add_file_name(file_name.clone());
+    imports
+        .in_progress
+        .push((get_import_path(&file_name), file_name.clone()));
 
     for (line_index, line) in data.lines().enumerate() {
+        let location =
+            |span: Span| SourceLocation::new(file_name.clone(), line_index, line.to_string(), span);
+
         //? Count indentation
         let mut indentation_count: usize = 0;
         let mut indentation_char_count: usize = 0;
@@ -265,60 +530,121 @@ pub fn convert_to_symbols(file_name: String, symbol_data: &mut SymbolData) -> Re
             }
             indentation_char_count += 1;
         }
-        if indentation_count % 4 != 0 {
-            return create_simple_line_error(
+        if !indentation_count.is_multiple_of(4) {
+            errors.push(Diagnostic::new(
+                ErrorCode::Indentation,
                 "Indentation must be a multiple of 4 spaces or single tabs".to_string(),
-                line_index,
-                &file_name,
-            );
+                Some(location(Span::new(0, indentation_char_count))),
+            ));
+            continue;
         }
 
         //? Get symbols
-        let symbols = match get_symbols_from_line(&line[indentation_char_count..]) {
-            Err(e) => return create_simple_line_error(e, line_index, &file_name),
-            Ok(symbols) => symbols,
+        let offset = |span: Span| {
+            Span::new(
+                span.start + indentation_char_count,
+                span.end + indentation_char_count,
+            )
         };
-
-        if !symbols.is_empty() {
-            let processed = match &symbols[0] {
-                Symbol::Keyword(Keyword::Import) => {
-                    if indentation_count != 0 {
-                        Err("Import statements cannot be indented".to_string())
-                    } else if symbols.len() != 2 {
-                        Err("Import statements must be formatted import [file name]".to_string())
-                    } else {
-                        match &symbols[1] {
-                            Symbol::Name(name) => {
-                                if name.len() < 2 || name.last().unwrap() != "why" {
-                                    Err("File extension must be .why".to_string())
-                                } else {
-                                    let name = join_file_name(name);
-
-                                    convert_to_symbols(name, symbol_data)?;
-                                    Ok(true)
-                                }
-                            }
-                            _ => Err("Import statements must be formatted import [file name]"
-                                .to_string()),
-                        }
-                    }
+        let (symbols, spans) =
+            match get_symbols_and_spans_from_line(&line[indentation_char_count..]) {
+                Err((e, span)) => {
+                    errors.push(Diagnostic::new(
+                        ErrorCode::Syntax,
+                        e,
+                        Some(location(offset(span))),
+                    ));
+                    continue;
                 }
-                _ => Ok(false),
+                Ok((symbols, spans)) => (symbols, spans.into_iter().map(offset).collect()),
             };
+        let mut line = Line::new(
+            file_name_index,
+            line_index,
+            indentation_count / 4,
+            symbols,
+            spans,
+            line.to_string(),
+        );
 
-            match processed {
-                Ok(true) => continue,
-                Ok(false) => {}
-                Err(e) => return create_simple_line_error(e, line_index, &file_name),
-            };
+        if !matches!(line.symbols.first(), Some(Symbol::Keyword(Keyword::Import))) {
+            symbol_data.add_line(line);
+            continue;
         }
 
-        symbol_data.add_line(file_name_index, line_index, indentation_count / 4, symbols);
+        let import = if indentation_count != 0 {
+            Err("Import statements cannot be indented".to_string())
+        } else {
+            parse_import(&line.symbols)
+        };
+        let import = import.and_then(|(import_name, alias)| {
+            Ok((
+                resolve_import(&import_name, &file_name, imports.library_path)?,
+                alias,
+            ))
+        });
+        let (import_name, alias) = match import {
+            Ok(import) => import,
+            Err(e) => {
+                errors.push(Diagnostic::new(
+                    ErrorCode::Import,
+                    e,
+                    Some(location(line.get_span())),
+                ));
+                continue;
+            }
+        };
+
+        let path = get_import_path(&import_name);
+        if let Some(start) = imports.in_progress.iter().position(|(p, _)| *p == path) {
+            let cycle: Vec<&str> = imports.in_progress[start..]
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .chain([import_name.as_str()])
+                .collect();
+            errors.push(Diagnostic::new(
+                ErrorCode::Import,
+                format!("Circular import - {}", cycle.join(" -> ")),
+                Some(location(line.spans[1])),
+            ));
+            continue;
+        }
+
+        //? Files are only included once - later imports refer to the same module
+        if let Some(module) = imports.modules.get(&path) {
+            line.import = Some(Import {
+                alias,
+                module: *module,
+                end: None,
+            });
+            symbol_data.add_line(line);
+            continue;
+        }
+
+        let module = imports.modules.len();
+        imports.modules.insert(path, module);
+        let import_span = line.spans[1];
+        let import_line = symbol_data.lines.len();
+        line.import = Some(Import {
+            alias,
+            module,
+            end: None,
+        });
+        symbol_data.add_line(line);
+
+        let first_error = errors.len();
+        convert_file_to_symbols(import_name, symbol_data, imports, errors);
+        for error in &mut errors[first_error..] {
+            error.notes.push(Note {
+                message: "Imported here".to_string(),
+                location: Some(location(import_span)),
+            });
+        }
+        symbol_data.lines[import_line].import.as_mut().unwrap().end = Some(symbol_data.lines.len());
     }
 
+    imports.in_progress.pop();
     println!("Finished processing '{}'", file_name);
-
-    Ok(())
 }
 
 fn get_bracketed_symbols_type(symbols: Vec<Symbol>) -> Symbol {
@@ -356,3 +682,139 @@ fn get_bracketed_symbols_type(symbols: Vec<Symbol>) -> Symbol {
 
     Symbol::List(list)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processing::symbols::Builtin;
+    use crate::test_util::{compile_files, get_output, run_captured};
+
+    fn get_files_output(files: &[(&str, &str)], library_path: &[&str]) -> String {
+        let memory = compile_files(files, library_path)
+            .unwrap_or_else(|e| panic!("{}", crate::errors::format_diagnostics(&e)));
+        let (result, output) = run_captured(memory, false, &[]);
+        result.unwrap();
+        output
+    }
+
+    fn name(name: &str) -> Symbol {
+        Symbol::Name(vec![name.to_string()])
+    }
+
+    #[test]
+    fn splits_ranges_without_spaces() {
+        for (line, start, end) in [
+            ("for i in start..end", name("start"), name("end")),
+            (
+                "for i in start..10",
+                name("start"),
+                get_all_symbol("10").unwrap(),
+            ),
+            ("for i in 0..end", get_all_symbol("0").unwrap(), name("end")),
+        ] {
+            let symbols = get_symbols_from_line(line).unwrap();
+            assert_eq!(
+                symbols[3..],
+                [start, Symbol::Punctuation(Punctuation::Range), end],
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn lexes_fields_of_indexed_values() {
+        assert_eq!(
+            get_symbols_from_line("print h[Point].a.b").unwrap(),
+            [
+                Symbol::Builtin(Builtin::Print),
+                Symbol::Member(
+                    Box::new(Symbol::Indexer(Box::new(name("h")), vec![name("Point")])),
+                    vec!["a".to_string(), "b".to_string()]
+                )
+            ]
+        );
+        assert!(get_symbols_from_line("print h[Point].").is_err());
+    }
+
+    #[test]
+    fn rejects_empty_name_parts() {
+        assert!(get_symbols_from_line("int x = a.").is_err());
+        assert!(get_symbols_from_line("int x = a...b").is_err());
+    }
+
+    #[test]
+    fn runs_ranges_with_variable_bounds() {
+        assert_eq!(
+            get_output(
+                "int start = 1\nint end = 4\nint total = 0\nfor i in start..end\n    total += i\nprint total\n",
+            ),
+            "6\n"
+        );
+    }
+
+    #[test]
+    fn imports_modules_once_with_aliases() {
+        let files = [
+            (
+                "main.why",
+                "import lib.shapes\nimport lib.user as u\nimport lib.shapes as s\nprint shapes.double (4)\nprint u.quad (3)\nprint s.count\nshapes.Point p = (1, 2)\nprint p.y\n",
+            ),
+            (
+                "lib/shapes.why",
+                "print \"loaded\"\nint count = 2\nclass Point\n    int x\n    int y\nfn double (int v) -> int\n    return v * 2\n",
+            ),
+            (
+                "lib/user.why",
+                "import shapes\nfn quad (int v) -> int\n    return shapes.double (shapes.double (v))\n",
+            ),
+        ];
+        assert_eq!(get_files_output(&files, &[]), "loaded\n8\n12\n2\n2\n");
+    }
+
+    #[test]
+    fn keeps_module_scopes_separate() {
+        let files = [
+            ("main.why", "import other\nprint x\n"),
+            ("other.why", "int x = 1\n"),
+        ];
+        let Err(errors) = compile_files(&files, &[]) else {
+            panic!("Expected compilation to fail");
+        };
+        assert_eq!(errors[0].message, "Reference 'x' not found");
+    }
+
+    #[test]
+    fn rejects_circular_imports() {
+        let files = [
+            ("main.why", "import a\n"),
+            ("a.why", "import b\n"),
+            ("b.why", "import a\n"),
+        ];
+        let Err(errors) = compile_files(&files, &[]) else {
+            panic!("Expected compilation to fail");
+        };
+        let cycle: Vec<&str> = errors[0]
+            .message
+            .trim_start_matches("Circular import - ")
+            .split(" -> ")
+            .collect();
+        assert_eq!(cycle.len(), 3);
+        assert!(cycle[0].ends_with("a.why") && cycle[1].ends_with("b.why"));
+        assert_eq!(cycle[0], cycle[2]);
+    }
+
+    #[test]
+    fn searches_library_path_after_importing_directory() {
+        let files = [
+            (
+                "src/main.why",
+                "import util\nimport extra\nprint util.f ()\nprint extra.f ()\n",
+            ),
+            ("src/util.why", "fn f () -> int\n    return 1\n"),
+            ("shared/util.why", "fn f () -> int\n    return 2\n"),
+            ("shared/extra.why", "fn f () -> int\n    return 3\n"),
+        ];
+        assert_eq!(get_files_output(&files, &["shared"]), "1\n3\n");
+    }
+}
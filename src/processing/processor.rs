@@ -1,24 +1,29 @@
-use crate::errors::create_line_error;
+use std::collections::HashMap;
+
+use crate::errors::{CompileError, Diagnostic, ErrorCode};
 use crate::memory::MemoryManager;
 use crate::processing::blocks::BlockCoordinator;
 use crate::processing::lines::base_block::BaseBlockLine;
 use crate::processing::lines::break_continue::BreakContinueLine;
+use crate::processing::lines::builtin_call::BuiltinCallLine;
 use crate::processing::lines::call::CallLine;
 use crate::processing::lines::class_line::ClassLine;
 use crate::processing::lines::dump::DumpLine;
 use crate::processing::lines::function_line::FunctionLine;
 use crate::processing::lines::if_line::IfLine;
+use crate::processing::lines::loop_line::LoopLine;
 use crate::processing::lines::variable_assignment::VariableAssignmentLine;
 use crate::processing::lines::variable_initialisation::VariableInitialisationLine;
 use crate::processing::lines::view_memory::ViewMemoryLine;
 use crate::processing::lines::while_line::WhileLine;
 use crate::processing::lines::LineHandler;
-use crate::processing::preprocessor::SymbolData;
+use crate::processing::preprocessor::{Import, SymbolData};
+use crate::processing::reference_manager::{Reference, ReferenceStack};
 
 pub enum ProcessingResult {
     Success,
     Unmatched,
-    Failure(String),
+    Failure(CompileError),
 }
 
 impl ProcessingResult {
@@ -40,7 +45,7 @@ impl ProcessingResult {
         matches!(self, Self::Unmatched)
     }
 
-    pub fn get_error(self) -> String {
+    pub fn get_error(self) -> CompileError {
         match self {
             Self::Failure(e) => e,
             _ => panic!("Attempted to get error where there was none!"),
@@ -53,7 +58,7 @@ macro_rules! q {
     ($r: expr) => {
         match $r {
             Ok(v) => v,
-            Err(e) => return $crate::processing::processor::ProcessingResult::Failure(e),
+            Err(e) => return $crate::processing::processor::ProcessingResult::Failure(e.into()),
         }
     };
 }
@@ -64,15 +69,156 @@ macro_rules! process_line {
     };
 }
 
+/// Creates a diagnostic for an error on the line at `line_index`
+///
+/// `code` replaces the error's own code unless it is `ErrorCode::Line`. If the error is about a
+/// name used in the line, the name is underlined instead of the whole line and a note is added
+/// pointing to where it was declared
+fn create_line_error(
+    code: ErrorCode,
+    error: CompileError,
+    line_index: usize,
+    symbol_data: &SymbolData,
+    reference_stack: &ReferenceStack,
+) -> Diagnostic {
+    let line = &symbol_data.lines[line_index];
+    let code = match code {
+        ErrorCode::Line => error.code,
+        code => code,
+    };
+
+    let span = error
+        .name
+        .as_ref()
+        .and_then(|name| line.find_name_span(name))
+        .unwrap_or_else(|| line.get_span());
+
+    let declaration = error.name.as_ref().and_then(|name| {
+        reference_stack
+            .get_declaration_line(&name[0])
+            .filter(|declared_at| *declared_at != line_index)
+            .map(|declared_at| (&name[0], declared_at))
+    });
+
+    let diagnostic = Diagnostic::new(
+        code,
+        error.message.clone(),
+        Some(symbol_data.get_location(line_index, span)),
+    );
+    match declaration {
+        Some((name, declared_at)) => diagnostic.with_note(
+            format!("'{}' declared here", name),
+            Some(symbol_data.get_line_location(declared_at)),
+        ),
+        None => diagnostic,
+    }
+}
+
+/// Exits the blocks left open at the end of an imported module and removes its reference
+/// handler, returning the index of the module in the reference stack
+fn end_module(
+    import: &Import,
+    last_line: usize,
+    symbol_data: &SymbolData,
+    memory: &mut MemoryManager,
+    block_coordinator: &mut BlockCoordinator,
+    errors: &mut Vec<Diagnostic>,
+) -> usize {
+    while block_coordinator.get_indentation() >= 1 {
+        let result = block_coordinator.force_exit_block_handler(memory);
+        if let Err(e) = result {
+            errors.push(create_line_error(
+                ErrorCode::Block,
+                e,
+                last_line,
+                symbol_data,
+                block_coordinator.get_reference_stack(),
+            ));
+        }
+    }
+
+    block_coordinator
+        .get_reference_stack_mut()
+        .remove_module_handler(import.alias.clone())
+}
+
+/// Registers the alias a module was imported as on the line at `line_index`
+fn register_module(
+    import: &Import,
+    module: usize,
+    line_index: usize,
+    symbol_data: &SymbolData,
+    memory: &MemoryManager,
+    block_coordinator: &mut BlockCoordinator,
+) -> Result<(), Diagnostic> {
+    let reference_stack = block_coordinator.get_reference_stack_mut();
+    reference_stack.set_current_line(line_index, memory.get_position());
+    reference_stack
+        .register_reference(Reference::Module(module), vec![import.alias.clone()])
+        .map_err(|e| {
+            create_line_error(
+                ErrorCode::Import,
+                e,
+                line_index,
+                symbol_data,
+                block_coordinator.get_reference_stack(),
+            )
+        })
+}
+
 /// Takes symbol lines as an input and outputs compiled memory
-pub fn process_symbols(symbol_data: SymbolData) -> Result<MemoryManager, String> {
+///
+/// Processing continues after a line fails so that all errors are reported. Lines indented
+/// beneath a failed line are skipped as they would only produce further errors
+pub fn process_symbols(symbol_data: SymbolData) -> Result<MemoryManager, Vec<Diagnostic>> {
     let mut memory = MemoryManager::new();
 
     let mut block_coordinator = BlockCoordinator::new(&mut memory);
 
-    let line_count = symbol_data.lines.len();
+    //? Errors found after the last line (e.g. when exiting blocks) are reported on the last line
+    let last_line = symbol_data.lines.len().saturating_sub(1);
+
+    let mut errors = Vec::new();
+    //? Indentation of the last failed line - lines indented further are in a poisoned block
+    let mut poisoned_indentation: Option<usize> = None;
+
+    //? Imports of the modules being processed as `(line index, import)` (innermost last)
+    let mut modules_in_progress: Vec<(usize, &Import)> = Vec::new();
+    //? Index in the reference stack of each processed module
+    let mut module_indexes: HashMap<usize, usize> = HashMap::new();
+
+    macro_rules! end_modules {
+        ($line_index: expr) => {
+            //? Modules end once all of the lines following their import have been processed
+            while let Some((import_line, import)) =
+                modules_in_progress.pop_if(|(_, import)| import.end == Some($line_index))
+            {
+                let module = end_module(
+                    import,
+                    $line_index - 1,
+                    &symbol_data,
+                    &mut memory,
+                    &mut block_coordinator,
+                    &mut errors,
+                );
+                module_indexes.insert(import.module, module);
+                if let Err(e) = register_module(
+                    import,
+                    module,
+                    import_line,
+                    &symbol_data,
+                    &memory,
+                    &mut block_coordinator,
+                ) {
+                    errors.push(e);
+                }
+            }
+        };
+    }
 
     'line_iterator: for (line_index, line) in symbol_data.lines.iter().enumerate() {
+        end_modules!(line_index);
+
         //? Skip empty lines
         if line.symbols.is_empty() {
             continue;
@@ -81,35 +227,98 @@ pub fn process_symbols(symbol_data: SymbolData) -> Result<MemoryManager, String>
         let indentation = line.indentation;
         let symbol_line = &line.symbols;
 
+        //? Skip the body of a failed line
+        match poisoned_indentation {
+            Some(poisoned) if indentation > poisoned => continue,
+            _ => poisoned_indentation = None,
+        }
+
+        macro_rules! line_error {
+            ($code: expr, $message: expr, $line_index: expr) => {{
+                errors.push(create_line_error(
+                    $code,
+                    $message,
+                    $line_index,
+                    &symbol_data,
+                    block_coordinator.get_reference_stack(),
+                ));
+                poisoned_indentation = Some(indentation.min(block_coordinator.get_indentation()));
+                continue 'line_iterator;
+            }};
+        }
+
         //? Error if indentation is skipped
         if indentation > block_coordinator.get_indentation() {
-            return create_line_error("Indentation to high".to_string(), line_index, &symbol_data);
+            line_error!(
+                ErrorCode::Indentation,
+                "Indentation to high".into(),
+                line_index
+            );
         }
 
-        //? Exit blocks until block indentation matches code indentation
+        //? Exit blocks until block indentation matches code indentation. Blocks are removed even
+        //? if exiting fails so the error belongs to the block, not this line
         while block_coordinator.get_indentation() >= 1
             && indentation < block_coordinator.get_indentation()
         {
-            if block_coordinator.get_indentation() >= 2
+            let result = if block_coordinator.get_indentation() >= 2
                 && indentation <= block_coordinator.get_indentation() - 2
             {
-                let result = block_coordinator.force_exit_block_handler(&mut memory);
-                if let Err(e) = result {
-                    return create_line_error(e, line_index, &symbol_data);
-                }
+                block_coordinator
+                    .force_exit_block_handler(&mut memory)
+                    .map(|_| true)
             } else {
-                let result = block_coordinator.exit_block_handler(&mut memory, symbol_line);
-                if let Err(e) = result {
-                    return create_line_error(e, line_count, &symbol_data);
+                block_coordinator.exit_block_handler(&mut memory, symbol_line)
+            };
+            match result {
+                Err(e) => errors.push(create_line_error(
+                    ErrorCode::Block,
+                    e,
+                    line_index,
+                    &symbol_data,
+                    block_coordinator.get_reference_stack(),
+                )),
+                Ok(false) => continue 'line_iterator,
+                Ok(true) => {}
+            }
+        }
+
+        //? Code generated from here on belongs to this line
+        let offset = memory.get_position();
+        memory
+            .debug_info
+            .add_line(offset, symbol_data.get_line_location(line_index));
+        block_coordinator
+            .get_reference_stack_mut()
+            .set_current_line(line_index, offset);
+
+        //? Process the lines of a newly imported module in their own scope
+        if let Some(import) = &line.import {
+            match import.end {
+                Some(_) => {
+                    block_coordinator
+                        .get_reference_stack_mut()
+                        .add_module_handler();
+                    modules_in_progress.push((line_index, import));
                 }
-                if !result.unwrap() {
-                    continue 'line_iterator;
+                None => {
+                    if let Err(e) = register_module(
+                        import,
+                        module_indexes[&import.module],
+                        line_index,
+                        &symbol_data,
+                        &memory,
+                        &mut block_coordinator,
+                    ) {
+                        errors.push(e);
+                    }
                 }
             }
+            continue;
         }
 
         //? Process line
-        // let r = ProcessingResult::Failure("".to_string());
+        // let r = ProcessingResult::Failure("".to_string().into());
         let r = process_line!(BaseBlockLine, symbol_line, memory, block_coordinator)
             .or_else(|| {
                 process_line!(
@@ -131,35 +340,49 @@ pub fn process_symbols(symbol_data: SymbolData) -> Result<MemoryManager, String>
             })
             .or_else(|| process_line!(IfLine, symbol_line, memory, block_coordinator))
             .or_else(|| process_line!(WhileLine, symbol_line, memory, block_coordinator))
+            .or_else(|| process_line!(LoopLine, symbol_line, memory, block_coordinator))
             .or_else(|| process_line!(BreakContinueLine, symbol_line, memory, block_coordinator))
             .or_else(|| process_line!(FunctionLine, symbol_line, memory, block_coordinator))
             .or_else(|| process_line!(CallLine, symbol_line, memory, block_coordinator))
-            .or_else(|| process_line!(ClassLine, symbol_line, memory, block_coordinator));
+            .or_else(|| process_line!(ClassLine, symbol_line, memory, block_coordinator))
+            .or_else(|| process_line!(BuiltinCallLine, symbol_line, memory, block_coordinator));
 
         //? Handle unmatched / failed line
         if r.is_failure() {
-            return create_line_error(r.get_error(), line_index, &symbol_data);
+            line_error!(ErrorCode::Line, r.get_error(), line_index);
         } else if r.is_unmatched() {
-            return create_line_error(
-                "Line didn't match any known patterns".to_string(),
-                line_index,
-                &symbol_data,
+            line_error!(
+                ErrorCode::UnmatchedLine,
+                "Line didn't match any known patterns".into(),
+                line_index
             );
         }
 
         if let Err(e) = block_coordinator.on_line_processed() {
-            return create_line_error(e, line_index, &symbol_data);
+            line_error!(ErrorCode::Block, e, line_index);
         }
     }
 
+    end_modules!(symbol_data.lines.len());
+
     //? Exit remaining blocks
     while block_coordinator.get_indentation() >= 1 {
         let result = block_coordinator.force_exit_block_handler(&mut memory);
         if let Err(e) = result {
-            return create_line_error(e, line_count, &symbol_data);
+            errors.push(create_line_error(
+                ErrorCode::Block,
+                e,
+                last_line,
+                &symbol_data,
+                block_coordinator.get_reference_stack(),
+            ));
         }
     }
 
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
     block_coordinator.complete(&mut memory);
 
     Ok(memory)
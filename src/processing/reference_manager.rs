@@ -1,39 +1,50 @@
+use crate::errors::{CompileError, CompileResult, ErrorCode};
 pub mod class;
 pub mod function;
 
+use crate::address::Address;
+use crate::memory::{DebugVariable, MemoryManager, PointerSlot};
 use crate::processing::reference_manager::class::ClassReference;
 use crate::processing::reference_manager::function::FunctionReference;
+use crate::processing::symbols::{Symbol, CLASS_SELF_NAME};
 use crate::processing::types::Type;
 use crate::util::join_reference_name;
 
-fn cant_find_reference_error(name: &[String], fail_point: usize) -> String {
-    let mut error_string = String::from("Searching for reference failed at token: ");
-
-    for i in 0..name.len() {
-        if i == fail_point {
-            error_string.push('*');
-        }
-        error_string += name[i].as_str();
-        if i == fail_point {
-            error_string.push('*');
-        }
-
-        if i != name.len() - 1 {
-            error_string.push('.');
-        }
-    }
+fn cant_find_reference_error(name: &[String], fail_point: usize) -> CompileError {
+    let message = if fail_point == 0 {
+        format!("Reference '{}' not found", join_reference_name(name))
+    } else {
+        format!(
+            "Reference '{}' not found - '{}' has no member '{}'",
+            join_reference_name(name),
+            join_reference_name(&name[..fail_point]),
+            name[fail_point]
+        )
+    };
+    CompileError::new(ErrorCode::UnknownReference, message).with_name(name)
+}
 
-    error_string
+/// Where a reference was declared
+#[derive(Clone, Copy, Debug)]
+pub struct Declaration {
+    /// Index of the line in `SymbolData`
+    pub line_index: usize,
+    /// Position in program memory of the code for the line
+    pub offset: usize,
 }
 
 pub enum Reference {
     Variable(Box<dyn Type>),
     Function(FunctionReference),
     Class(ClassReference),
+    /// Imported module - index into the modules of the `ReferenceStack`
+    Module(usize),
 }
 
 pub struct ReferenceHandler {
     pub name: String,
+    /// Where the reference was declared, if known
+    pub declared_at: Option<Declaration>,
     reference: Reference,
     sub_references: Vec<ReferenceHandler>,
 }
@@ -42,6 +53,7 @@ impl ReferenceHandler {
     pub fn new(reference: Reference, name: String) -> ReferenceHandler {
         Self {
             name,
+            declared_at: None,
             reference,
             sub_references: Vec::new(),
         }
@@ -54,10 +66,60 @@ impl ReferenceHandler {
         &mut self.reference
     }
 
-    pub fn add_sub_reference(&mut self, reference: Reference, name: String) -> &ReferenceHandler {
+    /// Creates a reference handler with sub-references for the fields of a variable e.g. `p.x`
+    pub fn new_with_fields(reference: Reference, name: String) -> CompileResult<ReferenceHandler> {
+        let mut handler = ReferenceHandler::new(reference, name);
+        if let Reference::Variable(variable) = &handler.reference {
+            for (field_name, field) in variable.get_fields()? {
+                handler
+                    .sub_references
+                    .push(ReferenceHandler::new_with_fields(
+                        Reference::Variable(field),
+                        field_name,
+                    )?);
+            }
+        }
+        Ok(handler)
+    }
+
+    pub fn add_sub_reference(
+        &mut self,
+        reference: Reference,
+        name: String,
+    ) -> CompileResult<&ReferenceHandler> {
         self.sub_references
-            .push(ReferenceHandler::new(reference, name));
-        self.sub_references.last().unwrap()
+            .push(ReferenceHandler::new_with_fields(reference, name)?);
+        Ok(self.sub_references.last().unwrap())
+    }
+
+    /// Returns this reference and its fields if they are variables, along with their full names
+    /// e.g. `p` and `p.x`
+    pub fn get_variables(&self) -> Vec<(String, &dyn Type)> {
+        let mut variables = Vec::new();
+        if let Reference::Variable(variable) = &self.reference {
+            variables.push((self.name.clone(), variable.as_ref()));
+            for sub_reference in &self.sub_references {
+                for (name, field) in sub_reference.get_variables() {
+                    variables.push((format!("{}.{}", self.name, name), field));
+                }
+            }
+        }
+        variables
+    }
+
+    /// Returns a direct sub-reference e.g. a method of a class
+    pub fn get_sub_reference(&self, name: &str) -> Option<&Reference> {
+        self.sub_references
+            .iter()
+            .find(|r| r.name == name)
+            .map(|r| r.reference())
+    }
+
+    pub fn get_sub_reference_mut(&mut self, name: &str) -> Option<&mut Reference> {
+        self.sub_references
+            .iter_mut()
+            .find(|r| r.name == name)
+            .map(|r| r.reference_mut())
     }
 
     /// Searches for a reference. If the top level fails, returns `Err(None)`. If a lower level fails, returns `Err([error])`
@@ -65,7 +127,7 @@ impl ReferenceHandler {
         &self,
         name: &[String],
         depth: usize,
-    ) -> Result<&Reference, Option<String>> {
+    ) -> Result<&Reference, Option<CompileError>> {
         Ok(self.get_reference_handler(name, depth)?.reference())
     }
 
@@ -74,7 +136,7 @@ impl ReferenceHandler {
         &mut self,
         name: &[String],
         depth: usize,
-    ) -> Result<&mut Reference, Option<String>> {
+    ) -> Result<&mut Reference, Option<CompileError>> {
         Ok(self.get_reference_handler_mut(name, depth)?.reference_mut())
     }
 
@@ -82,7 +144,7 @@ impl ReferenceHandler {
         &self,
         name: &[String],
         depth: usize,
-    ) -> Result<&ReferenceHandler, Option<String>> {
+    ) -> Result<&ReferenceHandler, Option<CompileError>> {
         if name[depth] != self.name {
             return Err(None);
         }
@@ -110,7 +172,7 @@ impl ReferenceHandler {
         &mut self,
         name: &[String],
         depth: usize,
-    ) -> Result<&mut ReferenceHandler, Option<String>> {
+    ) -> Result<&mut ReferenceHandler, Option<CompileError>> {
         if name[depth] != self.name {
             return Err(None);
         }
@@ -140,53 +202,90 @@ impl Reference {
         matches!(self, Reference::Variable(_))
     }
 
-    pub fn get_variable_ref(&self) -> Result<&dyn Type, String> {
+    pub fn get_variable_ref(&self) -> CompileResult<&dyn Type> {
         match &self {
             Reference::Variable(variable) => Ok(variable.as_ref()),
-            _ => Err("Reference is not a variable".to_string()),
+            _ => Err("Reference is not a variable".to_string().into()),
         }
     }
 
-    pub fn get_variable(self) -> Result<Box<dyn Type>, String> {
+    pub fn get_variable(self) -> CompileResult<Box<dyn Type>> {
         match self {
             Reference::Variable(variable) => Ok(variable),
-            _ => Err("Reference is not a variable".to_string()),
+            _ => Err("Reference is not a variable".to_string().into()),
         }
     }
 
-    pub fn clone_variable(&self) -> Result<Reference, String> {
+    pub fn clone_variable(&self) -> CompileResult<Reference> {
         match &self {
             Reference::Variable(t) => Ok(Reference::Variable(t.duplicate())),
-            _ => Err("Reference not a variable".to_string()),
+            _ => Err("Reference not a variable".to_string().into()),
         }
     }
 
-    pub fn get_function_ref(&self) -> Result<&FunctionReference, String> {
+    pub fn get_class_ref(&self) -> CompileResult<&ClassReference> {
+        match &self {
+            Reference::Class(class) => Ok(class),
+            _ => Err("Reference is not a class".to_string().into()),
+        }
+    }
+
+    pub fn get_class_mut(&mut self) -> CompileResult<&mut ClassReference> {
+        match self {
+            Reference::Class(class) => Ok(class),
+            _ => Err("Reference is not a class".to_string().into()),
+        }
+    }
+
+    pub fn get_function_ref(&self) -> CompileResult<&FunctionReference> {
         match &self {
             Reference::Function(function) => Ok(function),
-            _ => Err("Reference is not a variable".to_string()),
+            _ => Err("Reference is not a variable".to_string().into()),
         }
     }
 
-    pub fn get_function_mut(&mut self) -> Result<&mut FunctionReference, String> {
+    pub fn get_function_mut(&mut self) -> CompileResult<&mut FunctionReference> {
         match self {
             Reference::Function(function) => Ok(function),
-            _ => Err("Reference is not a variable".to_string()),
+            _ => Err("Reference is not a variable".to_string().into()),
         }
     }
 
-    pub fn get_function(self) -> Result<FunctionReference, String> {
+    pub fn get_function(self) -> CompileResult<FunctionReference> {
         match self {
             Reference::Function(function) => Ok(function),
-            _ => Err("Reference is not a variable".to_string()),
+            _ => Err("Reference is not a variable".to_string().into()),
         }
     }
 }
 
+/// Top-level references of an imported module
+struct Module {
+    /// Name the module was first imported as - prefixes its variables in the debug info
+    name: String,
+    references: ReferenceManager,
+}
+
+/// Where a reference was found in a `ReferenceStack`
+#[derive(Clone, Copy)]
+enum Location {
+    Layer(usize),
+    /// Top level of a module, reached through an alias in `layer`
+    Module {
+        module: usize,
+        layer: usize,
+    },
+}
+
 #[derive(Default)]
 pub struct ReferenceStack {
     stack: Vec<ReferenceManager>,
     reference_depth_limit: usize,
+    /// Line being processed - recorded as the declaration site of new references
+    current_line: Option<Declaration>,
+    modules: Vec<Module>,
+    /// Layers holding the top level of the modules being processed (innermost last)
+    module_layers: Vec<usize>,
 }
 
 impl ReferenceStack {
@@ -194,9 +293,32 @@ impl ReferenceStack {
         ReferenceStack {
             stack: Vec::new(),
             reference_depth_limit: 0,
+            current_line: None,
+            modules: Vec::new(),
+            module_layers: Vec::new(),
         }
     }
 
+    /// Lowest layer visible to the code being processed
+    fn get_module_floor(&self) -> usize {
+        self.module_layers.last().copied().unwrap_or(0)
+    }
+
+    pub fn set_current_line(&mut self, line_index: usize, offset: usize) {
+        self.current_line = Some(Declaration { line_index, offset });
+    }
+
+    /// Returns the index of the line a top-level reference was declared on, searching up the stack
+    pub fn get_declaration_line(&self, name: &str) -> Option<usize> {
+        self.stack[self.get_module_floor()..]
+            .iter()
+            .rev()
+            .flat_map(|manager| manager.references.iter())
+            .find(|handler| handler.name == name)
+            .and_then(|handler| handler.declared_at)
+            .map(|declaration| declaration.line_index)
+    }
+
     pub fn get_top_stack(&self) -> &Vec<ReferenceHandler> {
         &self.stack.last().unwrap().references
     }
@@ -229,15 +351,15 @@ impl ReferenceStack {
         &mut self,
         reference: Reference,
         name: Vec<String>,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         if name.len() == 1 {
             self.stack
                 .last_mut()
                 .unwrap()
-                .register_reference(reference, name)
+                .register_reference(reference, name, self.current_line)
         } else {
             let handler = self.get_reference_handler_mut(&name[..(name.len() - 1)])?;
-            handler.add_sub_reference(reference, name.into_iter().last().unwrap());
+            handler.add_sub_reference(reference, name.into_iter().last().unwrap())?;
             Ok(())
         }
     }
@@ -248,149 +370,140 @@ impl ReferenceStack {
         reference: Reference,
         name: Vec<String>,
         offset: usize,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         let len = self.stack.len();
-        self.stack[(len - 1) - offset].register_reference(reference, name)
+        self.stack[(len - 1) - offset].register_reference(reference, name, self.current_line)
     }
 
-    /// Searches for a variable going up the reference stack
-    pub fn get_reference(&self, name: &[String]) -> Result<&Reference, String> {
+    /// Finds the reference called `name`, returning where it is along with how many parts of
+    /// `name` were used to reach it. Names starting with a module alias e.g. `f.fn_name` are
+    /// searched for in the module
+    fn locate(&self, name: &[String]) -> CompileResult<(Location, usize)> {
         //? Go up the stack and search for a variable
 
-        let mut i = self.stack.len() - 1;
-        loop {
-            match self.stack[i].get_reference(name) {
-                Ok(Some(r)) => {
-                    if i < self.reference_depth_limit && r.is_variable() {
-                        continue;
-                    }
-                    return Ok(r);
+        let mut first_error = None;
+        //? Module code can't see the references of the file importing it
+        for i in (self.get_module_floor()..self.stack.len()).rev() {
+            if name.len() > 1 {
+                if let Some(module) = self.stack[i].get_module(&name[0]) {
+                    return self.locate_in_module(module, name, 1, i);
                 }
-                Err(e) => return Err(e),
-                Ok(None) => {}
-            }
-            if i == 0 {
-                break;
             }
-            i -= 1;
-        }
-
-        Err(cant_find_reference_error(name, 0))
-    }
 
-    pub fn get_reference_and_offset(&self, name: &[String]) -> Result<(&Reference, usize), String> {
-        //? Go up the stack and search for a variable
-
-        let mut i = self.stack.len() - 1;
-        loop {
-            match self.stack[i].get_reference(name) {
+            match self.stack[i].get_reference_handler(name) {
                 Ok(Some(r)) => {
-                    if i < self.reference_depth_limit && r.is_variable() {
+                    //? Variables outside of the reference depth limit are inaccessible
+                    if i < self.reference_depth_limit && r.reference.is_variable() {
                         continue;
                     }
-                    return Ok((r, self.stack.len() - 1 - i));
+                    return Ok((Location::Layer(i), 0));
+                }
+                //? Keep searching as the name may be shadowed e.g. by a method's `self`
+                Err(e) => {
+                    if first_error.is_none() {
+                        first_error = Some(e);
+                    }
                 }
-                Err(e) => return Err(e),
                 Ok(None) => {}
             }
-            if i == 0 {
-                break;
-            }
-            i -= 1;
         }
 
-        Err(cant_find_reference_error(name, 0))
+        Err(first_error.unwrap_or_else(|| cant_find_reference_error(name, 0)))
     }
 
-    /// Searches for a variable going up the reference stack
-    pub fn get_reference_mut(&mut self, name: &[String]) -> Result<&mut Reference, String> {
-        //? Go up the stack and search for a variable
-
-        let mut i = self.stack.len() - 1;
-        loop {
-            match self.stack[i].get_reference(name) {
-                Ok(Some(r)) => {
-                    if i < self.reference_depth_limit && r.is_variable() {
-                        continue;
-                    }
-                    // TODO
-                    //? Redundant function call to appease borrow checkers
-                    let r = self.stack[i].get_reference_mut(name).unwrap().unwrap();
-                    return Ok(r);
-                }
-                Err(e) => return Err(e),
-                Ok(None) => {}
+    /// Searches for `name[depth..]` in the top level of `module`, which was reached from `layer`
+    fn locate_in_module(
+        &self,
+        module: usize,
+        name: &[String],
+        depth: usize,
+        layer: usize,
+    ) -> CompileResult<(Location, usize)> {
+        let references = &self.modules[module].references;
+        if name.len() - depth > 1 {
+            if let Some(inner) = references.get_module(&name[depth]) {
+                return self.locate_in_module(inner, name, depth + 1, layer);
             }
+        }
 
-            if i == 0 {
-                break;
+        match references.get_reference_handler(&name[depth..])? {
+            //? Module variables live in the top-level stack frame so, like any other variable,
+            //? are inaccessible outside of the reference depth limit
+            Some(r) if layer < self.reference_depth_limit && r.reference.is_variable() => {
+                Err(cant_find_reference_error(name, depth))
             }
-            i -= 1;
+            Some(_) => Ok((Location::Module { module, layer }, depth)),
+            None => Err(cant_find_reference_error(name, depth)),
         }
-
-        Err(cant_find_reference_error(name, 0))
     }
 
-    pub fn get_reference_handler(&self, name: &[String]) -> Result<&ReferenceHandler, String> {
-        //? Go up the stack and search for a variable
+    fn get_manager(&self, location: Location) -> &ReferenceManager {
+        match location {
+            Location::Layer(i) => &self.stack[i],
+            Location::Module { module, .. } => &self.modules[module].references,
+        }
+    }
 
-        let mut i = self.stack.len() - 1;
-        loop {
-            match self.stack[i].get_reference_handler(name) {
-                Ok(Some(r)) => {
-                    if i < self.reference_depth_limit && r.reference.is_variable() {
-                        continue;
-                    }
-                    return Ok(r);
-                }
-                Err(e) => return Err(e),
-                Ok(None) => {}
-            }
-            if i == 0 {
-                break;
-            }
-            i -= 1;
+    fn get_manager_mut(&mut self, location: Location) -> &mut ReferenceManager {
+        match location {
+            Location::Layer(i) => &mut self.stack[i],
+            Location::Module { module, .. } => &mut self.modules[module].references,
         }
+    }
 
-        Err(cant_find_reference_error(name, 0))
+    /// Searches for a variable going up the reference stack
+    pub fn get_reference(&self, name: &[String]) -> CompileResult<&Reference> {
+        let (location, depth) = self.locate(name)?;
+        Ok(self
+            .get_manager(location)
+            .get_reference(&name[depth..])?
+            .unwrap())
+    }
+
+    pub fn get_reference_and_offset(&self, name: &[String]) -> CompileResult<(&Reference, usize)> {
+        let (location, depth) = self.locate(name)?;
+        let layer = match location {
+            Location::Layer(i) => i,
+            Location::Module { layer, .. } => layer,
+        };
+        Ok((
+            self.get_manager(location)
+                .get_reference(&name[depth..])?
+                .unwrap(),
+            self.stack.len() - 1 - layer,
+        ))
+    }
+
+    /// Searches for a variable going up the reference stack
+    pub fn get_reference_mut(&mut self, name: &[String]) -> CompileResult<&mut Reference> {
+        let (location, depth) = self.locate(name)?;
+        Ok(self
+            .get_manager_mut(location)
+            .get_reference_mut(&name[depth..])?
+            .unwrap())
+    }
+
+    pub fn get_reference_handler(&self, name: &[String]) -> CompileResult<&ReferenceHandler> {
+        let (location, depth) = self.locate(name)?;
+        Ok(self
+            .get_manager(location)
+            .get_reference_handler(&name[depth..])?
+            .unwrap())
     }
 
     /// Searches for a variable going up the reference stack
     pub fn get_reference_handler_mut(
         &mut self,
         name: &[String],
-    ) -> Result<&mut ReferenceHandler, String> {
-        //? Go up the stack and search for a variable
-
-        let mut i = self.stack.len() - 1;
-        loop {
-            match self.stack[i].get_reference_handler_mut(name) {
-                Ok(Some(r)) => {
-                    if i < self.reference_depth_limit && r.reference.is_variable() {
-                        continue;
-                    }
-                    // TODO
-                    //? Redundant function call to appease borrow checkers
-                    let r = self.stack[i]
-                        .get_reference_handler_mut(name)
-                        .unwrap()
-                        .unwrap();
-                    return Ok(r);
-                }
-                Err(e) => return Err(e),
-                Ok(None) => {}
-            }
-
-            if i == 0 {
-                break;
-            }
-            i -= 1;
-        }
-
-        Err(cant_find_reference_error(name, 0))
+    ) -> CompileResult<&mut ReferenceHandler> {
+        let (location, depth) = self.locate(name)?;
+        Ok(self
+            .get_manager_mut(location)
+            .get_reference_handler_mut(&name[depth..])?
+            .unwrap())
     }
 
-    // pub fn get_and_remove_reference(&mut self, name: &[String]) -> Result<(Reference, usize), String> {
+    // pub fn get_and_remove_reference(&mut self, name: &[String]) -> CompileResult<(Reference, usize)> {
     //     //? Go up the stack and search for a variable
     //
     //     let mut i = self.stack.len() - 1;
@@ -412,17 +525,164 @@ impl ReferenceStack {
     //     Err(cant_find_reference_error(name, 0))
     // }
 
+    /// Searches for the function called by `name` e.g. `f` or `p.method`, returning it with the
+    /// arguments to call it with. Method calls on an instance have the instance prepended to the
+    /// arguments
+    pub fn get_function_and_arguments(
+        &self,
+        name: &[String],
+        arguments: Vec<Vec<Symbol>>,
+    ) -> CompileResult<(&FunctionReference, Vec<Vec<Symbol>>)> {
+        //? Method call on an instance e.g. `p.move(1)`
+        let mut class_name = None;
+        if name.len() > 1 {
+            if let Ok(Reference::Variable(receiver)) = self.get_reference(&name[..(name.len() - 1)])
+            {
+                class_name = receiver.get_class_name();
+            }
+        }
+
+        let Some(class_name) = class_name else {
+            let function_reference = self.get_reference(name)?.get_function_ref()?;
+            if function_reference.is_method() {
+                return Err("Methods must be called on an instance of their class"
+                    .to_string()
+                    .into());
+            }
+            return Ok((function_reference, arguments));
+        };
+
+        let method_name = name.last().unwrap();
+        let Some(method) = self
+            .get_class_handler(class_name)
+            //? Instances of classes from imported modules can be used outside of the module
+            .or_else(|e| {
+                self.modules
+                    .iter()
+                    .find_map(|module| module.references.get_class_handler(class_name))
+                    .ok_or(e)
+            })?
+            .get_sub_reference(method_name)
+        else {
+            return Err(format!("Class '{}' has no method '{}'", class_name, method_name).into());
+        };
+        let function_reference = method.get_function_ref()?;
+        if !function_reference.is_method() {
+            return Err(format!("'{}' is not a method", method_name).into());
+        }
+
+        let mut method_arguments = Vec::with_capacity(arguments.len() + 1);
+        method_arguments.push(vec![Symbol::Name(Vec::from(&name[..(name.len() - 1)]))]);
+        method_arguments.extend(arguments);
+        Ok((function_reference, method_arguments))
+    }
+
+    /// Searches for the class with the name `name`. This includes the class currently being
+    /// defined, which is registered under `CLASS_SELF_NAME` until its definition ends
+    pub fn get_class_handler(&self, name: &str) -> CompileResult<&ReferenceHandler> {
+        let i = self.get_class_layer(name)?;
+        Ok(self.stack[i].get_class_handler(name).unwrap())
+    }
+
+    pub fn get_class_handler_mut(&mut self, name: &str) -> CompileResult<&mut ReferenceHandler> {
+        let i = self.get_class_layer(name)?;
+        Ok(self.stack[i].get_class_handler_mut(name).unwrap())
+    }
+
+    fn get_class_layer(&self, name: &str) -> CompileResult<usize> {
+        (self.get_module_floor()..self.stack.len())
+            .rev()
+            .find(|i| self.stack[*i].get_class_handler(name).is_some())
+            .ok_or_else(|| {
+                CompileError::new(
+                    ErrorCode::UnknownReference,
+                    format!("Class '{}' not found", name),
+                )
+                .with_name(&[name.to_string()])
+            })
+    }
+
     /// Adds a reference handler (adds a variable scope)
     pub fn add_handler(&mut self) {
         self.stack.push(ReferenceManager::new());
     }
 
-    /// Removes a reference handler (removes a variable scope)
-    pub fn remove_handler(&mut self) {
+    /// Adds a reference handler for the top level of an imported module. References in the
+    /// layers below it are hidden until it is removed with `remove_module_handler`
+    pub fn add_module_handler(&mut self) {
+        self.add_handler();
+        self.module_layers.push(self.stack.len() - 1);
+    }
+
+    /// Removes the reference handler of the module being processed, keeping its references so
+    /// they can be accessed through a `Reference::Module`. Returns the index of the module
+    pub fn remove_module_handler(&mut self, name: String) -> usize {
+        if self.module_layers.pop() != Some(self.stack.len() - 1) {
+            panic!("Attempted to remove a module handler when a module isn't being processed!");
+        }
+        let references = self.stack.pop().unwrap();
+        self.modules.push(Module { name, references });
+        self.modules.len() - 1
+    }
+
+    /// Removes a reference handler (removes a variable scope). The variables in the scope are
+    /// added to the debug info with a scope ending at the current position
+    pub fn remove_handler(&mut self, program_memory: &mut MemoryManager) {
         if self.reference_depth_limit >= self.stack.len() {
             panic!("Number of reference stacks lower than reference depth limit!");
         }
-        self.stack.pop();
+        let removed = self.stack.pop().unwrap();
+        Self::record_variables(&removed, None, program_memory);
+
+        //? Module variables live in the top-level stack frame so stay in scope until it ends
+        if self.stack.is_empty() {
+            for module in &self.modules {
+                Self::record_variables(&module.references, Some(&module.name), program_memory);
+            }
+        }
+    }
+
+    /// Adds the variables in `references` to the debug info and pointer map with a scope ending
+    /// at the current position, prefixing their names with `prefix` if given
+    fn record_variables(
+        references: &ReferenceManager,
+        prefix: Option<&str>,
+        program_memory: &mut MemoryManager,
+    ) {
+        let end = program_memory.get_position();
+        for handler in &references.references {
+            let Some(declared_at) = handler.declared_at else {
+                continue;
+            };
+            let variables = handler.get_variables();
+            //? The first variable is the handler's own - its fields are found from its type
+            if let Some((_, variable)) = variables.first() {
+                let addresses = variable
+                    .get_heap_frame_addresses()
+                    .expect("Variable's heap frame addresses should be known");
+                for address in addresses {
+                    if let Address::StackDirect(offset) = address {
+                        program_memory.pointer_map.add_slot(PointerSlot {
+                            offset,
+                            scope: declared_at.offset..end,
+                        });
+                    }
+                }
+            }
+            for (name, variable) in variables {
+                let name = match prefix {
+                    Some(prefix) => format!("{}.{}", prefix, name),
+                    None => name,
+                };
+                program_memory.debug_info.add_variable(DebugVariable {
+                    name,
+                    type_symbol: variable.get_type_symbol(),
+                    address: variable.get_address().clone(),
+                    size: variable.get_length(),
+                    scope: declared_at.offset..end,
+                });
+            }
+        }
     }
 }
 
@@ -438,26 +698,33 @@ impl ReferenceManager {
         }
     }
 
-    /// Registers a variable
+    /// Registers a variable declared at `declared_at`
     pub fn register_reference(
         &mut self,
         reference: Reference,
         name: Vec<String>,
-    ) -> Result<(), String> {
+        declared_at: Option<Declaration>,
+    ) -> CompileResult<()> {
         if matches!(self.get_reference(&name), Ok(Some(_))) {
-            return Err(format!(
-                "Reference with name '{}' already exists",
-                join_reference_name(&name)
-            ));
+            return Err(CompileError::new(
+                ErrorCode::DuplicateName,
+                format!(
+                    "Reference with name '{}' already exists",
+                    join_reference_name(&name)
+                ),
+            )
+            .with_name(&name));
         }
 
         if name.len() == 1 {
             let name = name.into_iter().next().unwrap();
-            self.references.push(ReferenceHandler::new(reference, name));
+            let mut handler = ReferenceHandler::new_with_fields(reference, name)?;
+            handler.declared_at = declared_at;
+            self.references.push(handler);
         } else {
             let handler = self.get_reference_handler_mut(&name[..(name.len() - 1)])?;
             if let Some(handler) = handler {
-                handler.add_sub_reference(reference, name.into_iter().last().unwrap());
+                handler.add_sub_reference(reference, name.into_iter().last().unwrap())?;
             } else {
                 return Err(cant_find_reference_error(&name, 0));
             }
@@ -466,7 +733,7 @@ impl ReferenceManager {
     }
 
     /// Returns the `Some(variable)` if it exists. If not, returns `None`
-    pub fn get_reference(&self, name: &[String]) -> Result<Option<&Reference>, String> {
+    pub fn get_reference(&self, name: &[String]) -> CompileResult<Option<&Reference>> {
         for reference in &self.references {
             match reference.get_reference(name, 0) {
                 Ok(reference) => return Ok(Some(reference)),
@@ -478,8 +745,34 @@ impl ReferenceManager {
         Ok(None)
     }
 
+    /// Returns the index of the module imported as `name`, if there is one
+    fn get_module(&self, name: &str) -> Option<usize> {
+        self.references.iter().find_map(|r| match r.reference {
+            Reference::Module(module) if r.name == name => Some(module),
+            _ => None,
+        })
+    }
+
+    fn get_class_handler(&self, name: &str) -> Option<&ReferenceHandler> {
+        self.references.iter().find(|r| match &r.reference {
+            Reference::Class(class) => {
+                r.name == name || (r.name == CLASS_SELF_NAME && class.name == name)
+            }
+            _ => false,
+        })
+    }
+
+    fn get_class_handler_mut(&mut self, name: &str) -> Option<&mut ReferenceHandler> {
+        self.references.iter_mut().find(|r| match &r.reference {
+            Reference::Class(class) => {
+                r.name == name || (r.name == CLASS_SELF_NAME && class.name == name)
+            }
+            _ => false,
+        })
+    }
+
     /// Returns the `Some(variable)` if it exists. If not, returns `None`
-    pub fn get_reference_mut(&mut self, name: &[String]) -> Result<Option<&mut Reference>, String> {
+    pub fn get_reference_mut(&mut self, name: &[String]) -> CompileResult<Option<&mut Reference>> {
         for reference in &mut self.references {
             match reference.get_reference_mut(name, 0) {
                 Ok(reference) => return Ok(Some(reference)),
@@ -495,7 +788,7 @@ impl ReferenceManager {
     pub fn get_reference_handler(
         &self,
         name: &[String],
-    ) -> Result<Option<&ReferenceHandler>, String> {
+    ) -> CompileResult<Option<&ReferenceHandler>> {
         for reference in &self.references {
             match reference.get_reference_handler(name, 0) {
                 Ok(reference) => return Ok(Some(reference)),
@@ -511,7 +804,7 @@ impl ReferenceManager {
     pub fn get_reference_handler_mut(
         &mut self,
         name: &[String],
-    ) -> Result<Option<&mut ReferenceHandler>, String> {
+    ) -> CompileResult<Option<&mut ReferenceHandler>> {
         for reference in &mut self.references {
             match reference.get_reference_handler_mut(name, 0) {
                 Ok(reference) => return Ok(Some(reference)),
@@ -527,7 +820,7 @@ impl ReferenceManager {
     //     &mut self,
     //     name: &[String],
     //     disallow_variables: bool,
-    // ) -> Result<Reference, String> {
+    // ) -> CompileResult<Reference> {
     //
     //     for i in 0..self.references.len() {
     //         match reference[i].get_reference(name, 0) {
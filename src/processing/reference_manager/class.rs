@@ -1,74 +1,222 @@
 use crate::address::Address;
+use crate::bx;
+use crate::errors::{CompileError, CompileResult, ErrorCode};
 use crate::memory::MemoryManager;
 use crate::processing::blocks::StackSizes;
 use crate::processing::instructions::copy_3::CopyInstruction;
-use crate::processing::reference_manager::function::FunctionReference;
 use crate::processing::symbols::{Literal, Operator, TypeSymbol};
-use crate::processing::types::Type;
+use crate::processing::types::{Fields, Type};
+use crate::util::warn;
 
+/// A class definition. Instances of the class are copies of the definition with an address
 pub struct ClassReference {
     pub name: String,
-    properties: Vec<(String, TypeSymbol)>,
-    functions: Vec<(String, FunctionReference)>,
+    /// Properties with addresses relative to the start of an instance
+    properties: Fields,
+    layout: StackSizes,
+    address: Option<Address>,
 }
 
-
 impl ClassReference {
     pub fn new_empty(name: String) -> Self {
+        let mut layout = StackSizes::new();
+        layout.add_stack();
         Self {
             name,
             properties: Vec::new(),
-            functions: Vec::new(),
+            layout,
+            address: None,
+        }
+    }
+
+    /// Adds a property to the end of the class layout
+    pub fn add_property(
+        &mut self,
+        name: String,
+        mut property: Box<dyn Type>,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        if self.properties.iter().any(|(n, _)| *n == name) {
+            return Err(CompileError::new(
+                ErrorCode::DuplicateName,
+                format!(
+                    "Property '{}' already exists in class '{}'",
+                    name, self.name
+                ),
+            ));
         }
+
+        property.allocate_variable(&mut self.layout, program_memory)?;
+        self.properties.push((name, property));
+        Ok(())
     }
 
-    pub fn as_type(&self) -> &dyn Type {
-        self as &dyn Type
+    /// Creates an unallocated instance of this class
+    pub fn instantiate(&self) -> Box<dyn Type> {
+        let mut instance = self.duplicate_known();
+        instance.address = None;
+        bx!(instance)
+    }
+
+    fn duplicate_known(&self) -> ClassReference {
+        let mut layout = StackSizes::new();
+        layout.add_stack();
+        layout.increment_stack_size(self.layout.get_stack_size());
+
+        ClassReference {
+            name: self.name.clone(),
+            properties: self
+                .properties
+                .iter()
+                .map(|(name, property)| (name.clone(), property.duplicate()))
+                .collect(),
+            layout,
+            address: self.address.clone(),
+        }
     }
 }
 
 impl Type for ClassReference {
     fn get_type_symbol(&self) -> TypeSymbol {
-        todo!()
+        TypeSymbol::Class
+    }
+
+    fn get_class_name(&self) -> Option<&str> {
+        Some(&self.name)
     }
 
-    fn runtime_copy_from(&self, other: &dyn Type, program_memory: &mut MemoryManager) -> Result<CopyInstruction, String> {
-        todo!()
+    fn allocate_variable(
+        &mut self,
+        stack: &mut StackSizes,
+        _program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        if self.address.is_some() {
+            warn(
+                format!(
+                    "Allocating {:?} when it already has a memory address",
+                    self.get_type_symbol()
+                )
+                .as_str(),
+            )
+        }
+        self.address = Some(Address::StackDirect(
+            stack.increment_stack_size(self.get_length()),
+        ));
+
+        Ok(())
     }
 
-    fn runtime_copy_from_literal(&self, literal: &Literal, program_memory: &mut MemoryManager) -> Result<CopyInstruction, String> {
-        todo!()
+    fn get_fields(&self) -> CompileResult<Fields> {
+        let mut fields = Vec::with_capacity(self.properties.len());
+        for (name, property) in &self.properties {
+            let Address::StackDirect(offset) = property.get_address() else {
+                panic!("Class property allocated outside of the class layout");
+            };
+            let mut field = property.duplicate();
+            *field.get_address_mut() = self.get_address().offset_direct(*offset)?;
+            fields.push((name.clone(), field));
+        }
+        Ok(fields)
     }
 
-    fn get_prefix_operation_result_type(&self, operator: &Operator) -> Vec<TypeSymbol> {
-        todo!()
+    fn runtime_copy_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        if other.get_class_name() != Some(self.name.as_str()) {
+            return Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Copy not implemented from type '{}' to '{}'",
+                    other
+                        .get_class_name()
+                        .unwrap_or(&other.get_type_symbol().to_string()),
+                    self.name
+                ),
+            ));
+        }
+
+        Ok(CopyInstruction::new_alloc(
+            program_memory,
+            other.get_address(),
+            self.get_address(),
+            self.get_length(),
+        ))
     }
 
-    fn get_operation_result_type(&self, operator: &Operator, rhs: &TypeSymbol) -> Vec<TypeSymbol> {
-        todo!()
+    fn runtime_copy_from_literal(
+        &self,
+        literal: &Literal,
+        _program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        Err(CompileError::new(
+            ErrorCode::TypeMismatch,
+            format!(
+                "{} literal cannot be used to initialise '{}' (use a list of properties)",
+                literal, self.name
+            ),
+        ))
     }
 
-    fn operate_prefix(&self, operator: &Operator, destination: &dyn Type, program_memory: &mut MemoryManager, stack_sizes: &mut StackSizes) -> Result<(), String> {
-        todo!()
+    fn get_prefix_operation_result_type(&self, _operator: &Operator) -> Vec<TypeSymbol> {
+        Vec::new()
     }
 
-    fn operate(&self, operator: &Operator, rhs: &dyn Type, destination: &dyn Type, program_memory: &mut MemoryManager, stack_sizes: &mut StackSizes) -> Result<(), String> {
-        todo!()
+    fn get_operation_result_type(
+        &self,
+        _operator: &Operator,
+        _rhs: &TypeSymbol,
+    ) -> Vec<TypeSymbol> {
+        Vec::new()
+    }
+
+    fn operate_prefix(
+        &self,
+        operator: &Operator,
+        _destination: &dyn Type,
+        _program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        Err(format!("Operator {} not supported on '{}'", operator, self.name).into())
+    }
+
+    fn operate(
+        &self,
+        operator: &Operator,
+        rhs: &dyn Type,
+        _destination: &dyn Type,
+        _program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        Err(CompileError::new(
+            ErrorCode::TypeMismatch,
+            format!(
+                "Operator {} not supported between '{}' and {}",
+                operator,
+                self.name,
+                rhs.get_type_symbol()
+            ),
+        ))
     }
 
     fn get_address(&self) -> &Address {
-        todo!()
+        self.address.as_ref().unwrap()
     }
 
     fn get_length(&self) -> usize {
-        todo!()
+        self.layout.get_stack_size()
     }
 
     fn get_address_mut(&mut self) -> &mut Address {
-        todo!()
+        self.address.as_mut().unwrap()
+    }
+
+    fn set_address(&mut self, address: Address) {
+        self.address = Some(address);
     }
 
     fn duplicate(&self) -> Box<dyn Type> {
-        todo!()
+        bx!(self.duplicate_known())
     }
 }
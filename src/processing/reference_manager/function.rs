@@ -1,4 +1,5 @@
-use crate::address::Address;
+use crate::address::{Address, ADDRESS_CODE_LENGTH};
+use crate::errors::CompileResult;
 use crate::memory::MemoryManager;
 use crate::processing::arithmetic::evaluate_arithmetic_to_types;
 use crate::processing::blocks::StackSizes;
@@ -7,54 +8,71 @@ use crate::processing::instructions::jump_instruction_10::JumpInstruction;
 use crate::processing::instructions::stack_create_0::StackCreateInstruction;
 use crate::processing::instructions::stack_down_4::StackDownInstruction;
 use crate::processing::reference_manager::ReferenceStack;
-use crate::processing::symbols::{Literal, Symbol};
+use crate::processing::symbols::{Literal, Symbol, CLASS_SELF_NAME};
 use crate::processing::types::pointer::PointerType;
-use crate::processing::types::Type;
+use crate::processing::types::{Type, TypeFactory};
 use crate::util::must_use_option::MustUseOption;
 use crate::util::warn;
+use std::cell::RefCell;
 
 #[must_use]
 pub struct IncompleteFunctionCall {
     stack_create_instruction: StackCreateInstruction,
     copy_instructions_to_offset: Vec<CopyInstruction>,
+    copy_instructions_to_offset_destination: Vec<CopyInstruction>,
 }
 
 impl IncompleteFunctionCall {
     pub fn new(
         stack_create_instruction: StackCreateInstruction,
         copy_instructions_to_offset: Vec<CopyInstruction>,
+        copy_instructions_to_offset_destination: Vec<CopyInstruction>,
     ) -> Self {
         Self {
             stack_create_instruction,
             copy_instructions_to_offset,
+            copy_instructions_to_offset_destination,
         }
     }
 }
 
+/// The returned value of a call (if requested) and the `IncompleteFunctionCall` if the call is
+/// recursive
+pub type CallResult = (Option<Box<dyn Type>>, MustUseOption<IncompleteFunctionCall>);
+
 pub struct FunctionReference {
     jump_address: usize,
     return_pointer: PointerType,
+    return_value: Option<Box<dyn Type>>,
     parameters: Vec<(String, Box<dyn Type>)>,
     stack_size: Option<usize>,
-    incomplete_function_calls: Vec<IncompleteFunctionCall>,
+    //? Recursive calls can be made while evaluating expressions, where references are immutable
+    incomplete_function_calls: RefCell<Vec<IncompleteFunctionCall>>,
 }
 
 impl FunctionReference {
     pub fn new(
         jump_address: usize,
         return_pointer: PointerType,
+        return_value: Option<Box<dyn Type>>,
         parameters: Vec<(String, Box<dyn Type>)>,
         stack_size: Option<usize>,
     ) -> Self {
         Self {
             jump_address,
             return_pointer,
+            return_value,
             parameters,
             stack_size,
-            incomplete_function_calls: Vec::new(),
+            incomplete_function_calls: RefCell::new(Vec::new()),
         }
     }
 
+    /// Returns whether the function is a method i.e. takes a hidden `self` parameter
+    pub fn is_method(&self) -> bool {
+        matches!(self.parameters.first(), Some((name, _)) if name == CLASS_SELF_NAME)
+    }
+
     /// Finishes the construction of all `IncompleteFunctionCall`s that required the stack size of
     /// the function to work properly
     pub fn set_stack_size_and_complete(
@@ -67,11 +85,11 @@ impl FunctionReference {
     }
 
     fn complete(&mut self, program_memory: &mut MemoryManager) {
-        for to_complete in &mut self.incomplete_function_calls {
+        for mut to_complete in self.incomplete_function_calls.take() {
             to_complete
                 .stack_create_instruction
                 .set_stack_size(self.stack_size.unwrap(), program_memory);
-            for copy_instruction in &to_complete.copy_instructions_to_offset {
+            for copy_instruction in to_complete.copy_instructions_to_offset {
                 let mut address = Address::stack_address_from_bytes(
                     copy_instruction.get_source_address(),
                     &program_memory.memory,
@@ -80,29 +98,46 @@ impl FunctionReference {
                 address.offset_if_stack(self.stack_size.unwrap());
                 copy_instruction.set_source(&address, program_memory);
             }
+            for copy_instruction in to_complete.copy_instructions_to_offset_destination {
+                let mut address = Address::stack_address_from_bytes(
+                    copy_instruction
+                        .get_destination_address(program_memory)
+                        .unwrap(),
+                    &program_memory.memory,
+                )
+                .unwrap();
+                address.offset_if_stack(self.stack_size.unwrap());
+                copy_instruction
+                    .set_destination(&address, program_memory)
+                    .unwrap();
+            }
         }
-
-        self.incomplete_function_calls = Vec::new();
     }
 
-    /// Calls the function. If the call is recursive (i.e. the stack size of the function is not
-    /// known yet), this returns an `IncompleteFunctionCall` that must be handled with the
-    /// `add_incomplete_function_call` method
+    /// Calls the function. If `get_return_value` is set, the returned value is copied into a new
+    /// variable which is returned. If the call is recursive (i.e. the stack size of the function
+    /// is not known yet), this also returns an `IncompleteFunctionCall` that must be handled with
+    /// the `add_incomplete_function_call` method
     pub fn call(
         &self,
-        _return_into: Option<&dyn Type>,
-        arguments: &Vec<Vec<Symbol>>,
+        arguments: &[Vec<Symbol>],
+        get_return_value: bool,
         program_memory: &mut MemoryManager,
         reference_stack: &ReferenceStack,
         stack_sizes: &mut StackSizes,
-    ) -> Result<MustUseOption<IncompleteFunctionCall>, String> {
+    ) -> CompileResult<CallResult> {
+        if get_return_value && self.return_value.is_none() {
+            return Err("Function does not return a value".to_string().into());
+        }
+
         // Check number of arguments
         if arguments.len() != self.parameters.len() {
             return Err(format!(
                 "Expected {} arguments - received {}",
                 self.parameters.len(),
                 arguments.len()
-            ));
+            )
+            .into());
         }
 
         // Evaluate arguments to intermediate type
@@ -128,6 +163,13 @@ impl FunctionReference {
 
         let mut copy_instructions_to_offset = Vec::new();
 
+        //? Receiver of a method call to copy `self` back into
+        let receiver = if self.is_method() {
+            Some(intermediate[0].as_ref().duplicate())
+        } else {
+            None
+        };
+
         // Copy intermediate types into new stack
         for (i, t) in intermediate.into_iter().enumerate() {
             let t = t.as_ref();
@@ -160,26 +202,71 @@ impl FunctionReference {
             &Address::Immediate(Vec::from(program_memory.get_position().to_le_bytes())),
             program_memory,
         );
+        program_memory
+            .add_code_pointer(copy_instruction.get_source_address() + ADDRESS_CODE_LENGTH);
+
+        //? Copy `self` back into the receiver so that changes made by the method persist
+        let mut copy_instructions_to_offset_destination = Vec::new();
+        if let Some(receiver) = receiver {
+            copy_instructions_to_offset_destination.push(self.copy_out_of_function(
+                self.parameters[0].1.as_ref(),
+                receiver,
+                program_memory,
+            )?);
+        }
+
+        //? Copy the return value out of the function's stack before it is removed
+        let mut returned = None;
+        if get_return_value {
+            let return_value = self.return_value.as_ref().unwrap();
+            let mut destination = return_value.duplicate();
+            destination.set_address(Address::StackDirect(
+                stack_sizes.increment_stack_size(return_value.get_length()),
+            ));
+            TypeFactory::record_temporary(destination.as_ref(), program_memory)?;
+            copy_instructions_to_offset_destination.push(self.copy_out_of_function(
+                return_value.as_ref(),
+                destination.duplicate(),
+                program_memory,
+            )?);
+            returned = Some(destination);
+        }
 
         StackDownInstruction::new_alloc(program_memory);
 
         if self.stack_size.is_none() {
-            Ok(MustUseOption::Some(IncompleteFunctionCall::new(
-                stack_create_instruction,
-                copy_instructions_to_offset,
-            )))
+            Ok((
+                returned,
+                MustUseOption::Some(IncompleteFunctionCall::new(
+                    stack_create_instruction,
+                    copy_instructions_to_offset,
+                    copy_instructions_to_offset_destination,
+                )),
+            ))
         } else {
-            Ok(MustUseOption::None)
+            Ok((returned, MustUseOption::None))
         }
     }
 
+    /// Copies a value from the function's stack into a variable in the caller's stack while the
+    /// function's stack still exists
+    fn copy_out_of_function(
+        &self,
+        source: &dyn Type,
+        mut destination: Box<dyn Type>,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        if let Some(stack_size) = self.stack_size {
+            destination.get_address_mut().offset_if_stack(stack_size); // Offset to account for new stack
+        }
+        destination.runtime_copy_from(source, program_memory)
+    }
+
     /// Adds an `IncompleteFunctionCall` to an internal list to be completed when the stack size
     /// of the function is determined
-    pub fn add_incomplete_function_call(
-        &mut self,
-        incomplete_function_call: IncompleteFunctionCall,
-    ) {
+    pub fn add_incomplete_function_call(&self, incomplete_function_call: IncompleteFunctionCall) {
         self.incomplete_function_calls
+            .borrow_mut()
             .push(incomplete_function_call)
     }
 }
@@ -0,0 +1,99 @@
+/// First part of the name of imports from the standard library e.g. `import std.math`
+pub const STD_LIBRARY_PREFIX: &str = "std";
+/// Directory given to the file names of standard library modules e.g. `<std>/math.why`. It
+/// can't be a real directory as it isn't a valid name
+const STD_LIBRARY_DIRECTORY: &str = "<std>";
+
+/// Name and source of each standard library module. The sources are embedded in the executable
+const STD_LIBRARY_MODULES: [(&str, &str); 4] = [
+    ("math", include_str!("../../std/math.why")),
+    ("logic", include_str!("../../std/logic.why")),
+    ("chars", include_str!("../../std/chars.why")),
+    ("string", include_str!("../../std/string.why")),
+];
+
+/// Returns the file name of the standard library module imported as `name` (without an
+/// extension) e.g. `<std>/math.why` for `std.math`
+pub fn get_std_file_name(name: &[String]) -> Result<String, String> {
+    match name {
+        [_, module] if STD_LIBRARY_MODULES.iter().any(|(m, _)| m == module) => {
+            Ok(format!("{}/{}.why", STD_LIBRARY_DIRECTORY, module))
+        }
+        _ => Err(format!(
+            "Standard library module '{}' not found - available modules are {}",
+            name.join("."),
+            STD_LIBRARY_MODULES
+                .iter()
+                .map(|(module, _)| format!("{}.{}", STD_LIBRARY_PREFIX, module))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Returns the source of a standard library module from its file name
+pub fn get_std_source(file_name: &str) -> Option<&'static str> {
+    let module = file_name
+        .strip_prefix(STD_LIBRARY_DIRECTORY)?
+        .strip_prefix('/')?
+        .strip_suffix(".why")?;
+    STD_LIBRARY_MODULES
+        .iter()
+        .find(|(m, _)| *m == module)
+        .map(|(_, source)| *source)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::get_output;
+
+    #[test]
+    fn math_module() {
+        assert_eq!(
+            get_output(
+                "import std.math\nprint math.abs(-4)\nprint math.clamp(12, 0, 10)\nprint math.sign(-3)\nprint math.pow(-2, 3)\nprint math.pow(2, -1)\nprint math.gcd(-48, 18)\nprint math.lcm(4, 6)\nprint math.is_odd(-3)\n"
+            ),
+            "4\n10\n-1\n-8\n0\n6\n12\ntrue\n"
+        );
+    }
+
+    #[test]
+    fn logic_module() {
+        assert_eq!(
+            get_output(
+                "import std.logic\nprint logic.xor(true, true)\nprint logic.xor(true, false)\nprint logic.implies(true, false)\nprint logic.to_int(true)\nprint logic.from_int(0)\n"
+            ),
+            "false\ntrue\nfalse\n1\nfalse\n"
+        );
+    }
+
+    #[test]
+    fn chars_module() {
+        assert_eq!(
+            get_output(
+                "import std.chars\nprint chars.is_alphanumeric('_')\nprint chars.is_space(' ')\nprintc chars.to_upper('q')\nprintc chars.to_lower('Q')\nprintc chars.to_upper('1')\nprint chars.digit_value('7')\nprint chars.from_digit(3)\n"
+            ),
+            "false\ntrue\nQq17\n3\n"
+        );
+    }
+
+    #[test]
+    fn string_module() {
+        assert_eq!(
+            get_output(
+                "import std.string\nstr s = \"hello\"\nprint string.length(s)\nprint string.count(s, 'l')\nprint string.index_of(s, 'o')\nprint string.index_of(s, 'z')\nprint string.is_empty(\"\")\nprint string.parse_int(\"-123x\")\nstring.repeat('x', 0)\nstring.repeat('-', 3)\nprint \"\"\n"
+            ),
+            "5\n2\n4\n-1\ntrue\n-123\n---\n"
+        );
+    }
+
+    #[test]
+    fn string_module_accepts_any_capacity() {
+        assert_eq!(
+            get_output(
+                "import std.string\nstr[64] long = \"a string that is longer than the default capacity\"\nprint string.length(long)\nprint string.index_of(long, 'g')\nstr[2] short = \"ab\"\nprint string.count(short, 'b')\n"
+            ),
+            "49\n7\n1\n"
+        );
+    }
+}
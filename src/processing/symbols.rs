@@ -16,8 +16,8 @@ use literals::LiteralSymbolHandler;
 pub use literals::CHAR_DELIMITER;
 pub use literals::STRING_DELIMITER;
 
-pub use operators::Operator;
 use operators::OperatorSymbolHandler;
+pub use operators::{Operator, CAST_BINDING_POWER, PREFIX_BINDING_POWER};
 
 pub use types::TypeSymbol;
 use types::TypeSymbolHandler;
@@ -31,6 +31,8 @@ use builtins::BuiltinSymbolHandler;
 pub use punctuation::Punctuation;
 pub use punctuation::PunctuationSymbolHandler;
 pub use punctuation::LIST_SEPARATOR_CHARACTER;
+pub use punctuation::RANGE_SEPARATOR;
+pub use punctuation::RETURN_TYPE_SEPARATOR;
 
 pub use keywords::Keyword;
 pub use keywords::KeywordSymbolHandler;
@@ -42,6 +44,7 @@ pub enum Symbol {
     Operator(Operator),
     BracketedSection(Vec<Symbol>),
     Indexer(Box<Symbol>, Vec<Symbol>),
+    Member(Box<Symbol>, Vec<String>), // ? Value, field names e.g. h[Point].x
     List(Vec<Vec<Symbol>>),
     MethodCall(Box<Symbol>, String, Vec<Vec<Symbol>>), // ? Value, method, arguments
     Type(TypeSymbol),
@@ -59,6 +62,15 @@ impl Symbol {
         }
         Ok(name.first().unwrap().clone())
     }
+
+    /// Returns the items of a list, treating a bracketed section as a list of one item e.g. `(a)`
+    pub fn get_list_items(&self) -> Option<Vec<Vec<Symbol>>> {
+        match self {
+            Symbol::List(items) => Some(items.clone()),
+            Symbol::BracketedSection(item) => Some(vec![item.clone()]),
+            _ => None,
+        }
+    }
 }
 
 pub trait SymbolHandler {
@@ -132,7 +144,7 @@ pub fn try_bracketed_into_parameters(bracketed: &Symbol) -> Result<Literal, Stri
 }
 
 //noinspection SpellCheckingInspection
-pub const ALLOWED_CHARS_IN_NAME: &str = "abcdefghijklmnopqrstuvwxyz_";
+pub const ALLOWED_CHARS_IN_NAME: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_";
 pub const NAME_SEPARATOR: char = '.';
 pub const CLASS_SELF_NAME: &str = "self";
 pub const FORBIDDEN_NAMES: [&str; 1] = [CLASS_SELF_NAME];
@@ -162,11 +174,17 @@ impl AllSymbolHandler {
         }
 
         let name: Vec<_> = string.split('.').map(|s| s.to_string()).collect();
-        if name.is_empty() {
+        //? Empty parts e.g. `a..b` or `a.` aren't names - the former is left for the range lexer
+        if name.iter().any(|part| part.is_empty()) {
             return Err(format!("Symbol '{string}' not recognised"));
         }
 
-        for part in &name {
+        for (i, part) in name.iter().enumerate() {
+            //? `self` can only be used to refer to the instance a method is called on e.g. `self.x`
+            if i == 0 && part == CLASS_SELF_NAME {
+                continue;
+            }
+
             for forbidden_name in FORBIDDEN_NAMES {
                 if part == forbidden_name {
                     return Err(format!("Name '{}' is reserved", part));
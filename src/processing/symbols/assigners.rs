@@ -10,6 +10,7 @@ pub enum Assigner {
     SubtractionSetter,
     ProductSetter,
     DivisionSetter,
+    ModuloSetter,
 }
 
 impl Assigner {
@@ -22,6 +23,7 @@ impl Assigner {
             Assigner::SubtractionSetter => Operator::Subtract,
             Assigner::ProductSetter => Operator::Product,
             Assigner::DivisionSetter => Operator::Divide,
+            Assigner::ModuloSetter => Operator::Modulo,
         };
 
         vec![lhs, Symbol::Operator(equivalent), BracketedSection(rhs)]
@@ -38,6 +40,7 @@ impl SymbolHandler for AssignerSymbolHandler {
             "-=" => Some(Symbol::Assigner(Assigner::SubtractionSetter)),
             "*=" => Some(Symbol::Assigner(Assigner::ProductSetter)),
             "/=" => Some(Symbol::Assigner(Assigner::DivisionSetter)),
+            "%=" => Some(Symbol::Assigner(Assigner::ModuloSetter)),
             _ => None,
         })
     }
@@ -1,10 +1,12 @@
 use super::Symbol;
 use super::SymbolHandler;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(PartialEq, Copy, Clone, strum_macros::Display, Debug, strum_macros::EnumIter)]
 pub enum Block {
     While,
     Loop,
+    For,
     If,
     Elif,
     Else,
@@ -20,6 +22,7 @@ impl Block {
         match self {
             Block::While => "while",
             Block::Loop => "loop",
+            Block::For => "for",
             Block::If => "if",
             Block::Elif => "elif",
             Block::Else => "else",
@@ -35,6 +38,7 @@ impl SymbolHandler for BlockSymbolHandler {
         Ok(match string {
             "while" => Some(Symbol::Block(Block::While)),
             "loop" => Some(Symbol::Block(Block::Loop)),
+            "for" => Some(Symbol::Block(Block::For)),
             "if" => Some(Symbol::Block(Block::If)),
             "elif" => Some(Symbol::Block(Block::Elif)),
             "else" => Some(Symbol::Block(Block::Else)),
@@ -6,6 +6,20 @@ pub enum Builtin {
     Print,
     PrintChars,
     Input,
+    Alloc,
+    Free,
+}
+
+impl Builtin {
+    pub fn get_code_representation(&self) -> &str {
+        match self {
+            Builtin::Print => "print",
+            Builtin::PrintChars => "printc",
+            Builtin::Input => "input",
+            Builtin::Alloc => "alloc",
+            Builtin::Free => "free",
+        }
+    }
 }
 
 pub struct BuiltinSymbolHandler {}
@@ -17,6 +31,8 @@ impl SymbolHandler for BuiltinSymbolHandler {
             "print" => Some(Symbol::Builtin(Builtin::Print)),
             "printc" => Some(Symbol::Builtin(Builtin::PrintChars)),
             "input" => Some(Symbol::Builtin(Builtin::Input)),
+            "alloc" => Some(Symbol::Builtin(Builtin::Alloc)),
+            "free" => Some(Symbol::Builtin(Builtin::Free)),
             _ => None,
         })
     }
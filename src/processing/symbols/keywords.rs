@@ -10,6 +10,8 @@ pub enum Keyword {
     ViewMemoryDecimal,
     As,
     Import,
+    Return,
+    In,
 }
 
 pub struct KeywordSymbolHandler {}
@@ -26,6 +28,8 @@ impl Keyword {
             Keyword::ViewMemoryDecimal => "viewmemdec",
             Keyword::As => "as",
             Keyword::Import => "import",
+            Keyword::Return => "return",
+            Keyword::In => "in",
         }
     }
 }
@@ -41,6 +45,8 @@ impl SymbolHandler for KeywordSymbolHandler {
             "viewmemdec" => Some(Symbol::Keyword(Keyword::ViewMemoryDecimal)),
             "as" => Some(Symbol::Keyword(Keyword::As)),
             "import" => Some(Symbol::Keyword(Keyword::Import)),
+            "return" => Some(Symbol::Keyword(Keyword::Return)),
+            "in" => Some(Symbol::Keyword(Keyword::In)),
             _ => None,
         })
     }
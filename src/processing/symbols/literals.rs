@@ -74,23 +74,27 @@ impl SymbolHandler for LiteralSymbolHandler {
                 let formatted_string = format_escape_codes(string[1..string.len() - 1].to_string());
 
                 if first_char == CHAR_DELIMITER {
-                    if formatted_string.len() != 1 {
-                        return Err("Char literals cannot contain multiple chars".to_string());
+                    let mut chars = formatted_string.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Some(Symbol::Literal(Literal::Char(c))),
+                        _ => return Err("Char literals must contain exactly one char".to_string()),
                     }
+                } else {
+                    Some(Symbol::Literal(Literal::String(formatted_string)))
                 }
-                Some(Symbol::Literal(Literal::String(formatted_string)));
+            } else {
+                None
             }
-            None
         };
 
         if result.is_some() {
             return Ok(result);
         }
 
-        return Ok(match string.parse::<i128>() {
+        Ok(match string.parse::<i128>() {
             Ok(ok) => Some(Symbol::Literal(Literal::Int(ok))),
             Err(_) => None,
-        });
+        })
     }
 }
 
@@ -9,6 +9,7 @@ pub enum Operator {
     Subtract,
     Product,
     Divide,
+    Modulo,
     Greater,
     Less,
     GreaterEqual,
@@ -20,6 +21,34 @@ pub enum Operator {
     Not,
 }
 
+/// Binding power of prefix operators - higher than all infix operators and casting
+pub const PREFIX_BINDING_POWER: u8 = 15;
+/// Binding power of casting (`as`) - higher than all infix operators
+pub const CAST_BINDING_POWER: u8 = 13;
+
+impl Operator {
+    /// Returns the left and right binding power of an infix operator. Higher binding powers are
+    /// evaluated first and a higher right binding power makes an operator left-associative
+    pub fn get_infix_binding_power(&self) -> Option<(u8, u8)> {
+        match self {
+            Operator::Or => Some((1, 2)),
+            Operator::And => Some((3, 4)),
+            Operator::Equal | Operator::NotEqual => Some((5, 6)),
+            Operator::Greater | Operator::Less | Operator::GreaterEqual | Operator::LessEqual => {
+                Some((7, 8))
+            }
+            Operator::Add | Operator::Subtract => Some((9, 10)),
+            Operator::Product | Operator::Divide | Operator::Modulo => Some((11, 12)),
+            Operator::Cast(_) | Operator::Not => None,
+        }
+    }
+
+    /// Returns whether the operator can be used as a prefix e.g. `- a` or `! a`
+    pub fn is_prefix(&self) -> bool {
+        matches!(self, Operator::Subtract | Operator::Not)
+    }
+}
+
 pub struct OperatorSymbolHandler {}
 
 impl SymbolHandler for OperatorSymbolHandler {
@@ -29,6 +58,7 @@ impl SymbolHandler for OperatorSymbolHandler {
             "-" => Some(Symbol::Operator(Operator::Subtract)),
             "*" => Some(Symbol::Operator(Operator::Product)),
             "/" => Some(Symbol::Operator(Operator::Divide)),
+            "%" => Some(Symbol::Operator(Operator::Modulo)),
             ">" => Some(Symbol::Operator(Operator::Greater)),
             "<" => Some(Symbol::Operator(Operator::Less)),
             ">=" => Some(Symbol::Operator(Operator::GreaterEqual)),
@@ -4,17 +4,25 @@ use super::SymbolHandler;
 #[derive(PartialEq, Copy, Clone, strum_macros::Display, Debug)]
 pub enum Punctuation {
     ListSeparator,
+    ReturnType,
+    Range,
 }
 
 pub struct PunctuationSymbolHandler {}
 
 pub const LIST_SEPARATOR_CHARACTER: char = ',';
+pub const RETURN_TYPE_SEPARATOR: &str = "->";
+pub const RANGE_SEPARATOR: &str = "..";
 
 impl SymbolHandler for PunctuationSymbolHandler {
     fn get_symbol(string: &str) -> Result<Option<Symbol>, String> {
         Ok(
             if string.len() == 1 && string.starts_with(LIST_SEPARATOR_CHARACTER) {
                 Some(Symbol::Punctuation(Punctuation::ListSeparator))
+            } else if string == RETURN_TYPE_SEPARATOR {
+                Some(Symbol::Punctuation(Punctuation::ReturnType))
+            } else if string == RANGE_SEPARATOR {
+                Some(Symbol::Punctuation(Punctuation::Range))
             } else {
                 None
             },
@@ -5,9 +5,12 @@ pub enum TypeSymbol {
     Integer,
     Boolean,
     Character,
+    String,
+    Class,
     // Function,
     Pointer,
     // Temporary(String)
+    Array,
 }
 
 impl TypeSymbol {
@@ -16,7 +19,10 @@ impl TypeSymbol {
             TypeSymbol::Integer => "int",
             TypeSymbol::Boolean => "bool",
             TypeSymbol::Character => "char",
+            TypeSymbol::String => "str",
+            TypeSymbol::Class => "class",
             TypeSymbol::Pointer => "ptr",
+            TypeSymbol::Array => "array",
             // TypeSymbol::Temporary(type_name) => type_name
         }
     }
@@ -31,6 +37,7 @@ impl TypeSymbolHandler {
             "int" => Some(TypeSymbol::Integer),
             "bool" => Some(TypeSymbol::Boolean),
             "char" => Some(TypeSymbol::Character),
+            "str" => Some(TypeSymbol::String),
             "ptr" => Some(TypeSymbol::Pointer),
             _ => None,
         }
@@ -39,6 +46,6 @@ impl TypeSymbolHandler {
 
 impl SymbolHandler for TypeSymbolHandler {
     fn get_symbol(string: &str) -> Result<Option<Symbol>, String> {
-        Ok(TypeSymbolHandler::get_raw_symbol(string).and_then(|s| Some(Symbol::Type(s))))
+        Ok(TypeSymbolHandler::get_raw_symbol(string).map(Symbol::Type))
     }
 }
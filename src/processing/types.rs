@@ -1,3 +1,4 @@
+use crate::errors::CompileResult;
 use crate::processing::symbols::{Literal, Operator, Symbol, TypeSymbol};
 
 use self::boolean::BoolWrapper;
@@ -8,10 +9,22 @@ use crate::bx;
 use crate::memory::MemoryManager;
 use crate::processing::blocks::StackSizes;
 use crate::processing::instructions::copy_3::CopyInstruction;
+use crate::processing::reference_manager::ReferenceStack;
+use crate::processing::types::array::ArrayType;
+use crate::processing::types::character::CharWrapper;
+use crate::processing::types::integer::IntegerWrapper;
 use crate::processing::types::pointer::PointerWrapper;
+use crate::processing::types::string::{StringType, StringWrapper};
 
+pub mod array;
 pub mod boolean;
+pub mod character;
+pub mod integer;
 pub mod pointer;
+pub mod string;
+
+/// Field holding the number of items in an array or chars in a string
+pub const LENGTH_FIELD: &str = "length";
 
 pub trait UninstantiatedType {
     fn instantiate(&self) -> Box<dyn Type>;
@@ -19,6 +32,9 @@ pub trait UninstantiatedType {
     fn get_type_symbol(&self) -> TypeSymbol;
 }
 
+/// Named values contained in a type e.g. the properties of a class instance
+pub type Fields = Vec<(String, Box<dyn Type>)>;
+
 pub trait Type {
     fn get_type_symbol(&self) -> TypeSymbol;
 
@@ -26,31 +42,101 @@ pub trait Type {
         &mut self,
         _stack: &mut StackSizes,
         _program_memory: &mut MemoryManager,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         Err(format!(
             "{:?} cannot be allocated as a variable",
             self.get_type_symbol()
-        ))
+        )
+        .into())
     }
 
-    fn get_constant(&self, _literal: &Literal) -> Result<Address, String> {
+    fn get_constant(&self, _literal: &Literal) -> CompileResult<Address> {
         Err(format!(
             "{:?} cannot be created as a constant",
             self.get_type_symbol()
-        ))
+        )
+        .into())
+    }
+
+    /// Name of the class this is an instance of
+    fn get_class_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Item type and length of this value if it is an array e.g. `int[10]`, or `int[]` if it
+    /// is heap-backed
+    fn get_array_type(&self) -> Option<String> {
+        None
+    }
+
+    /// Properties of this value with their addresses e.g. the fields of a class instance
+    fn get_fields(&self) -> CompileResult<Fields> {
+        Ok(Vec::new())
+    }
+
+    /// Values set in order when initialising this from a list e.g. `(1, 2)`. Defaults to the
+    /// fields
+    fn get_list_items(&self) -> CompileResult<Vec<Box<dyn Type>>> {
+        Ok(self
+            .get_fields()?
+            .into_iter()
+            .map(|(_, field)| field)
+            .collect())
+    }
+
+    /// Whether the start of this value holds the id of a heap frame e.g. a `ptr`
+    fn holds_heap_frame(&self) -> bool {
+        self.get_type_symbol() == TypeSymbol::Pointer
+    }
+
+    /// Addresses of the parts of this value holding heap frame ids e.g. each item of a `ptr[4]`
+    /// or the `ptr` fields of a class instance
+    fn get_heap_frame_addresses(&self) -> CompileResult<Vec<Address>> {
+        if self.holds_heap_frame() {
+            return Ok(vec![self.get_address().clone()]);
+        }
+        let mut addresses = Vec::new();
+        for item in self.get_list_items()? {
+            addresses.extend(item.get_heap_frame_addresses()?);
+        }
+        Ok(addresses)
+    }
+
+    /// Allocates a heap frame for `count` (an `int`) items e.g. for a heap-backed array
+    fn runtime_heap_alloc(
+        &self,
+        _count: &dyn Type,
+        _program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        Err(format!(
+            "{} cannot be allocated with a number of items",
+            self.get_type_symbol()
+        )
+        .into())
     }
 
     fn runtime_copy_from(
         &self,
         other: &dyn Type,
         program_memory: &mut MemoryManager,
-    ) -> Result<CopyInstruction, String>;
+    ) -> CompileResult<CopyInstruction>;
+
+    /// Converts `other` into this type (used by `as`). Defaults to a plain copy
+    fn runtime_cast_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        self.runtime_copy_from(other, program_memory)?;
+        Ok(())
+    }
 
     fn runtime_copy_from_literal(
         &self,
         literal: &Literal,
         program_memory: &mut MemoryManager,
-    ) -> Result<CopyInstruction, String>;
+    ) -> CompileResult<CopyInstruction>;
 
     fn get_prefix_operation_result_type(&self, operator: &Operator) -> Vec<TypeSymbol>;
 
@@ -62,7 +148,7 @@ pub trait Type {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         stack_sizes: &mut StackSizes,
-    ) -> Result<(), String>;
+    ) -> CompileResult<()>;
 
     fn operate(
         &self,
@@ -71,7 +157,17 @@ pub trait Type {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         stack_sizes: &mut StackSizes,
-    ) -> Result<(), String>;
+    ) -> CompileResult<()>;
+
+    /// Returns the item at `index` e.g. a `char` in a `str`
+    fn get_indexed(
+        &self,
+        _index: &dyn Type,
+        _program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<Box<dyn Type>> {
+        Err(format!("{} cannot be indexed", self.get_type_symbol()).into())
+    }
 
     fn get_address(&self) -> &Address;
 
@@ -79,18 +175,21 @@ pub trait Type {
 
     fn get_address_mut(&mut self) -> &mut Address;
 
+    fn set_address(&mut self, address: Address);
+
     fn run_method(
         &self,
         method_name: &String,
         _arguments: &[Vec<Symbol>],
         _stack: &mut StackSizes,
         _program_memory: &mut MemoryManager,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         Err(format!(
             "'{}' not implemented for {:?}",
             method_name,
             self.get_type_symbol()
-        ))
+        )
+        .into())
     }
 
     fn duplicate(&self) -> Box<dyn Type>;
@@ -108,7 +207,7 @@ pub trait Operation<LHS> {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         stack_sizes: &mut StackSizes,
-    ) -> Result<(), String>;
+    ) -> CompileResult<()>;
 }
 
 pub trait PrefixOperation<LHS> {
@@ -122,7 +221,7 @@ pub trait PrefixOperation<LHS> {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         stack_sizes: &mut StackSizes,
-    ) -> Result<(), String>;
+    ) -> CompileResult<()>;
 }
 
 // TODO: Refine
@@ -133,38 +232,103 @@ pub struct TypeFactory {
 impl TypeFactory {
     pub fn get() -> Self {
         Self {
-            uninstantiated_types: vec![bx!(BoolWrapper {}), bx!(PointerWrapper {})],
+            uninstantiated_types: vec![
+                bx!(BoolWrapper {}),
+                bx!(CharWrapper {}),
+                bx!(IntegerWrapper {}),
+                bx!(PointerWrapper {}),
+                bx!(StringWrapper {}),
+            ],
         }
     }
 
-    pub fn get_unallocated_type(new_type: &TypeSymbol) -> Result<Box<dyn Type>, String> {
+    pub fn get_unallocated_type(new_type: &TypeSymbol) -> CompileResult<Box<dyn Type>> {
         let factory = Self::get();
         let Some(wrapper) = factory
             .uninstantiated_types
             .iter()
             .find(|t| t.get_type_symbol() == *new_type)
         else {
-            return Err(format!("Type {:?} cannot be instantiated", new_type));
+            return Err(format!("Type {:?} cannot be instantiated", new_type).into());
         };
 
-        return Ok(wrapper.instantiate());
+        Ok(wrapper.instantiate())
+    }
+
+    /// Gets a type with an explicit size e.g. `str[16]` or an array such as `int[10]`
+    pub fn get_unallocated_sized_type(
+        new_type: &TypeSymbol,
+        size: usize,
+    ) -> CompileResult<Box<dyn Type>> {
+        match new_type {
+            TypeSymbol::String => Ok(bx!(StringType::with_capacity(size))),
+            _ => Ok(bx!(ArrayType::new(
+                Self::get_unallocated_type(new_type)?,
+                Some(size)
+            )?)),
+        }
+    }
+
+    /// Gets an unallocated instance of the type described by a symbol e.g. `int`, `str[16]`,
+    /// `int[10]`, `int[]` or the name of a class
+    pub fn get_unallocated_type_from_symbol(
+        symbol: &Symbol,
+        reference_stack: &ReferenceStack,
+    ) -> CompileResult<Box<dyn Type>> {
+        match symbol {
+            Symbol::Type(type_symbol) => Self::get_unallocated_type(type_symbol),
+            Symbol::Indexer(sized, size) => match (sized.as_ref(), size.as_slice()) {
+                (sized, [Symbol::Literal(Literal::Int(size))]) => {
+                    let Ok(size) = (*size).try_into() else {
+                        return Err(format!("Invalid size ({})", size).into());
+                    };
+                    match sized {
+                        Symbol::Type(type_symbol) => {
+                            Self::get_unallocated_sized_type(type_symbol, size)
+                        }
+                        item => Ok(bx!(ArrayType::new(
+                            Self::get_unallocated_type_from_symbol(item, reference_stack)?,
+                            Some(size)
+                        )?)),
+                    }
+                }
+                //? Heap-backed array e.g. `int[]`
+                (item, []) => Ok(bx!(ArrayType::new(
+                    Self::get_unallocated_type_from_symbol(item, reference_stack)?,
+                    None
+                )?)),
+                _ => Err("Sized types must be formatted [Type][[Size]]"
+                    .to_string()
+                    .into()),
+            },
+            Symbol::Name(class_name) if class_name.len() == 1 => Ok(reference_stack
+                .get_class_handler(&class_name[0])?
+                .reference()
+                .get_class_ref()?
+                .instantiate()),
+            //? Class from an imported module e.g. `f.Point`
+            Symbol::Name(class_name) => Ok(reference_stack
+                .get_reference(class_name)?
+                .get_class_ref()?
+                .instantiate()),
+            _ => Err(format!("Type expected, recieved {}", symbol).into()),
+        }
     }
 
     pub fn get_default_type_for_literal(
         literal: &Literal,
         prefered_type: Option<&TypeSymbol>,
-    ) -> Result<TypeSymbol, String> {
+    ) -> CompileResult<TypeSymbol> {
         match literal {
             Literal::Bool(_) => Ok(TypeSymbol::Boolean),
+            Literal::Char(_) => Ok(TypeSymbol::Character),
+            Literal::String(_) => Ok(TypeSymbol::String),
             Literal::Int(_) => Ok(match prefered_type {
                 None => TypeSymbol::Integer,
                 Some(TypeSymbol::Pointer) => TypeSymbol::Pointer,
                 _ => TypeSymbol::Integer,
             }),
-            _ => Err(format!(
-                "{} does not have a default type (use as syntax)",
-                literal
-            )),
+            _ => Err(format!("{} does not have a default type (use as syntax)", literal).into()),
         }
     }
 
@@ -173,13 +337,41 @@ impl TypeFactory {
         stack: &mut StackSizes,
         program_memory: &mut MemoryManager,
         prefered_type: Option<&TypeSymbol>,
-    ) -> Result<Box<dyn Type>, String> {
+    ) -> CompileResult<Box<dyn Type>> {
         let type_symbol = Self::get_default_type_for_literal(literal, prefered_type)?;
-        let mut t = Self::get_unallocated_type(&type_symbol)?;
-        t.allocate_variable(stack, program_memory)?;
+        let mut t = match literal {
+            //? Strings only need to be as long as the literal
+            Literal::String(value) => {
+                Self::get_unallocated_sized_type(&type_symbol, value.chars().count())?
+            }
+            _ => Self::get_unallocated_type(&type_symbol)?,
+        };
+        Self::allocate_temporary(t.as_mut(), stack, program_memory)?;
         t.runtime_copy_from_literal(literal, program_memory)?;
         Ok(t)
     }
+
+    /// Allocates `t` as a temporary that isn't bound to a variable e.g. the result of an operation
+    pub fn allocate_temporary(
+        t: &mut dyn Type,
+        stack: &mut StackSizes,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        t.allocate_variable(stack, program_memory)?;
+        Self::record_temporary(t, program_memory)
+    }
+
+    /// Adds the parts of an allocated temporary holding heap frames to the pointer map so that
+    /// the garbage collector doesn't free them while the temporary is still being used
+    pub fn record_temporary(t: &dyn Type, program_memory: &mut MemoryManager) -> CompileResult<()> {
+        let position = program_memory.get_position();
+        for address in t.get_heap_frame_addresses()? {
+            if let Address::StackDirect(offset) = address {
+                program_memory.pointer_map.add_temporary(offset, position);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for TypeFactory {
@@ -0,0 +1,372 @@
+use crate::address::{Address, CloneableBox};
+use crate::bx;
+use crate::errors::{CompileError, CompileResult, ErrorCode};
+use crate::memory::MemoryManager;
+use crate::processing::blocks::StackSizes;
+use crate::processing::instructions::copy_3::CopyInstruction;
+use crate::processing::instructions::heap_alloc_2::HeapAllocInstruction;
+use crate::processing::instructions::index_check_26::IndexCheckInstruction;
+use crate::processing::instructions::multiply_18::MultiplyInstruction;
+use crate::processing::symbols::{Builtin, Literal, Operator, TypeSymbol};
+use crate::processing::types::integer::{IntegerType, INTEGER_SIZE};
+use crate::processing::types::{Fields, Type, LENGTH_FIELD};
+use crate::util::{warn, USIZE_BYTES};
+
+/// Array of `int`, `bool`, `char` or `ptr` items
+///
+/// Fixed length arrays e.g. `int[10]` store their items directly. Heap-backed arrays e.g.
+/// `int[]` are stored as `[frame id (usize)][length (int)]` with their items in a heap frame
+/// allocated at runtime with `alloc`
+pub struct ArrayType {
+    /// Unallocated instance of the type of the items
+    item: Box<dyn Type>,
+    /// Number of items, `None` if heap-backed
+    length: Option<usize>,
+    address: Option<Address>,
+}
+
+impl ArrayType {
+    pub fn new(item: Box<dyn Type>, length: Option<usize>) -> CompileResult<Self> {
+        //? Indexed addresses read the whole item so items can't have fields or be indexed
+        if !matches!(
+            item.get_type_symbol(),
+            TypeSymbol::Integer | TypeSymbol::Boolean | TypeSymbol::Character | TypeSymbol::Pointer
+        ) {
+            return Err(format!(
+                "Arrays can only hold {}, {}, {} or {} items",
+                TypeSymbol::Integer.get_code_representation(),
+                TypeSymbol::Boolean.get_code_representation(),
+                TypeSymbol::Character.get_code_representation(),
+                TypeSymbol::Pointer.get_code_representation()
+            )
+            .into());
+        }
+        if length == Some(0) {
+            return Err("Arrays must have at least one item".to_string().into());
+        }
+
+        Ok(Self {
+            item,
+            length,
+            address: None,
+        })
+    }
+
+    /// Address of the `int` number of items. Immediate for fixed length arrays
+    fn get_length_address(&self) -> CompileResult<Address> {
+        match self.length {
+            Some(length) => Ok(Address::Immediate(Vec::from((length as i64).to_le_bytes()))),
+            None => Ok(self.get_address().offset_direct(USIZE_BYTES)?),
+        }
+    }
+
+    /// Creates an item of this array with the given address
+    fn create_item(&self, address: Address) -> Box<dyn Type> {
+        let mut item = self.item.duplicate();
+        item.set_address(address);
+        item
+    }
+}
+
+impl Type for ArrayType {
+    fn get_type_symbol(&self) -> TypeSymbol {
+        TypeSymbol::Array
+    }
+
+    fn allocate_variable(
+        &mut self,
+        stack: &mut StackSizes,
+        _program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        if self.address.is_some() {
+            warn(
+                format!(
+                    "Allocating {:?} when it already has a memory address",
+                    self.get_type_symbol()
+                )
+                .as_str(),
+            )
+        }
+        self.address = Some(Address::StackDirect(
+            stack.increment_stack_size(self.get_length()),
+        ));
+
+        Ok(())
+    }
+
+    fn get_array_type(&self) -> Option<String> {
+        Some(format!(
+            "{}[{}]",
+            self.item.get_type_symbol().get_code_representation(),
+            self.length.map(|l| l.to_string()).unwrap_or_default()
+        ))
+    }
+
+    fn get_fields(&self) -> CompileResult<Fields> {
+        let mut length = IntegerType::new();
+        length.set_address(self.get_length_address()?);
+        Ok(vec![(LENGTH_FIELD.to_string(), bx!(length))])
+    }
+
+    fn get_list_items(&self) -> CompileResult<Vec<Box<dyn Type>>> {
+        let Some(length) = self.length else {
+            return Err(format!(
+                "Heap-backed arrays cannot be initialised from a list (use {})",
+                Builtin::Alloc.get_code_representation()
+            )
+            .into());
+        };
+
+        let item_length = self.item.get_length();
+        (0..length)
+            .map(|i| Ok(self.create_item(self.get_address().offset_direct(i * item_length)?)))
+            .collect()
+    }
+
+    fn holds_heap_frame(&self) -> bool {
+        self.length.is_none()
+    }
+
+    fn runtime_heap_alloc(
+        &self,
+        count: &dyn Type,
+        program_memory: &mut MemoryManager,
+        stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        if self.length.is_some() {
+            return Err(format!(
+                "Only heap-backed arrays can be allocated - {} has a fixed length",
+                self.get_array_type().unwrap()
+            )
+            .into());
+        }
+        if count.get_type_symbol() != TypeSymbol::Integer {
+            return Err(format!(
+                "The number of items must be an {} - received {}",
+                TypeSymbol::Integer,
+                count.get_type_symbol()
+            )
+            .into());
+        }
+
+        let length = self.get_length_address()?;
+        CopyInstruction::new_alloc(program_memory, count.get_address(), &length, INTEGER_SIZE);
+
+        //? Negative lengths give a negative size which is rejected when allocating
+        let mut size = IntegerType::new();
+        size.allocate_variable(stack_sizes, program_memory)?;
+        MultiplyInstruction::new_alloc(
+            program_memory,
+            &length,
+            &Address::Immediate(Vec::from((self.item.get_length() as i64).to_le_bytes())),
+            size.get_address(),
+            INTEGER_SIZE,
+        );
+
+        HeapAllocInstruction::new_alloc(program_memory, size.get_address(), self.get_address());
+        Ok(())
+    }
+
+    fn runtime_copy_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        if other.get_array_type() != self.get_array_type() {
+            return Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Copy not implemented from type '{}' to '{}'",
+                    other
+                        .get_array_type()
+                        .unwrap_or(other.get_type_symbol().to_string()),
+                    self.get_array_type().unwrap()
+                ),
+            ));
+        }
+
+        Ok(CopyInstruction::new_alloc(
+            program_memory,
+            other.get_address(),
+            self.get_address(),
+            self.get_length(),
+        ))
+    }
+
+    fn runtime_copy_from_literal(
+        &self,
+        literal: &Literal,
+        _program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        Err(CompileError::new(
+            ErrorCode::TypeMismatch,
+            format!(
+                "{} literal cannot be used to initialise '{}' (use a list of items)",
+                literal,
+                self.get_array_type().unwrap()
+            ),
+        ))
+    }
+
+    fn get_prefix_operation_result_type(&self, _operator: &Operator) -> Vec<TypeSymbol> {
+        Vec::new()
+    }
+
+    fn get_operation_result_type(
+        &self,
+        _operator: &Operator,
+        _rhs: &TypeSymbol,
+    ) -> Vec<TypeSymbol> {
+        Vec::new()
+    }
+
+    fn operate_prefix(
+        &self,
+        operator: &Operator,
+        _destination: &dyn Type,
+        _program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        Err(format!(
+            "Operator {} not supported on '{}'",
+            operator,
+            self.get_array_type().unwrap()
+        )
+        .into())
+    }
+
+    fn operate(
+        &self,
+        operator: &Operator,
+        rhs: &dyn Type,
+        _destination: &dyn Type,
+        _program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        Err(CompileError::new(
+            ErrorCode::TypeMismatch,
+            format!(
+                "Operator {} not supported between '{}' and {}",
+                operator,
+                self.get_array_type().unwrap(),
+                rhs.get_type_symbol()
+            ),
+        ))
+    }
+
+    fn get_indexed(
+        &self,
+        index: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<Box<dyn Type>> {
+        if index.get_type_symbol() != TypeSymbol::Integer {
+            return Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Arrays must be indexed with an {} - received {}",
+                    TypeSymbol::Integer,
+                    index.get_type_symbol()
+                ),
+            ));
+        }
+
+        let address = match (self.length, self.get_address()) {
+            (Some(_), Address::StackDirect(address)) => Address::StackIndexed(
+                CloneableBox::new(Address::Immediate(Vec::from(address.to_le_bytes()))),
+                CloneableBox::new(index.get_address().clone()),
+            ),
+            (Some(_), _) => {
+                return Err("Only fixed length arrays on the stack can be indexed"
+                    .to_string()
+                    .into())
+            }
+            //? The frame id is read from the start of the array
+            (None, address) => Address::HeapIndexed(
+                CloneableBox::new(address.clone()),
+                CloneableBox::new(Address::Immediate(Vec::from(0usize.to_le_bytes()))),
+                CloneableBox::new(index.get_address().clone()),
+            ),
+        };
+
+        IndexCheckInstruction::new_alloc(
+            program_memory,
+            index.get_address(),
+            &self.get_length_address()?,
+            INTEGER_SIZE,
+        );
+
+        Ok(self.create_item(address))
+    }
+
+    fn get_address(&self) -> &Address {
+        self.address.as_ref().unwrap()
+    }
+
+    fn get_length(&self) -> usize {
+        match self.length {
+            Some(length) => length * self.item.get_length(),
+            None => USIZE_BYTES + INTEGER_SIZE,
+        }
+    }
+
+    fn get_address_mut(&mut self) -> &mut Address {
+        self.address.as_mut().unwrap()
+    }
+
+    fn set_address(&mut self, address: Address) {
+        self.address = Some(address);
+    }
+
+    fn duplicate(&self) -> Box<dyn Type> {
+        bx!(Self {
+            item: self.item.duplicate(),
+            length: self.length,
+            address: self.address.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execution::RuntimeErrorKind;
+    use crate::test_util::{compile_ok, get_output, run};
+
+    #[test]
+    fn indexes_fixed_length_arrays() {
+        assert_eq!(
+            get_output(
+                "int[3] xs = (1, 2, 3)\nxs[1] = xs[0] + xs[2]\nint i = 2\nxs[i] = -1\nprint xs[1]\nprint xs[2]\nprint xs.length\nbool[2] bs = (false, true)\nprint bs[1]\n"
+            ),
+            "4\n-1\n3\ntrue\n"
+        );
+    }
+
+    #[test]
+    fn indexes_heap_arrays() {
+        assert_eq!(
+            get_output(
+                "int[] xs\nint n = 4\nalloc xs n\nfor i in 0..n\n    xs[i] = i * i\nprint xs[3]\nprint xs.length\nfree xs\nalloc xs 2\nprint xs.length\n"
+            ),
+            "9\n4\n2\n"
+        );
+    }
+
+    #[test]
+    fn checks_bounds() {
+        for source in [
+            "int[3] xs\nint i = 3\nprint xs[i]\n",
+            "int[3] xs\nint i = -1\nxs[i] = 1\n",
+            "int[] xs\nalloc xs 2\nint i = 2\nprint xs[i]\n",
+        ] {
+            assert!(
+                matches!(
+                    run(compile_ok(source)).unwrap_err().kind,
+                    RuntimeErrorKind::IndexOutOfBounds(..)
+                ),
+                "{}",
+                source
+            );
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use crate::address::Address;
-use crate::errors::create_literal_not_impl_error;
+use crate::errors::{create_literal_not_impl_error, CompileError, CompileResult, ErrorCode};
 use crate::memory::MemoryManager;
 use crate::processing::blocks::StackSizes;
 use crate::processing::instructions::binary_and_8::BinaryAndInstruction;
 use crate::processing::instructions::binary_not_7::BinaryNotInstruction;
 use crate::processing::instructions::binary_or_12::BinaryOrInstruction;
 use crate::processing::instructions::copy_3::CopyInstruction;
+use crate::processing::instructions::not_equal_15::NotEqualInstruction;
 use crate::processing::symbols::Literal;
 use crate::processing::types::PrefixOperation;
 use crate::util::warn;
@@ -33,7 +34,7 @@ impl Type for BoolType {
         &mut self,
         stack: &mut StackSizes,
         _program_memory: &mut MemoryManager,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         if self.address.is_some() {
             warn(
                 format!(
@@ -50,7 +51,7 @@ impl Type for BoolType {
         Ok(())
     }
 
-    fn get_constant(&self, literal: &Literal) -> Result<Address, String> {
+    fn get_constant(&self, literal: &Literal) -> CompileResult<Address> {
         match literal {
             Literal::Bool(value) => {
                 if *value {
@@ -74,7 +75,7 @@ impl Type for BoolType {
         &self,
         other: &dyn Type,
         program_memory: &mut MemoryManager,
-    ) -> Result<CopyInstruction, String> {
+    ) -> CompileResult<CopyInstruction> {
         match other.get_type_symbol() {
             TypeSymbol::Boolean => Ok(CopyInstruction::new_alloc(
                 program_memory,
@@ -82,19 +83,46 @@ impl Type for BoolType {
                 self.address.as_ref().unwrap(),
                 BOOLEAN_SIZE,
             )),
-            s => Err(format!(
-                "Copy not implemented from type '{}' to '{}'",
-                s,
-                TypeSymbol::Boolean
+            s => Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Copy not implemented from type '{}' to '{}'",
+                    s,
+                    TypeSymbol::Boolean
+                ),
             )),
         }
     }
 
+    fn runtime_cast_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        match other.get_type_symbol() {
+            //? Any non-zero value is true
+            TypeSymbol::Integer | TypeSymbol::Pointer => {
+                NotEqualInstruction::new_alloc(
+                    program_memory,
+                    other.get_address(),
+                    &Address::Immediate(vec![0; other.get_length()]),
+                    self.get_address(),
+                    other.get_length(),
+                );
+                Ok(())
+            }
+            _ => {
+                self.runtime_copy_from(other, program_memory)?;
+                Ok(())
+            }
+        }
+    }
+
     fn runtime_copy_from_literal(
         &self,
         literal: &Literal,
         program_memory: &mut MemoryManager,
-    ) -> Result<CopyInstruction, String> {
+    ) -> CompileResult<CopyInstruction> {
         let constant = self.get_constant(literal)?;
 
         Ok(CopyInstruction::new_alloc(
@@ -119,6 +147,10 @@ impl Type for BoolType {
         self.address.as_mut().unwrap()
     }
 
+    fn set_address(&mut self, address: Address) {
+        self.address = Some(address);
+    }
+
     fn duplicate(&self) -> Box<dyn Type> {
         let mut t = BoolType::new();
         t.address = self.address.as_ref().cloned();
@@ -148,7 +180,7 @@ impl Operation<BoolType> for BoolAnd {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
         assert_eq!(rhs.get_type_symbol(), TypeSymbol::Boolean);
 
@@ -186,7 +218,7 @@ impl Operation<BoolType> for BoolOr {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
         assert_eq!(rhs.get_type_symbol(), TypeSymbol::Boolean);
 
@@ -206,7 +238,7 @@ pub struct BoolNot {}
 
 impl PrefixOperation<BoolType> for BoolNot {
     fn get_symbol(&self) -> Operator {
-        Operator::And
+        Operator::Not
     }
 
     fn get_result_type(&self) -> Option<TypeSymbol> {
@@ -219,7 +251,7 @@ impl PrefixOperation<BoolType> for BoolNot {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
 
         let (address_from, length) = (lhs.get_address(), lhs.get_length());
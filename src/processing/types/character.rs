@@ -0,0 +1,390 @@
+use crate::address::Address;
+use crate::errors::{create_literal_not_impl_error, CompileError, CompileResult, ErrorCode};
+use crate::memory::MemoryManager;
+use crate::processing::blocks::StackSizes;
+use crate::processing::instructions::copy_3::CopyInstruction;
+use crate::processing::instructions::equality_14::EqualityInstruction;
+use crate::processing::instructions::less_20::LessInstruction;
+use crate::processing::instructions::less_equal_21::LessEqualInstruction;
+use crate::processing::instructions::not_equal_15::NotEqualInstruction;
+use crate::processing::symbols::Literal;
+use crate::processing::types::{Operation, Type};
+use crate::util::warn;
+use crate::{
+    bx, default_get_type_symbol_impl, default_type_initialiser, default_type_operate_impl,
+    default_type_struct, default_type_wrapper_struct_and_impl,
+    processing::symbols::{Operator, TypeSymbol},
+};
+
+default_type_wrapper_struct_and_impl!(CharWrapper, CharType, TypeSymbol::Character);
+default_type_struct!(CharType);
+default_type_initialiser!(
+    CharType,
+    (
+        CharEqual,
+        CharNotEqual,
+        CharLess,
+        CharGreater,
+        CharLessEqual,
+        CharGreaterEqual
+    ),
+    ()
+);
+
+/// Size of a `char` in bytes (a unicode scalar value)
+pub const CHAR_SIZE: usize = 4;
+
+impl CharType {
+    /// Creates a `char` referring to an existing location e.g. an item in a string
+    pub fn new_with_address(address: Address) -> Self {
+        let mut t = CharType::new();
+        t.address = Some(address);
+        t
+    }
+}
+
+impl Type for CharType {
+    default_get_type_symbol_impl!(CharType, TypeSymbol::Character);
+
+    fn allocate_variable(
+        &mut self,
+        stack: &mut StackSizes,
+        _program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        if self.address.is_some() {
+            warn(
+                format!(
+                    "Allocating {:?} when it already has a memory address",
+                    self.get_type_symbol()
+                )
+                .as_str(),
+            )
+        }
+        self.address = Some(Address::StackDirect(stack.increment_stack_size(CHAR_SIZE)));
+
+        Ok(())
+    }
+
+    fn get_constant(&self, literal: &Literal) -> CompileResult<Address> {
+        match literal {
+            Literal::Char(value) => {
+                Ok(Address::Immediate(Vec::from((*value as u32).to_le_bytes())))
+            }
+            Literal::Int(value) => match u32::try_from(*value).ok().and_then(char::from_u32) {
+                Some(c) => Ok(Address::Immediate(Vec::from((c as u32).to_le_bytes()))),
+                None => Err(format!(
+                    "The value ({}) is not a valid {} code",
+                    *value,
+                    self.get_type_symbol()
+                )
+                .into()),
+            },
+            other => create_literal_not_impl_error(other, self.get_type_symbol()),
+        }
+    }
+
+    fn runtime_copy_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        match other.get_type_symbol() {
+            TypeSymbol::Character => Ok(CopyInstruction::new_alloc(
+                program_memory,
+                other.get_address(),
+                self.address.as_ref().unwrap(),
+                CHAR_SIZE,
+            )),
+            s => Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Copy not implemented from type '{}' to '{}'",
+                    s,
+                    TypeSymbol::Character
+                ),
+            )),
+        }
+    }
+
+    fn runtime_cast_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        match other.get_type_symbol() {
+            //? Take the lowest bytes of the code point
+            TypeSymbol::Integer => {
+                CopyInstruction::new_alloc(
+                    program_memory,
+                    other.get_address(),
+                    self.get_address(),
+                    CHAR_SIZE,
+                );
+                Ok(())
+            }
+            _ => {
+                self.runtime_copy_from(other, program_memory)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn runtime_copy_from_literal(
+        &self,
+        literal: &Literal,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        let constant = self.get_constant(literal)?;
+
+        Ok(CopyInstruction::new_alloc(
+            program_memory,
+            &constant,
+            self.address.as_ref().unwrap(),
+            CHAR_SIZE,
+        ))
+    }
+
+    default_type_operate_impl!(CharType);
+
+    fn get_address(&self) -> &Address {
+        self.address.as_ref().unwrap()
+    }
+
+    fn get_length(&self) -> usize {
+        CHAR_SIZE
+    }
+
+    fn get_address_mut(&mut self) -> &mut Address {
+        self.address.as_mut().unwrap()
+    }
+
+    fn set_address(&mut self, address: Address) {
+        self.address = Some(address);
+    }
+
+    fn duplicate(&self) -> Box<dyn Type> {
+        let mut t = CharType::new();
+        t.address = self.address.as_ref().cloned();
+        bx!(t)
+    }
+}
+
+pub struct CharEqual {}
+
+impl Operation<CharType> for CharEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::Equal
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Character => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &CharType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Character);
+
+        EqualityInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            CHAR_SIZE,
+        );
+        Ok(())
+    }
+}
+
+pub struct CharNotEqual {}
+
+impl Operation<CharType> for CharNotEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::NotEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Character => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &CharType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Character);
+
+        NotEqualInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            CHAR_SIZE,
+        );
+        Ok(())
+    }
+}
+
+pub struct CharLess {}
+
+impl Operation<CharType> for CharLess {
+    fn get_symbol(&self) -> Operator {
+        Operator::Less
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Character => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &CharType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Character);
+
+        LessInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            CHAR_SIZE,
+            false,
+        );
+        Ok(())
+    }
+}
+
+pub struct CharGreater {}
+
+impl Operation<CharType> for CharGreater {
+    fn get_symbol(&self) -> Operator {
+        Operator::Greater
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Character => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &CharType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Character);
+        //? a > b is equivalent to b < a
+        LessInstruction::new_alloc(
+            program_memory,
+            rhs.get_address(),
+            lhs.get_address(),
+            destination.get_address(),
+            CHAR_SIZE,
+            false,
+        );
+        Ok(())
+    }
+}
+
+pub struct CharLessEqual {}
+
+impl Operation<CharType> for CharLessEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::LessEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Character => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &CharType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Character);
+
+        LessEqualInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            CHAR_SIZE,
+            false,
+        );
+        Ok(())
+    }
+}
+
+pub struct CharGreaterEqual {}
+
+impl Operation<CharType> for CharGreaterEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::GreaterEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Character => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &CharType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Character);
+        //? a >= b is equivalent to b <= a
+        LessEqualInstruction::new_alloc(
+            program_memory,
+            rhs.get_address(),
+            lhs.get_address(),
+            destination.get_address(),
+            CHAR_SIZE,
+            false,
+        );
+        Ok(())
+    }
+}
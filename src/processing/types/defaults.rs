@@ -104,7 +104,7 @@ macro_rules! default_type_operate_impl {
             destination: &dyn $crate::processing::types::Type,
             program_memory: &mut $crate::memory::MemoryManager,
             stack_sizes: &mut $crate::processing::blocks::StackSizes,
-        ) -> Result<(), String> {
+        ) -> $crate::errors::CompileResult<()> {
             for op in self.operators_prefix.iter() {
                 if op.get_symbol() == *operator && op.get_result_type().is_some() {
                     return op.operate_prefix(self, destination, program_memory, stack_sizes);
@@ -115,7 +115,8 @@ macro_rules! default_type_operate_impl {
                 "Operator {} not supported on {}",
                 operator,
                 self.get_type_symbol()
-            ))
+            )
+            .into())
         }
 
         fn operate(
@@ -125,7 +126,7 @@ macro_rules! default_type_operate_impl {
             destination: &dyn $crate::processing::types::Type,
             program_memory: &mut $crate::memory::MemoryManager,
             stack_sizes: &mut $crate::processing::blocks::StackSizes,
-        ) -> Result<(), String> {
+        ) -> $crate::errors::CompileResult<()> {
             for op in self.operators.iter() {
                 if op.get_symbol() == *operator
                     && op.get_result_type(&rhs.get_type_symbol()).is_some()
@@ -134,11 +135,14 @@ macro_rules! default_type_operate_impl {
                 }
             }
 
-            Err(format!(
-                "Operator {} not supported between {} and {}",
-                operator,
-                self.get_type_symbol(),
-                rhs.get_type_symbol()
+            Err($crate::errors::CompileError::new(
+                $crate::errors::ErrorCode::TypeMismatch,
+                format!(
+                    "Operator {} not supported between {} and {}",
+                    operator,
+                    self.get_type_symbol(),
+                    rhs.get_type_symbol()
+                ),
             ))
         }
     };
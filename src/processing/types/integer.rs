@@ -0,0 +1,697 @@
+use crate::address::Address;
+use crate::errors::{create_literal_not_impl_error, CompileError, CompileResult, ErrorCode};
+use crate::memory::MemoryManager;
+use crate::processing::blocks::StackSizes;
+use crate::processing::instructions::add_instruction_13::AddInstruction;
+use crate::processing::instructions::binary_and_8::BinaryAndInstruction;
+use crate::processing::instructions::copy_3::CopyInstruction;
+use crate::processing::instructions::divide_19::DivideInstruction;
+use crate::processing::instructions::equality_14::EqualityInstruction;
+use crate::processing::instructions::less_20::LessInstruction;
+use crate::processing::instructions::less_equal_21::LessEqualInstruction;
+use crate::processing::instructions::modulo_25::ModuloInstruction;
+use crate::processing::instructions::multiply_18::MultiplyInstruction;
+use crate::processing::instructions::not_equal_15::NotEqualInstruction;
+use crate::processing::instructions::subtract_17::SubtractInstruction;
+use crate::processing::symbols::Literal;
+use crate::processing::types::boolean::BOOLEAN_SIZE;
+use crate::processing::types::character::CHAR_SIZE;
+use crate::processing::types::{Operation, PrefixOperation, Type};
+use crate::util::{warn, USIZE_BYTES};
+use crate::{
+    bx, default_get_type_symbol_impl, default_type_initialiser, default_type_operate_impl,
+    default_type_struct, default_type_wrapper_struct_and_impl,
+    processing::symbols::{Operator, TypeSymbol},
+};
+
+default_type_wrapper_struct_and_impl!(IntegerWrapper, IntegerType, TypeSymbol::Integer);
+default_type_struct!(IntegerType);
+default_type_initialiser!(
+    IntegerType,
+    (
+        IntAdd,
+        IntSubtract,
+        IntProduct,
+        IntDivide,
+        IntModulo,
+        IntEqual,
+        IntNotEqual,
+        IntLess,
+        IntGreater,
+        IntLessEqual,
+        IntGreaterEqual
+    ),
+    (IntNegate)
+);
+
+/// Size of an `int` in bytes (signed, two's complement)
+pub const INTEGER_SIZE: usize = 8;
+
+impl Type for IntegerType {
+    default_get_type_symbol_impl!(IntegerType, TypeSymbol::Integer);
+
+    fn allocate_variable(
+        &mut self,
+        stack: &mut StackSizes,
+        _program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        if self.address.is_some() {
+            warn(
+                format!(
+                    "Allocating {:?} when it already has a memory address",
+                    self.get_type_symbol()
+                )
+                .as_str(),
+            )
+        }
+        self.address = Some(Address::StackDirect(
+            stack.increment_stack_size(INTEGER_SIZE),
+        ));
+
+        Ok(())
+    }
+
+    fn get_constant(&self, literal: &Literal) -> CompileResult<Address> {
+        match literal {
+            Literal::Int(value) => {
+                let int: Result<i64, _> = (*value).try_into();
+                if let Ok(int) = int {
+                    Ok(Address::Immediate(Vec::from(int.to_le_bytes())))
+                } else {
+                    Err(format!(
+                        "The value ({}) can't fit into an {} (the value must be between {} and {})",
+                        *value,
+                        self.get_type_symbol(),
+                        i64::MIN,
+                        i64::MAX
+                    )
+                    .into())
+                }
+            }
+            Literal::Bool(value) => Ok(Address::Immediate(Vec::from(
+                i64::from(*value).to_le_bytes(),
+            ))),
+            other => create_literal_not_impl_error(other, self.get_type_symbol()),
+        }
+    }
+
+    fn runtime_copy_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        match other.get_type_symbol() {
+            TypeSymbol::Integer => Ok(CopyInstruction::new_alloc(
+                program_memory,
+                other.get_address(),
+                self.address.as_ref().unwrap(),
+                INTEGER_SIZE,
+            )),
+            s => Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Copy not implemented from type '{}' to '{}'",
+                    s,
+                    TypeSymbol::Integer
+                ),
+            )),
+        }
+    }
+
+    fn runtime_cast_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        match other.get_type_symbol() {
+            TypeSymbol::Pointer => {
+                let length = USIZE_BYTES.min(INTEGER_SIZE);
+                if length < INTEGER_SIZE {
+                    CopyInstruction::new_alloc(
+                        program_memory,
+                        &Address::Immediate(vec![0; INTEGER_SIZE]),
+                        self.get_address(),
+                        INTEGER_SIZE,
+                    );
+                }
+                CopyInstruction::new_alloc(
+                    program_memory,
+                    other.get_address(),
+                    self.get_address(),
+                    length,
+                );
+                Ok(())
+            }
+            TypeSymbol::Character => {
+                CopyInstruction::new_alloc(
+                    program_memory,
+                    &Address::Immediate(vec![0; INTEGER_SIZE]),
+                    self.get_address(),
+                    INTEGER_SIZE,
+                );
+                CopyInstruction::new_alloc(
+                    program_memory,
+                    other.get_address(),
+                    self.get_address(),
+                    CHAR_SIZE,
+                );
+                Ok(())
+            }
+            TypeSymbol::Boolean => {
+                CopyInstruction::new_alloc(
+                    program_memory,
+                    &Address::Immediate(vec![0; INTEGER_SIZE]),
+                    self.get_address(),
+                    INTEGER_SIZE,
+                );
+                //? Lowest byte comes first (little-endian) so true becomes 1
+                BinaryAndInstruction::new_alloc(
+                    program_memory,
+                    other.get_address(),
+                    &Address::Immediate(vec![1]),
+                    self.get_address(),
+                    BOOLEAN_SIZE,
+                );
+                Ok(())
+            }
+            _ => {
+                self.runtime_copy_from(other, program_memory)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn runtime_copy_from_literal(
+        &self,
+        literal: &Literal,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        let constant = self.get_constant(literal)?;
+
+        Ok(CopyInstruction::new_alloc(
+            program_memory,
+            &constant,
+            self.address.as_ref().unwrap(),
+            INTEGER_SIZE,
+        ))
+    }
+
+    default_type_operate_impl!(IntegerType);
+
+    fn get_address(&self) -> &Address {
+        self.address.as_ref().unwrap()
+    }
+
+    fn get_length(&self) -> usize {
+        INTEGER_SIZE
+    }
+
+    fn get_address_mut(&mut self) -> &mut Address {
+        self.address.as_mut().unwrap()
+    }
+
+    fn set_address(&mut self, address: Address) {
+        self.address = Some(address);
+    }
+
+    fn duplicate(&self) -> Box<dyn Type> {
+        let mut t = IntegerType::new();
+        t.address = self.address.as_ref().cloned();
+        bx!(t)
+    }
+}
+
+pub struct IntAdd {}
+
+impl Operation<IntegerType> for IntAdd {
+    fn get_symbol(&self) -> Operator {
+        Operator::Add
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Integer),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Integer);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        AddInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntSubtract {}
+
+impl Operation<IntegerType> for IntSubtract {
+    fn get_symbol(&self) -> Operator {
+        Operator::Subtract
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Integer),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Integer);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        SubtractInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntProduct {}
+
+impl Operation<IntegerType> for IntProduct {
+    fn get_symbol(&self) -> Operator {
+        Operator::Product
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Integer),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Integer);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        MultiplyInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntDivide {}
+
+impl Operation<IntegerType> for IntDivide {
+    fn get_symbol(&self) -> Operator {
+        Operator::Divide
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Integer),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Integer);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        DivideInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+            true,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntModulo {}
+
+impl Operation<IntegerType> for IntModulo {
+    fn get_symbol(&self) -> Operator {
+        Operator::Modulo
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Integer),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Integer);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        ModuloInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+            true,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntEqual {}
+
+impl Operation<IntegerType> for IntEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::Equal
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        EqualityInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntNotEqual {}
+
+impl Operation<IntegerType> for IntNotEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::NotEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        NotEqualInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntLess {}
+
+impl Operation<IntegerType> for IntLess {
+    fn get_symbol(&self) -> Operator {
+        Operator::Less
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        LessInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+            true,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntGreater {}
+
+impl Operation<IntegerType> for IntGreater {
+    fn get_symbol(&self) -> Operator {
+        Operator::Greater
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+        //? a > b is equivalent to b < a
+        LessInstruction::new_alloc(
+            program_memory,
+            rhs.get_address(),
+            lhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+            true,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntLessEqual {}
+
+impl Operation<IntegerType> for IntLessEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::LessEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+
+        LessEqualInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+            true,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntGreaterEqual {}
+
+impl Operation<IntegerType> for IntGreaterEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::GreaterEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Integer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &IntegerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Integer);
+        //? a >= b is equivalent to b <= a
+        LessEqualInstruction::new_alloc(
+            program_memory,
+            rhs.get_address(),
+            lhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+            true,
+        );
+        Ok(())
+    }
+}
+
+pub struct IntNegate {}
+
+impl PrefixOperation<IntegerType> for IntNegate {
+    fn get_symbol(&self) -> Operator {
+        Operator::Subtract
+    }
+
+    fn get_result_type(&self) -> Option<TypeSymbol> {
+        Some(TypeSymbol::Integer)
+    }
+
+    fn operate_prefix(
+        &self,
+        lhs: &IntegerType,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Integer);
+
+        //? -a is calculated as 0 - a
+        SubtractInstruction::new_alloc(
+            program_memory,
+            &Address::Immediate(vec![0; INTEGER_SIZE]),
+            lhs.get_address(),
+            destination.get_address(),
+            INTEGER_SIZE,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::get_output;
+
+    #[test]
+    fn adds_negative_operands() {
+        assert_eq!(
+            get_output("int a = -1\nprint a + a\nint b = -300\nprint b + 7\nprint -255 + -1\n"),
+            "-2\n-293\n-256\n"
+        );
+    }
+
+    #[test]
+    fn carries_between_bytes() {
+        assert_eq!(
+            get_output("int a = 255\nprint a + 1\nptr p = 65535\nptr q = 65535\nprint p + q\n"),
+            "256\n131070\n"
+        );
+    }
+
+    #[test]
+    fn operates_on_negative_operands() {
+        assert_eq!(
+            get_output(
+                "int b = -300\nprint b - 7\nprint b * -3\nprint b / 7\nprint b % 7\nprint 7 - 300\n"
+            ),
+            "-307\n900\n-42\n-6\n-293\n"
+        );
+    }
+
+    #[test]
+    fn compares_signed_values() {
+        assert_eq!(
+            get_output(
+                "int a = -5\nint b = 3\nprint a < b\nprint a > b\nprint a <= -5\nprint b >= 4\nprint a == -5\nprint a != b\n"
+            ),
+            "true\nfalse\ntrue\nfalse\ntrue\ntrue\n"
+        );
+    }
+}
@@ -1,13 +1,19 @@
 use crate::address::Address;
-use crate::errors::create_literal_not_impl_error;
+use crate::errors::{create_literal_not_impl_error, CompileError, CompileResult, ErrorCode};
 use crate::memory::MemoryManager;
 use crate::processing::blocks::StackSizes;
 use crate::processing::instructions::add_instruction_13::AddInstruction;
-use crate::processing::instructions::binary_not_7::BinaryNotInstruction;
 use crate::processing::instructions::copy_3::CopyInstruction;
+use crate::processing::instructions::divide_19::DivideInstruction;
 use crate::processing::instructions::equality_14::EqualityInstruction;
+use crate::processing::instructions::less_20::LessInstruction;
+use crate::processing::instructions::less_equal_21::LessEqualInstruction;
+use crate::processing::instructions::modulo_25::ModuloInstruction;
+use crate::processing::instructions::multiply_18::MultiplyInstruction;
 use crate::processing::instructions::not_equal_15::NotEqualInstruction;
+use crate::processing::instructions::subtract_17::SubtractInstruction;
 use crate::processing::symbols::Literal;
+use crate::processing::types::integer::INTEGER_SIZE;
 use crate::processing::types::{Operation, Type};
 use crate::util::{warn, USIZE_BYTES};
 use crate::{
@@ -18,7 +24,23 @@ use crate::{
 
 default_type_wrapper_struct_and_impl!(PointerWrapper, PointerType, TypeSymbol::Pointer);
 default_type_struct!(PointerType);
-default_type_initialiser!(PointerType, (Add, Subtract, Equal, NotEqual), ());
+default_type_initialiser!(
+    PointerType,
+    (
+        Add,
+        Subtract,
+        Product,
+        Divide,
+        Modulo,
+        Equal,
+        NotEqual,
+        Less,
+        Greater,
+        LessEqual,
+        GreaterEqual
+    ),
+    ()
+);
 
 impl PointerType {
     pub fn duplicate_known(&self) -> PointerType {
@@ -41,7 +63,7 @@ impl Type for PointerType {
         &mut self,
         stack: &mut StackSizes,
         _program_memory: &mut MemoryManager,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         if self.address.is_some() {
             warn(
                 format!(
@@ -58,14 +80,14 @@ impl Type for PointerType {
         Ok(())
     }
 
-    fn get_constant(&self, literal: &Literal) -> Result<Address, String> {
+    fn get_constant(&self, literal: &Literal) -> CompileResult<Address> {
         match literal {
             Literal::Int(value) => {
                 let ptr: Result<usize, _> = (*value).try_into();
                 if let Ok(ptr) = ptr {
                     Ok(Address::Immediate(Vec::from(ptr.to_le_bytes())))
                 } else {
-                    Err(format!("The value ({}) can't fit into a {} (the value must be greater than zero and fit within your platform pointer width [{} bytes])", *value, self.get_type_symbol(), USIZE_BYTES))
+                    Err(format!("The value ({}) can't fit into a {} (the value must be greater than zero and fit within your platform pointer width [{} bytes])", *value, self.get_type_symbol(), USIZE_BYTES).into())
                 }
             }
             other => create_literal_not_impl_error(other, self.get_type_symbol()),
@@ -76,7 +98,7 @@ impl Type for PointerType {
         &self,
         other: &dyn Type,
         program_memory: &mut MemoryManager,
-    ) -> Result<CopyInstruction, String> {
+    ) -> CompileResult<CopyInstruction> {
         match other.get_type_symbol() {
             TypeSymbol::Pointer => Ok(CopyInstruction::new_alloc(
                 program_memory,
@@ -84,19 +106,53 @@ impl Type for PointerType {
                 self.address.as_ref().unwrap(),
                 USIZE_BYTES,
             )),
-            s => Err(format!(
-                "Copy not implemented from type '{}' to '{}'",
-                s,
-                TypeSymbol::Pointer
+            s => Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Copy not implemented from type '{}' to '{}'",
+                    s,
+                    TypeSymbol::Pointer
+                ),
             )),
         }
     }
 
+    fn runtime_cast_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        match other.get_type_symbol() {
+            TypeSymbol::Integer => {
+                let length = USIZE_BYTES.min(INTEGER_SIZE);
+                if length < USIZE_BYTES {
+                    CopyInstruction::new_alloc(
+                        program_memory,
+                        &Address::Immediate(vec![0; USIZE_BYTES]),
+                        self.get_address(),
+                        USIZE_BYTES,
+                    );
+                }
+                CopyInstruction::new_alloc(
+                    program_memory,
+                    other.get_address(),
+                    self.get_address(),
+                    length,
+                );
+                Ok(())
+            }
+            _ => {
+                self.runtime_copy_from(other, program_memory)?;
+                Ok(())
+            }
+        }
+    }
+
     fn runtime_copy_from_literal(
         &self,
         literal: &Literal,
         program_memory: &mut MemoryManager,
-    ) -> Result<CopyInstruction, String> {
+    ) -> CompileResult<CopyInstruction> {
         let constant = self.get_constant(literal)?;
 
         Ok(CopyInstruction::new_alloc(
@@ -121,6 +177,10 @@ impl Type for PointerType {
         self.address.as_mut().unwrap()
     }
 
+    fn set_address(&mut self, address: Address) {
+        self.address = Some(address);
+    }
+
     fn duplicate(&self) -> Box<dyn Type> {
         bx!(self.duplicate_known())
     }
@@ -148,7 +208,7 @@ impl Operation<PointerType> for Add {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         assert_eq!(destination.get_type_symbol(), TypeSymbol::Pointer);
         assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
 
@@ -185,38 +245,128 @@ impl Operation<PointerType> for Subtract {
         rhs: &dyn Type,
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
-        stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
         assert_eq!(destination.get_type_symbol(), TypeSymbol::Pointer);
         assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
 
-        let mut magic_number = PointerType::new();
-        magic_number
-            .allocate_variable(stack_sizes, program_memory)
-            .unwrap();
-        //? Not the subtracted value
-        BinaryNotInstruction::new_alloc(
+        let (address_from, length) = (lhs.get_address(), lhs.get_length());
+        SubtractInstruction::new_alloc(
             program_memory,
+            address_from,
             rhs.get_address(),
-            magic_number.get_address(),
+            destination.get_address(),
+            length,
+        );
+        Ok(())
+    }
+}
+
+pub struct Product {}
+
+impl Operation<PointerType> for Product {
+    fn get_symbol(&self) -> Operator {
+        Operator::Product
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Pointer => Some(TypeSymbol::Pointer),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &PointerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Pointer);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
+
+        MultiplyInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
             USIZE_BYTES,
         );
-        //? Add one to the magic number
-        AddInstruction::new_alloc(
+        Ok(())
+    }
+}
+
+pub struct Divide {}
+
+impl Operation<PointerType> for Divide {
+    fn get_symbol(&self) -> Operator {
+        Operator::Divide
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Pointer => Some(TypeSymbol::Pointer),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &PointerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Pointer);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
+
+        DivideInstruction::new_alloc(
             program_memory,
-            magic_number.get_address(),
-            &Address::Immediate(Vec::from(1usize.to_le_bytes())),
-            magic_number.get_address(),
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
             USIZE_BYTES,
+            false,
         );
+        Ok(())
+    }
+}
 
-        let (address_from, length) = (lhs.get_address(), lhs.get_length());
-        AddInstruction::new_alloc(
+pub struct Modulo {}
+
+impl Operation<PointerType> for Modulo {
+    fn get_symbol(&self) -> Operator {
+        Operator::Modulo
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Pointer => Some(TypeSymbol::Pointer),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &PointerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Pointer);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
+
+        ModuloInstruction::new_alloc(
             program_memory,
-            address_from,
-            magic_number.get_address(),
+            lhs.get_address(),
+            rhs.get_address(),
             destination.get_address(),
-            length,
+            USIZE_BYTES,
+            false,
         );
         Ok(())
     }
@@ -244,7 +394,7 @@ impl Operation<PointerType> for Equal {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
         assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
 
@@ -282,7 +432,7 @@ impl Operation<PointerType> for NotEqual {
         destination: &dyn Type,
         program_memory: &mut MemoryManager,
         _stack_sizes: &mut StackSizes,
-    ) -> Result<(), String> {
+    ) -> CompileResult<()> {
         assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
         assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
 
@@ -297,3 +447,186 @@ impl Operation<PointerType> for NotEqual {
         Ok(())
     }
 }
+
+pub struct Less {}
+
+impl Operation<PointerType> for Less {
+    fn get_symbol(&self) -> Operator {
+        Operator::Less
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Pointer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &PointerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
+
+        LessInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            USIZE_BYTES,
+            false,
+        );
+        Ok(())
+    }
+}
+
+pub struct Greater {}
+
+impl Operation<PointerType> for Greater {
+    fn get_symbol(&self) -> Operator {
+        Operator::Greater
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Pointer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &PointerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
+        //? a > b is equivalent to b < a
+        LessInstruction::new_alloc(
+            program_memory,
+            rhs.get_address(),
+            lhs.get_address(),
+            destination.get_address(),
+            USIZE_BYTES,
+            false,
+        );
+        Ok(())
+    }
+}
+
+pub struct LessEqual {}
+
+impl Operation<PointerType> for LessEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::LessEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Pointer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &PointerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
+
+        LessEqualInstruction::new_alloc(
+            program_memory,
+            lhs.get_address(),
+            rhs.get_address(),
+            destination.get_address(),
+            USIZE_BYTES,
+            false,
+        );
+        Ok(())
+    }
+}
+
+pub struct GreaterEqual {}
+
+impl Operation<PointerType> for GreaterEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::GreaterEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::Pointer => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &PointerType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::Pointer);
+        //? a >= b is equivalent to b <= a
+        LessEqualInstruction::new_alloc(
+            program_memory,
+            rhs.get_address(),
+            lhs.get_address(),
+            destination.get_address(),
+            USIZE_BYTES,
+            false,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execution::RuntimeErrorKind;
+    use crate::test_util::{compile_ok, get_output, run};
+
+    #[test]
+    fn multiplies_divides_and_takes_modulo() {
+        assert_eq!(
+            get_output(
+                "ptr a = 7\nptr b = 3\na *= b\nprint a\na /= b\nprint a\nprint a % b\na %= b\nprint a\n"
+            ),
+            "21\n7\n1\n1\n"
+        );
+    }
+
+    #[test]
+    fn divides_unsigned() {
+        assert_eq!(
+            get_output(
+                "ptr big = 18446744073709551615\nptr two = 2\nprint big / two\nprint big % two\n"
+            ),
+            "9223372036854775807\n1\n"
+        );
+    }
+
+    #[test]
+    fn rejects_division_by_zero() {
+        let memory = compile_ok("ptr a = 7\nptr b = 0\nptr c = a / b\n");
+        assert!(matches!(
+            run(memory).unwrap_err().kind,
+            RuntimeErrorKind::DivisionByZero
+        ));
+    }
+}
@@ -0,0 +1,645 @@
+use crate::address::{Address, CloneableBox};
+use crate::errors::{create_literal_not_impl_error, CompileError, CompileResult, ErrorCode};
+use crate::memory::MemoryManager;
+use crate::processing::blocks::StackSizes;
+use crate::processing::instructions::binary_and_8::BinaryAndInstruction;
+use crate::processing::instructions::binary_not_7::BinaryNotInstruction;
+use crate::processing::instructions::binary_or_12::BinaryOrInstruction;
+use crate::processing::instructions::copy_3::CopyInstruction;
+use crate::processing::instructions::equality_14::EqualityInstruction;
+use crate::processing::instructions::index_check_26::IndexCheckInstruction;
+use crate::processing::instructions::less_20::LessInstruction;
+use crate::processing::instructions::string_less_27::StringLessInstruction;
+use crate::processing::symbols::Literal;
+use crate::processing::types::boolean::{BoolType, BOOLEAN_SIZE};
+use crate::processing::types::character::{CharType, CHAR_SIZE};
+use crate::processing::types::integer::{IntegerType, INTEGER_SIZE};
+use crate::processing::types::{Fields, Operation, PrefixOperation, Type, LENGTH_FIELD};
+use crate::util::{warn, USIZE_BYTES};
+use crate::{
+    bx, default_get_type_symbol_impl, default_type_operate_impl,
+    default_type_wrapper_struct_and_impl,
+    processing::symbols::{Operator, TypeSymbol},
+};
+
+default_type_wrapper_struct_and_impl!(StringWrapper, StringType, TypeSymbol::String);
+
+/// Capacity (in chars) of a `str` declared without an explicit size
+pub const STRING_DEFAULT_CAPACITY: usize = 32;
+
+/// Fixed capacity string stored as `[length (usize)][capacity * char]`. Unused chars are
+/// always zeroed
+pub struct StringType {
+    operators: Vec<Box<dyn Operation<StringType>>>,
+    operators_prefix: Vec<Box<dyn PrefixOperation<StringType>>>,
+    address: Option<Address>,
+    capacity: usize,
+}
+
+impl StringType {
+    pub fn new() -> Self {
+        Self::with_capacity(STRING_DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            operators: vec![
+                bx!(StrEqual {}),
+                bx!(StrNotEqual {}),
+                bx!(StrLess {}),
+                bx!(StrGreater {}),
+                bx!(StrLessEqual {}),
+                bx!(StrGreaterEqual {}),
+            ],
+            operators_prefix: vec![],
+            address: None,
+            capacity,
+        }
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Gets the capacity of a string from its length in memory
+    fn capacity_of(string: &dyn Type) -> usize {
+        (string.get_length() - USIZE_BYTES) / CHAR_SIZE
+    }
+
+    /// Address of the first char
+    fn get_chars_address(string: &dyn Type) -> CompileResult<Address> {
+        Ok(string.get_address().offset_direct(USIZE_BYTES)?)
+    }
+}
+
+impl Default for StringType {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Type for StringType {
+    default_get_type_symbol_impl!(StringType, TypeSymbol::String);
+
+    fn allocate_variable(
+        &mut self,
+        stack: &mut StackSizes,
+        _program_memory: &mut MemoryManager,
+    ) -> CompileResult<()> {
+        if self.address.is_some() {
+            warn(
+                format!(
+                    "Allocating {:?} when it already has a memory address",
+                    self.get_type_symbol()
+                )
+                .as_str(),
+            )
+        }
+        self.address = Some(Address::StackDirect(
+            stack.increment_stack_size(self.get_length()),
+        ));
+
+        Ok(())
+    }
+
+    fn get_constant(&self, literal: &Literal) -> CompileResult<Address> {
+        match literal {
+            Literal::String(value) => {
+                let length = value.chars().count();
+                if length > self.capacity {
+                    return Err(format!(
+                        "String of length {} can't fit into a {} with capacity {}",
+                        length,
+                        self.get_type_symbol(),
+                        self.capacity
+                    )
+                    .into());
+                }
+
+                let mut data = Vec::with_capacity(self.get_length());
+                data.extend(length.to_le_bytes());
+                for c in value.chars() {
+                    data.extend((c as u32).to_le_bytes());
+                }
+                data.resize(self.get_length(), 0);
+                Ok(Address::Immediate(data))
+            }
+            other => create_literal_not_impl_error(other, self.get_type_symbol()),
+        }
+    }
+
+    fn runtime_copy_from(
+        &self,
+        other: &dyn Type,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        match other.get_type_symbol() {
+            TypeSymbol::String => {
+                let other_capacity = StringType::capacity_of(other);
+                if other_capacity > self.capacity {
+                    return Err(CompileError::new(
+                        ErrorCode::TypeMismatch,
+                        format!(
+                            "Cannot copy a {} with capacity {} into one with capacity {}",
+                            TypeSymbol::String,
+                            other_capacity,
+                            self.capacity
+                        ),
+                    ));
+                }
+
+                //? Clear the chars the other string can't reach
+                if other_capacity < self.capacity {
+                    CopyInstruction::new_alloc(
+                        program_memory,
+                        &Address::Immediate(vec![0; (self.capacity - other_capacity) * CHAR_SIZE]),
+                        &StringType::get_chars_address(self)?
+                            .offset_direct(other_capacity * CHAR_SIZE)?,
+                        (self.capacity - other_capacity) * CHAR_SIZE,
+                    );
+                }
+
+                Ok(CopyInstruction::new_alloc(
+                    program_memory,
+                    other.get_address(),
+                    self.address.as_ref().unwrap(),
+                    other.get_length(),
+                ))
+            }
+            s => Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Copy not implemented from type '{}' to '{}'",
+                    s,
+                    TypeSymbol::String
+                ),
+            )),
+        }
+    }
+
+    fn runtime_copy_from_literal(
+        &self,
+        literal: &Literal,
+        program_memory: &mut MemoryManager,
+    ) -> CompileResult<CopyInstruction> {
+        let constant = self.get_constant(literal)?;
+
+        Ok(CopyInstruction::new_alloc(
+            program_memory,
+            &constant,
+            self.address.as_ref().unwrap(),
+            self.get_length(),
+        ))
+    }
+
+    default_type_operate_impl!(StringType);
+
+    fn get_indexed(
+        &self,
+        index: &dyn Type,
+        program_memory: &mut MemoryManager,
+        _stack_sizes: &mut StackSizes,
+    ) -> CompileResult<Box<dyn Type>> {
+        if index.get_type_symbol() != TypeSymbol::Integer {
+            return Err(CompileError::new(
+                ErrorCode::TypeMismatch,
+                format!(
+                    "Strings must be indexed with an {} - received {}",
+                    TypeSymbol::Integer,
+                    index.get_type_symbol()
+                ),
+            ));
+        }
+
+        let Address::StackDirect(chars_address) = StringType::get_chars_address(self)? else {
+            return Err("Only strings on the stack can be indexed"
+                .to_string()
+                .into());
+        };
+
+        //? Only chars within the length can be read or written so the length stays correct.
+        //? Assigning a new string changes the length
+        IndexCheckInstruction::new_alloc(
+            program_memory,
+            index.get_address(),
+            self.get_address(),
+            INTEGER_SIZE,
+        );
+
+        Ok(bx!(CharType::new_with_address(Address::StackIndexed(
+            CloneableBox::new(Address::Immediate(Vec::from(chars_address.to_le_bytes()))),
+            CloneableBox::new(index.get_address().clone()),
+        ))))
+    }
+
+    fn get_fields(&self) -> CompileResult<Fields> {
+        let mut length = IntegerType::new();
+        length.set_address(self.get_address().clone());
+        Ok(vec![(LENGTH_FIELD.to_string(), bx!(length))])
+    }
+
+    fn get_list_items(&self) -> CompileResult<Vec<Box<dyn Type>>> {
+        //? Strings are initialised from literals, not from their length
+        Ok(Vec::new())
+    }
+
+    fn get_address(&self) -> &Address {
+        self.address.as_ref().unwrap()
+    }
+
+    fn get_length(&self) -> usize {
+        USIZE_BYTES + self.capacity * CHAR_SIZE
+    }
+
+    fn get_address_mut(&mut self) -> &mut Address {
+        self.address.as_mut().unwrap()
+    }
+
+    fn set_address(&mut self, address: Address) {
+        self.address = Some(address);
+    }
+
+    fn duplicate(&self) -> Box<dyn Type> {
+        let mut t = StringType::with_capacity(self.capacity);
+        t.address = self.address.as_ref().cloned();
+        bx!(t)
+    }
+}
+
+/// Writes whether `lhs` and `rhs` hold the same text into `destination`
+fn string_equality(
+    lhs: &StringType,
+    rhs: &dyn Type,
+    destination: &dyn Type,
+    program_memory: &mut MemoryManager,
+    stack_sizes: &mut StackSizes,
+) -> CompileResult<()> {
+    //? Lengths must match
+    EqualityInstruction::new_alloc(
+        program_memory,
+        lhs.get_address(),
+        rhs.get_address(),
+        destination.get_address(),
+        USIZE_BYTES,
+    );
+
+    //? Unused chars are zeroed so only the shared capacity needs comparing
+    let mut chars_equal = BoolType::new();
+    chars_equal.allocate_variable(stack_sizes, program_memory)?;
+    EqualityInstruction::new_alloc(
+        program_memory,
+        &StringType::get_chars_address(lhs)?,
+        &StringType::get_chars_address(rhs)?,
+        chars_equal.get_address(),
+        lhs.capacity.min(StringType::capacity_of(rhs)) * CHAR_SIZE,
+    );
+
+    BinaryAndInstruction::new_alloc(
+        program_memory,
+        destination.get_address(),
+        chars_equal.get_address(),
+        destination.get_address(),
+        BOOLEAN_SIZE,
+    );
+    Ok(())
+}
+
+pub struct StrEqual {}
+
+impl Operation<StringType> for StrEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::Equal
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::String => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &StringType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::String);
+
+        string_equality(lhs, rhs, destination, program_memory, stack_sizes)
+    }
+}
+
+pub struct StrNotEqual {}
+
+impl Operation<StringType> for StrNotEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::NotEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::String => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &StringType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::String);
+
+        string_equality(lhs, rhs, destination, program_memory, stack_sizes)?;
+        BinaryNotInstruction::new_alloc(
+            program_memory,
+            destination.get_address(),
+            destination.get_address(),
+            BOOLEAN_SIZE,
+        );
+        Ok(())
+    }
+}
+
+/// Writes whether `lhs` comes before `rhs` in dictionary order into `destination`
+fn string_less(
+    lhs: &dyn Type,
+    rhs: &dyn Type,
+    destination: &dyn Type,
+    program_memory: &mut MemoryManager,
+    stack_sizes: &mut StackSizes,
+) -> CompileResult<()> {
+    let lhs_chars = StringType::get_chars_address(lhs)?;
+    let rhs_chars = StringType::get_chars_address(rhs)?;
+    //? The string with the smaller capacity fits entirely within the shared capacity so if the
+    //? shared chars are equal, the shorter string comes first
+    let shared_size = StringType::capacity_of(lhs).min(StringType::capacity_of(rhs)) * CHAR_SIZE;
+
+    StringLessInstruction::new_alloc(
+        program_memory,
+        &lhs_chars,
+        &rhs_chars,
+        destination.get_address(),
+        shared_size,
+    );
+
+    let mut chars_equal = BoolType::new();
+    chars_equal.allocate_variable(stack_sizes, program_memory)?;
+    EqualityInstruction::new_alloc(
+        program_memory,
+        &lhs_chars,
+        &rhs_chars,
+        chars_equal.get_address(),
+        shared_size,
+    );
+
+    let mut shorter = BoolType::new();
+    shorter.allocate_variable(stack_sizes, program_memory)?;
+    LessInstruction::new_alloc(
+        program_memory,
+        lhs.get_address(),
+        rhs.get_address(),
+        shorter.get_address(),
+        USIZE_BYTES,
+        false,
+    );
+
+    BinaryAndInstruction::new_alloc(
+        program_memory,
+        chars_equal.get_address(),
+        shorter.get_address(),
+        chars_equal.get_address(),
+        BOOLEAN_SIZE,
+    );
+    BinaryOrInstruction::new_alloc(
+        program_memory,
+        destination.get_address(),
+        chars_equal.get_address(),
+        destination.get_address(),
+        BOOLEAN_SIZE,
+    );
+    Ok(())
+}
+
+pub struct StrLess {}
+
+impl Operation<StringType> for StrLess {
+    fn get_symbol(&self) -> Operator {
+        Operator::Less
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::String => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &StringType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::String);
+
+        string_less(lhs, rhs, destination, program_memory, stack_sizes)
+    }
+}
+
+pub struct StrGreater {}
+
+impl Operation<StringType> for StrGreater {
+    fn get_symbol(&self) -> Operator {
+        Operator::Greater
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::String => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &StringType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::String);
+        //? a > b is equivalent to b < a
+        string_less(rhs, lhs, destination, program_memory, stack_sizes)
+    }
+}
+
+pub struct StrLessEqual {}
+
+impl Operation<StringType> for StrLessEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::LessEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::String => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &StringType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::String);
+        //? a <= b is equivalent to !(b < a)
+        string_less(rhs, lhs, destination, program_memory, stack_sizes)?;
+        BinaryNotInstruction::new_alloc(
+            program_memory,
+            destination.get_address(),
+            destination.get_address(),
+            BOOLEAN_SIZE,
+        );
+        Ok(())
+    }
+}
+
+pub struct StrGreaterEqual {}
+
+impl Operation<StringType> for StrGreaterEqual {
+    fn get_symbol(&self) -> Operator {
+        Operator::GreaterEqual
+    }
+
+    fn get_result_type(&self, rhs: &TypeSymbol) -> Option<TypeSymbol> {
+        match rhs {
+            TypeSymbol::String => Some(TypeSymbol::Boolean),
+            _ => None,
+        }
+    }
+
+    fn operate(
+        &self,
+        lhs: &StringType,
+        rhs: &dyn Type,
+        destination: &dyn Type,
+        program_memory: &mut MemoryManager,
+        stack_sizes: &mut StackSizes,
+    ) -> CompileResult<()> {
+        assert_eq!(destination.get_type_symbol(), TypeSymbol::Boolean);
+        assert_eq!(rhs.get_type_symbol(), TypeSymbol::String);
+        //? a >= b is equivalent to !(a < b)
+        string_less(lhs, rhs, destination, program_memory, stack_sizes)?;
+        BinaryNotInstruction::new_alloc(
+            program_memory,
+            destination.get_address(),
+            destination.get_address(),
+            BOOLEAN_SIZE,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execution::RuntimeErrorKind;
+    use crate::test_util::{compile_err, compile_ok, get_output, run};
+
+    #[test]
+    fn indexes_within_length() {
+        assert_eq!(
+            get_output(
+                "str[4] s = \"hi\"\nprintc s[1]\ns[1] = 'o'\nprint s\nprint s == \"ho\"\nprint s.length\n"
+            ),
+            "iho\ntrue\n2\n"
+        );
+    }
+
+    #[test]
+    fn rejects_index_past_length() {
+        for (source, index) in [
+            ("char c = s[2]\n", 2),
+            ("s[3] = 'B'\n", 3),
+            ("int i = -1\nchar c = s[i]\n", -1),
+        ] {
+            let memory = compile_ok(&format!("str[4] s = \"hi\"\n{}", source));
+            assert!(matches!(
+                run(memory).unwrap_err().kind,
+                RuntimeErrorKind::IndexOutOfBounds(i, 2) if i == index
+            ));
+        }
+    }
+
+    #[test]
+    fn exposes_length() {
+        assert_eq!(
+            get_output(
+                "str[8] s = \"hi\"\nprint s.length\ns = \"hello\"\nprint s.length\nstr e = \"\"\nprint e.length\n"
+            ),
+            "2\n5\n0\n"
+        );
+    }
+
+    #[test]
+    fn rejects_length_assignment() {
+        let errors = compile_err("str s = \"hi\"\ns.length = 5\n");
+        assert_eq!(errors[0].message, "s.length is read-only");
+    }
+
+    #[test]
+    fn rejects_non_integer_index() {
+        let errors = compile_err("str[4] s = \"hi\"\nptr i = 1\nchar c = s[i]\n");
+        assert!(errors[0].message.contains("Strings must be indexed"));
+    }
+
+    #[test]
+    fn compares_in_dictionary_order() {
+        let cases = [
+            ("abc", 4, "abd", 8),
+            ("abd", 8, "abc", 4),
+            ("ab", 2, "abc", 8),
+            ("abc", 8, "ab", 2),
+            ("", 4, "a", 4),
+            ("b", 4, "abc", 4),
+            ("abc", 4, "abc", 8),
+        ];
+
+        let mut source = String::new();
+        let mut expected_output = String::new();
+        for (i, (lhs, lhs_capacity, rhs, rhs_capacity)) in cases.into_iter().enumerate() {
+            let name = (b'a' + i as u8) as char;
+            source += &format!("str[{}] l{} = \"{}\"\n", lhs_capacity, name, lhs);
+            source += &format!("str[{}] r{} = \"{}\"\n", rhs_capacity, name, rhs);
+            for (operator, expected) in [
+                ("<", lhs < rhs),
+                (">", lhs > rhs),
+                ("<=", lhs <= rhs),
+                (">=", lhs >= rhs),
+            ] {
+                source += &format!("print (l{} {} r{})\n", name, operator, name);
+                expected_output += &format!("{}\n", expected);
+            }
+        }
+
+        assert_eq!(get_output(&source), expected_output);
+    }
+}
@@ -0,0 +1,112 @@
+//! Helpers for compiling and running programs in tests
+
+use crate::errors::{format_diagnostics, Diagnostic};
+use crate::execution::{execute, RuntimeError};
+use crate::memory::{MemoryManager, RuntimeMemoryManager};
+use crate::processing::optimiser::{optimise, OptimisationLevel};
+use crate::processing::preprocessor::{convert_to_symbols, SymbolData};
+use crate::processing::processor::process_symbols;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Creates an empty directory for the files of a test program
+fn create_program_directory() -> PathBuf {
+    //? Tests run in parallel so each program needs its own directory
+    static NEXT_DIRECTORY: AtomicUsize = AtomicUsize::new(0);
+    let directory = std::env::temp_dir().join(format!(
+        "whython-test-{}-{}",
+        std::process::id(),
+        NEXT_DIRECTORY.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+/// Compiles `source` as the main file of a program
+pub fn compile(source: &str) -> Result<MemoryManager, Vec<Diagnostic>> {
+    compile_files(&[("main.why", source)], &[])
+}
+
+/// Compiles a program made of `files` (paths relative to the program's directory and their
+/// sources), the first of which is the main file. `library_path` is relative to the program's
+/// directory
+pub fn compile_files(
+    files: &[(&str, &str)],
+    library_path: &[&str],
+) -> Result<MemoryManager, Vec<Diagnostic>> {
+    let directory = create_program_directory();
+    for (path, source) in files {
+        let path = directory.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+    let library_path: Vec<PathBuf> = library_path.iter().map(|p| directory.join(p)).collect();
+
+    let mut symbol_data = SymbolData::new();
+    let result = convert_to_symbols(
+        directory.join(files[0].0).to_string_lossy().to_string(),
+        &library_path,
+        &mut symbol_data,
+    )
+    .and_then(|_| process_symbols(symbol_data));
+    fs::remove_dir_all(&directory).ok();
+    result
+}
+
+/// Compiles `source`, panicking with the rendered errors if it fails
+pub fn compile_ok(source: &str) -> MemoryManager {
+    compile(source).unwrap_or_else(|e| panic!("{}", format_diagnostics(&e)))
+}
+
+/// Compiles `source`, panicking if it succeeds
+pub fn compile_err(source: &str) -> Vec<Diagnostic> {
+    match compile(source) {
+        Ok(_) => panic!("Expected compilation to fail"),
+        Err(e) => e,
+    }
+}
+
+/// Executes a compiled program
+pub fn run(memory: MemoryManager) -> Result<(), RuntimeError> {
+    let mut runtime_memory = RuntimeMemoryManager::from_program_memory(memory);
+    execute(&mut runtime_memory, &AtomicBool::new(false))
+}
+
+/// Executes a compiled program with `input` as its lines of input, returning its result along
+/// with everything it printed
+pub fn run_captured(
+    memory: MemoryManager,
+    garbage_collection: bool,
+    input: &[&str],
+) -> (Result<(), RuntimeError>, String) {
+    let mut runtime_memory = RuntimeMemoryManager::from_program_memory(memory);
+    runtime_memory.capture_output();
+    runtime_memory.provide_input(input);
+    if garbage_collection {
+        runtime_memory.enable_garbage_collector();
+    }
+    let result = execute(&mut runtime_memory, &AtomicBool::new(false));
+    (result, runtime_memory.take_output().unwrap())
+}
+
+/// Compiles and runs `source` both unoptimised and with `-O1`, checking that they print the
+/// same thing, and returns what was printed
+pub fn get_output(source: &str) -> String {
+    let (result, output) = run_captured(compile_ok(source), false, &[]);
+    result.unwrap_or_else(|e| panic!("{}\noutput: {:?}", e, output));
+
+    let mut optimised = compile_ok(source);
+    optimise(&mut optimised, OptimisationLevel::Basic).unwrap();
+    let (result, optimised_output) = run_captured(optimised, false, &[]);
+    result.unwrap_or_else(|e| panic!("-O1: {}\noutput: {:?}", e, optimised_output));
+    assert_eq!(output, optimised_output, "-O1 changed the output");
+    output
+}
+
+/// Compiles and runs `source` with the garbage collector enabled and returns what was printed
+pub fn get_gc_output(source: &str) -> String {
+    let (result, output) = run_captured(compile_ok(source), true, &[]);
+    result.unwrap_or_else(|e| panic!("{}\noutput: {:?}", e, output));
+    output
+}
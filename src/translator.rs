@@ -1,92 +1,133 @@
-use crate::processing::instructions::add_instruction_13::{AddInstruction, ADD_INSTRUCTION_CODE};
-use crate::processing::instructions::binary_and_8::{
-    BinaryAndInstruction, BINARY_AND_INSTRUCTION_CODE,
+use crate::memory::DebugInfo;
+use crate::processing::instructions::jump_if_not_9::JUMP_IF_NOT_INSTRUCTION_CODE;
+use crate::processing::instructions::jump_instruction_10::JUMP_INSTRUCTION_CODE;
+use crate::processing::instructions::{
+    debug_instruction, format_jump_target, InstructionCodeType, INSTRUCTION_CODE_LENGTH,
 };
-use crate::processing::instructions::binary_not_7::{
-    BinaryNotInstruction, BINARY_NOT_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::binary_or_12::{
-    BinaryOrInstruction, BINARY_OR_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::copy_3::{CopyInstruction, COPY_INSTRUCTION_CODE};
-use crate::processing::instructions::dump_5::{DumpInstruction, DUMP_INSTRUCTION_CODE};
-use crate::processing::instructions::dynamic_jump_11::{
-    DynamicJumpInstruction, DYNAMIC_JUMP_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::equality_14::{
-    EqualityInstruction, EQUALITY_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::heap_alloc_2::{
-    HeapAllocInstruction, HEAP_ALLOC_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::jump_if_not_9::{
-    JumpIfNotInstruction, JUMP_IF_NOT_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::jump_instruction_10::{
-    JumpInstruction, JUMP_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::not_equal_15::{
-    NotEqualInstruction, NOT_EQUAL_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::stack_create_0::StackCreateInstruction;
-use crate::processing::instructions::stack_create_0::STACK_CREATE_INSTRUCTION_CODE;
-use crate::processing::instructions::stack_down_4::{
-    StackDownInstruction, STACK_DOWN_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::stack_up_1::{StackUpInstruction, STACK_UP_INSTRUCTION_CODE};
-use crate::processing::instructions::view_memory_6::{
-    ViewMemoryInstruction, VIEW_MEMORY_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::view_memory_dec_16::{
-    ViewMemoryDecInstruction, VIEW_MEMORY_DEC_INSTRUCTION_CODE,
-};
-use crate::processing::instructions::{InstructionCodeType, INSTRUCTION_CODE_LENGTH};
+use crate::util::{get_usize, USIZE_BYTES};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Decodes the instruction starting at `address`
+///
+/// Returns the instruction with its operands and the position of the next instruction
+pub fn translate_instruction(data: &[u8], address: usize) -> Result<(String, usize), String> {
+    let mut i = address;
+    let code = data
+        .get(i..i + INSTRUCTION_CODE_LENGTH)
+        .ok_or_else(|| "Instruction code runs past the end of memory".to_string())?;
+    i += INSTRUCTION_CODE_LENGTH;
 
-macro_rules! translate {
-    ($instruction: ident, $data: expr, $i: expr) => {
-        $instruction::get_debug(&$data, &mut $i)
-    };
+    let code = InstructionCodeType::from_le_bytes(code.try_into().unwrap());
+    let output = debug_instruction(code, data, &mut i)
+        .unwrap_or_else(|| Err(format!("Unknown instruction code [{}]", code)))?;
+
+    Ok((output, i))
 }
 
-/// Prints the instructions and their data in the given memory
-pub fn translate(data: &[u8], translate_one: bool) {
-    println!("<------------------------------>");
-    let mut i: usize = 0;
+/// Returns the destination of the instruction at `address` if it is a jump to a fixed position
+fn get_jump_target(data: &[u8], address: usize) -> Option<usize> {
+    let code = InstructionCodeType::from_le_bytes(
+        data.get(address..address + INSTRUCTION_CODE_LENGTH)?
+            .try_into()
+            .unwrap(),
+    );
+    match code {
+        //? Destination is the first operand of both jumps
+        JUMP_INSTRUCTION_CODE | JUMP_IF_NOT_INSTRUCTION_CODE => {
+            data.get(
+                address + INSTRUCTION_CODE_LENGTH..address + INSTRUCTION_CODE_LENGTH + USIZE_BYTES,
+            )?;
+            Some(get_usize(&mut (address + INSTRUCTION_CODE_LENGTH), data))
+        }
+        _ => None,
+    }
+}
+
+/// Decodes all instructions in the given memory along with their operands. Jump targets are
+/// shown as labels. Function entry points and the source line each instruction was compiled
+/// from are shown if `debug_info` has them
+///
+/// Decoding stops at the first unknown instruction (as its size can't be known) and the
+/// remaining bytes are shown as raw data
+pub fn disassemble(data: &[u8], entry_point: usize, debug_info: &DebugInfo) -> String {
+    //? Decode first so that labels can be placed before the instructions they refer to
+    let mut instructions = Vec::new();
+    let mut failure = None;
+    let mut i = 0;
     while i < data.len() {
-        print!("[{:0>5}] | ", i);
+        match translate_instruction(data, i) {
+            Ok((output, next)) => {
+                instructions.push((i, output));
+                i = next;
+            }
+            Err(e) => {
+                failure = Some((i, e));
+                break;
+            }
+        }
+    }
 
-        let code = &data[i..i + INSTRUCTION_CODE_LENGTH];
-        i += INSTRUCTION_CODE_LENGTH;
+    let labels: BTreeMap<usize, String> = instructions
+        .iter()
+        .filter_map(|(address, _)| get_jump_target(data, *address))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .enumerate()
+        .map(|(n, target)| (target, format!("L{}", n)))
+        .collect();
 
-        let output = match InstructionCodeType::from_le_bytes(code.try_into().unwrap()) {
-            STACK_CREATE_INSTRUCTION_CODE => translate!(StackCreateInstruction, data, i),
-            STACK_UP_INSTRUCTION_CODE => translate!(StackUpInstruction, data, i),
-            HEAP_ALLOC_INSTRUCTION_CODE => translate!(HeapAllocInstruction, data, i),
-            COPY_INSTRUCTION_CODE => translate!(CopyInstruction, data, i),
-            STACK_DOWN_INSTRUCTION_CODE => translate!(StackDownInstruction, data, i),
-            DUMP_INSTRUCTION_CODE => translate!(DumpInstruction, data, i),
-            VIEW_MEMORY_INSTRUCTION_CODE => translate!(ViewMemoryInstruction, data, i),
-            BINARY_NOT_INSTRUCTION_CODE => translate!(BinaryNotInstruction, data, i),
-            BINARY_AND_INSTRUCTION_CODE => translate!(BinaryAndInstruction, data, i),
-            JUMP_IF_NOT_INSTRUCTION_CODE => translate!(JumpIfNotInstruction, data, i),
-            JUMP_INSTRUCTION_CODE => translate!(JumpInstruction, data, i),
-            DYNAMIC_JUMP_INSTRUCTION_CODE => translate!(DynamicJumpInstruction, data, i),
-            BINARY_OR_INSTRUCTION_CODE => translate!(BinaryOrInstruction, data, i),
-            ADD_INSTRUCTION_CODE => translate!(AddInstruction, data, i),
-            EQUALITY_INSTRUCTION_CODE => translate!(EqualityInstruction, data, i),
-            NOT_EQUAL_INSTRUCTION_CODE => translate!(NotEqualInstruction, data, i),
-            VIEW_MEMORY_DEC_INSTRUCTION_CODE => translate!(ViewMemoryDecInstruction, data, i),
-            code => {
-                println!("Debug not implemented for code {}. Terminating translation due to unknown instruction size.", code);
-                return;
-            }
-        };
+    let mut output = String::new();
+    writeln!(output, "<------------------------------>").unwrap();
+    for (address, instruction) in &instructions {
+        if *address == entry_point {
+            writeln!(output, "entry:").unwrap();
+        }
+        if let Some(function) = debug_info.get_function_at_entry(*address) {
+            writeln!(output, "fn {}:", function.name).unwrap();
+        }
+        if let Some(label) = labels.get(address) {
+            writeln!(output, "{}:", label).unwrap();
+        }
+        if let Some(line) = debug_info
+            .get_line(*address)
+            .filter(|line| line.offset == *address)
+        {
+            writeln!(
+                output,
+                "        ; {} | {}",
+                debug_info.format_line(line),
+                line.source.trim()
+            )
+            .unwrap();
+        }
 
-        println!("{}", output);
+        let mut instruction = instruction.clone();
+        if let Some(target) = get_jump_target(data, *address) {
+            instruction = instruction.replace(
+                &format_jump_target(target),
+                &format!("{} ({})", labels[&target], format_jump_target(target)),
+            );
+        }
+        writeln!(output, "[{:0>5}] | {}", address, instruction).unwrap();
+    }
 
-        if translate_one {
-            break;
+    if let Some((address, e)) = failure {
+        writeln!(output, "[{:0>5}] | Decoding failed: {}", address, e).unwrap();
+        for (row, chunk) in data[address..].chunks(16).enumerate() {
+            write!(output, "[{:0>5}] | .data", address + row * 16).unwrap();
+            for byte in chunk {
+                write!(output, " {:02X}", byte).unwrap();
+            }
+            writeln!(output).unwrap();
         }
     }
-    println!("<------------------------------>");
+    //? Jumps to the end of the program terminate it
+    else if let Some(label) = labels.get(&data.len()) {
+        writeln!(output, "{}:", label).unwrap();
+        writeln!(output, "[{:0>5}] | <end>", data.len()).unwrap();
+    }
+    write!(output, "<------------------------------>").unwrap();
+
+    output
 }
@@ -3,8 +3,6 @@ pub mod ref_or_box;
 
 use std::io::{stdin, stdout, Read, Write};
 
-#[cfg(target_pointer_width = "128")]
-pub const USIZE_BYTES: usize = 16;
 #[cfg(target_pointer_width = "64")]
 pub const USIZE_BYTES: usize = 8;
 #[cfg(target_pointer_width = "32")]
@@ -12,7 +10,7 @@ pub const USIZE_BYTES: usize = 4;
 #[cfg(target_pointer_width = "16")]
 pub const USIZE_BYTES: usize = 2;
 
-/// Gets a `u8` from `memory` at the pointer
+// /// Gets a `u8` from `memory` at the pointer
 // pub fn get_u8(pointer: &usize, memory: &[u8]) -> u8 {
 //     u8::from_le_bytes((&memory[*pointer..(*pointer + 1)]).try_into().unwrap())
 // }
@@ -30,6 +28,21 @@ pub fn get_usize(pointer: &mut usize, memory: &[u8]) -> usize {
     u
 }
 
+/// Interprets up to 16 little-endian bytes as an unsigned integer
+pub fn get_u128(data: &[u8]) -> u128 {
+    let mut full = [0; 16];
+    full[..data.len()].copy_from_slice(data);
+    u128::from_le_bytes(full)
+}
+
+/// Interprets up to 16 little-endian bytes as a two's complement integer, extending the sign
+pub fn get_i128(data: &[u8]) -> i128 {
+    let negative = data.last().is_some_and(|b| b & 0x80 != 0);
+    let mut full = if negative { [0xFF; 16] } else { [0; 16] };
+    full[..data.len()].copy_from_slice(data);
+    i128::from_le_bytes(full)
+}
+
 // /// Evaluates to the `Ok` value or returns `Err(e)`
 // #[macro_export] macro_rules! propagate_error {
 //     ($result: expr) => {